@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::result;
+
+use crate::OwnedPetrinet;
+
+/// Error returned by `NetBuilder::build`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// A place with this name was already declared.
+    DuplicatePlace(String),
+    /// A transition with this name was already declared.
+    DuplicateTransition(String),
+    /// An arc refers to a place which was not declared.
+    UnknownPlace(String),
+    /// An arc refers to a transition which was not declared.
+    UnknownTransition(String),
+    /// An arc between the place and the transition has weight 0.
+    ZeroWeight { place: String, transition: String },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicatePlace(name) => write!(f, "place {:?} is declared twice", name),
+            Self::DuplicateTransition(name) => {
+                write!(f, "transition {:?} is declared twice", name)
+            }
+            Self::UnknownPlace(name) => write!(f, "place {:?} is not declared", name),
+            Self::UnknownTransition(name) => write!(f, "transition {:?} is not declared", name),
+            Self::ZeroWeight { place, transition } => write!(
+                f,
+                "arc between place {:?} and transition {:?} has weight 0",
+                place, transition
+            ),
+        }
+    }
+}
+
+impl error::Error for BuildError {}
+
+/// An arc declared with a `NetBuilder`, resolved by `build`.
+#[derive(Debug)]
+struct PendingArc {
+    place: String,
+    transition: String,
+    weight: u32,
+    input: bool,
+}
+
+/// Build an `OwnedPetrinet`, referring to places and transitions by name.
+///
+/// The builder methods can be chained. Arcs may refer to places and
+/// transitions which are declared later on, as names are only resolved by
+/// `build`, in the order the arcs were declared. Errors found while
+/// declaring, like duplicate names, are reported before errors found while
+/// resolving. Only the first error is reported, later calls after an error
+/// are ignored.
+#[derive(Debug, Default)]
+pub struct NetBuilder {
+    net: OwnedPetrinet,
+    places: HashMap<String, usize>,
+    transitions: HashMap<String, usize>,
+    arcs: Vec<PendingArc>,
+    error: Option<BuildError>,
+}
+
+impl NetBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&mut self, f: impl FnOnce(&mut Self) -> result::Result<(), BuildError>) -> &mut Self {
+        if self.error.is_none() {
+            if let Err(e) = f(self) {
+                self.error = Some(e);
+            }
+        }
+
+        self
+    }
+
+    /// Declare a place holding `tokens` tokens.
+    pub fn place(&mut self, name: &str, tokens: u32) -> &mut Self {
+        self.record(|builder| {
+            if builder.places.contains_key(name) {
+                return Err(BuildError::DuplicatePlace(name.to_string()));
+            }
+
+            let place = builder.net.add_place(tokens, name);
+            builder.places.insert(name.to_string(), place);
+            Ok(())
+        })
+    }
+
+    /// Declare a transition.
+    pub fn transition(&mut self, name: &str) -> &mut Self {
+        self.record(|builder| {
+            if builder.transitions.contains_key(name) {
+                return Err(BuildError::DuplicateTransition(name.to_string()));
+            }
+
+            let transition = builder.net.add_transition(Some(name));
+            builder.transitions.insert(name.to_string(), transition);
+            Ok(())
+        })
+    }
+
+    fn arc(&mut self, place: &str, transition: &str, weight: u32, input: bool) -> &mut Self {
+        self.record(|builder| {
+            if weight < 1 {
+                return Err(BuildError::ZeroWeight {
+                    place: place.to_string(),
+                    transition: transition.to_string(),
+                });
+            }
+
+            builder.arcs.push(PendingArc {
+                place: place.to_string(),
+                transition: transition.to_string(),
+                weight,
+                input,
+            });
+            Ok(())
+        })
+    }
+
+    /// Add an arc from `place` to `transition`.
+    pub fn input(&mut self, place: &str, transition: &str, weight: u32) -> &mut Self {
+        self.arc(place, transition, weight, true)
+    }
+
+    /// Add an arc from `transition` to `place`.
+    pub fn output(&mut self, transition: &str, place: &str, weight: u32) -> &mut Self {
+        self.arc(place, transition, weight, false)
+    }
+
+    /// Finish building, resolving the names of all arcs. Returns the first
+    /// error if any occurred.
+    pub fn build(mut self) -> result::Result<OwnedPetrinet, BuildError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        for arc in &self.arcs {
+            let p = *self
+                .places
+                .get(&arc.place)
+                .ok_or_else(|| BuildError::UnknownPlace(arc.place.clone()))?;
+            let t = *self
+                .transitions
+                .get(&arc.transition)
+                .ok_or_else(|| BuildError::UnknownTransition(arc.transition.clone()))?;
+
+            if arc.input {
+                self.net.add_input(t, p, arc.weight).expect("valid arc");
+            } else {
+                self.net.add_output(t, p, arc.weight).expect("valid arc");
+            }
+        }
+
+        Ok(self.net)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_net_builder() {
+        let mut builder = NetBuilder::new();
+        builder
+            .place("p1", 2)
+            .place("p2", 2)
+            .place("p3", 0)
+            .transition("t1")
+            .input("p1", "t1", 2)
+            .input("p2", "t1", 1)
+            .output("t1", "p3", 2);
+        let mut net = builder.build().expect("valid net");
+
+        assert_eq!(Some("t1"), net.transitions()[0].name());
+        assert!(net.step());
+        assert_eq!(vec![0, 1, 2], net.marking());
+    }
+
+    #[test]
+    fn test_net_builder_forward_references() {
+        let mut builder = NetBuilder::new();
+        builder
+            .input("p1", "t1", 1)
+            .output("t1", "p2", 1)
+            .transition("t1")
+            .place("p2", 0)
+            .place("p1", 1);
+        let mut net = builder.build().expect("valid net");
+
+        assert_eq!("p2", net.places()[0].name());
+        assert_eq!(1, net.transitions()[0].inputs()[0].place());
+        assert!(net.step());
+        assert_eq!(vec![1, 0], net.marking());
+    }
+
+    #[test]
+    fn test_net_builder_errors() {
+        let build = |f: fn(&mut NetBuilder)| {
+            let mut builder = NetBuilder::new();
+            builder.place("p", 1).transition("t");
+            f(&mut builder);
+            builder.build().map(|_| ())
+        };
+
+        assert_eq!(
+            Err(BuildError::DuplicatePlace("p".to_string())),
+            build(|b| {
+                b.place("p", 0);
+            })
+        );
+        assert_eq!(
+            Err(BuildError::DuplicateTransition("t".to_string())),
+            build(|b| {
+                b.transition("t");
+            })
+        );
+        assert_eq!(
+            Err(BuildError::UnknownPlace("q".to_string())),
+            build(|b| {
+                b.input("q", "t", 1);
+            })
+        );
+        assert_eq!(
+            Err(BuildError::UnknownTransition("u".to_string())),
+            build(|b| {
+                b.output("u", "p", 1);
+            })
+        );
+        assert_eq!(
+            Err(BuildError::ZeroWeight {
+                place: "p".to_string(),
+                transition: "t".to_string()
+            }),
+            build(|b| {
+                b.input("p", "t", 0);
+            })
+        );
+        assert_eq!(
+            Err(BuildError::UnknownPlace("q".to_string())),
+            build(|b| {
+                b.input("q", "t", 1).input("r", "t", 1);
+            }),
+            "the first error is reported"
+        );
+        assert_eq!(
+            Err(BuildError::DuplicatePlace("p".to_string())),
+            build(|b| {
+                b.input("q", "t", 1).place("p", 0);
+            }),
+            "declaration errors come first"
+        );
+        assert_eq!(
+            "place \"q\" is not declared",
+            BuildError::UnknownPlace("q".to_string()).to_string()
+        );
+    }
+}
@@ -0,0 +1,526 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ptr;
+use std::result;
+
+use crate::{ArcError, FireError};
+
+/// A place whose tokens carry a color, a value of type `T`.
+///
+/// The tokens form a multiset: the place stores how many tokens of each
+/// color it holds, ordered by color, so colors are always visited in the
+/// same order.
+#[derive(Debug)]
+pub struct ColoredPlace<T: Ord> {
+    tokens: RefCell<BTreeMap<T, u32>>,
+    name: String,
+}
+
+impl<T: Ord + Clone> ColoredPlace<T> {
+    pub fn new(name: &str) -> Self {
+        Self {
+            tokens: RefCell::new(BTreeMap::new()),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of tokens of color `color`.
+    pub fn tokens(&self, color: &T) -> u32 {
+        self.tokens.borrow().get(color).copied().unwrap_or(0)
+    }
+
+    /// Total number of tokens of all colors, which may exceed `u32::MAX`.
+    pub fn token_count(&self) -> u64 {
+        self.tokens.borrow().values().copied().map(u64::from).sum()
+    }
+
+    /// Add `n` tokens of color `color`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token count of the color overflows.
+    pub fn add_tokens(&self, color: T, n: u32) {
+        let mut tokens = self.tokens.borrow_mut();
+        let count = tokens.entry(color).or_insert(0);
+        *count = count.checked_add(n).expect("token count overflow");
+    }
+
+    fn remove_tokens(&self, color: &T, n: u32) {
+        let mut tokens = self.tokens.borrow_mut();
+        let count = tokens.get_mut(color).expect("enough tokens of the color");
+        *count -= n;
+        if *count == 0 {
+            tokens.remove(color);
+        }
+    }
+}
+
+/// An arc inscribed with a constant color. It moves `weight` tokens of that
+/// color.
+#[derive(Debug)]
+pub struct ColoredArc<'a, T: Ord> {
+    place: &'a ColoredPlace<T>,
+    color: T,
+    weight: u32,
+}
+
+impl<'a, T: Ord + Clone> ColoredArc<'a, T> {
+    pub fn new(
+        place: &'a ColoredPlace<T>,
+        color: T,
+        weight: u32,
+    ) -> result::Result<Self, ArcError> {
+        if weight < 1 {
+            Err(ArcError::ZeroWeight)
+        } else {
+            Ok(Self {
+                place,
+                color,
+                weight,
+            })
+        }
+    }
+
+    pub fn color(&self) -> &T {
+        &self.color
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+}
+
+/// An input arc inscribed with a variable. Firing binds the variable to a
+/// color and moves `weight` tokens of that color, see
+/// `ColoredTransition::bindings`.
+#[derive(Debug)]
+pub struct ColoredVariableArc<'a, T: Ord> {
+    place: &'a ColoredPlace<T>,
+    variable: String,
+    weight: u32,
+}
+
+impl<'a, T: Ord + Clone> ColoredVariableArc<'a, T> {
+    pub fn new(
+        place: &'a ColoredPlace<T>,
+        variable: &str,
+        weight: u32,
+    ) -> result::Result<Self, ArcError> {
+        if weight < 1 {
+            Err(ArcError::ZeroWeight)
+        } else {
+            Ok(Self {
+                place,
+                variable: variable.to_string(),
+                weight,
+            })
+        }
+    }
+
+    pub fn variable(&self) -> &str {
+        &self.variable
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+}
+
+/// An output arc inscribed with an expression of a variable. Firing
+/// produces `weight` tokens of the color the expression maps the bound
+/// color to.
+pub struct ColoredExpressionArc<'a, T: Ord> {
+    place: &'a ColoredPlace<T>,
+    variable: String,
+    weight: u32,
+    expression: Box<dyn Fn(&T) -> T + 'a>,
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for ColoredExpressionArc<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColoredExpressionArc")
+            .field("place", &self.place)
+            .field("variable", &self.variable)
+            .field("weight", &self.weight)
+            .finish()
+    }
+}
+
+impl<'a, T: Ord + Clone> ColoredExpressionArc<'a, T> {
+    pub fn new(
+        place: &'a ColoredPlace<T>,
+        variable: &str,
+        weight: u32,
+        expression: impl Fn(&T) -> T + 'a,
+    ) -> result::Result<Self, ArcError> {
+        if weight < 1 {
+            Err(ArcError::ZeroWeight)
+        } else {
+            Ok(Self {
+                place,
+                variable: variable.to_string(),
+                weight,
+                expression: Box::new(expression),
+            })
+        }
+    }
+
+    pub fn variable(&self) -> &str {
+        &self.variable
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+}
+
+/// A transition of a colored net.
+///
+/// Besides arcs with constant colors, a transition may have arcs inscribed
+/// with a single variable: input arcs bind the variable to a color, and
+/// output arcs produce colors computed from it.
+#[derive(Debug)]
+pub struct ColoredTransition<'a, T: Ord> {
+    input_arcs: Vec<&'a ColoredArc<'a, T>>,
+    output_arcs: Vec<&'a ColoredArc<'a, T>>,
+    variable_input_arcs: Vec<&'a ColoredVariableArc<'a, T>>,
+    expression_output_arcs: Vec<&'a ColoredExpressionArc<'a, T>>,
+}
+
+impl<T: Ord> Default for ColoredTransition<'_, T> {
+    fn default() -> Self {
+        Self {
+            input_arcs: Vec::new(),
+            output_arcs: Vec::new(),
+            variable_input_arcs: Vec::new(),
+            expression_output_arcs: Vec::new(),
+        }
+    }
+}
+
+impl<'a, T: Ord + Clone> ColoredTransition<'a, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_input(&mut self, arc: &'a ColoredArc<'a, T>) {
+        self.input_arcs.push(arc);
+    }
+
+    pub fn add_output(&mut self, arc: &'a ColoredArc<'a, T>) {
+        self.output_arcs.push(arc);
+    }
+
+    /// The variable of the transition, if it has an arc inscribed with one.
+    pub fn variable(&self) -> Option<&str> {
+        self.variable_input_arcs
+            .iter()
+            .map(|arc| arc.variable())
+            .chain(self.expression_output_arcs.iter().map(|arc| arc.variable()))
+            .next()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the transition already has an arc inscribed with another
+    /// variable.
+    pub fn add_variable_input(&mut self, arc: &'a ColoredVariableArc<'a, T>) {
+        self.assert_variable(arc.variable());
+        self.variable_input_arcs.push(arc);
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the transition already has an arc inscribed with another
+    /// variable.
+    pub fn add_expression_output(&mut self, arc: &'a ColoredExpressionArc<'a, T>) {
+        self.assert_variable(arc.variable());
+        self.expression_output_arcs.push(arc);
+    }
+
+    fn assert_variable(&self, variable: &str) {
+        assert!(
+            self.variable().is_none_or(|v| v == variable),
+            "a transition can only have a single variable"
+        );
+    }
+
+    /// Whether the input places hold enough tokens for all input arcs, with
+    /// the variable bound to `binding`. Arcs on the same place and color add
+    /// up.
+    fn has_tokens(&self, binding: Option<&T>) -> bool {
+        let mut demand: Vec<(&ColoredPlace<T>, &T, u64)> = Vec::new();
+        let variable_arcs = self
+            .variable_input_arcs
+            .iter()
+            .filter_map(|arc| binding.map(|color| (arc.place, color, arc.weight)));
+        let arcs = self
+            .input_arcs
+            .iter()
+            .map(|arc| (arc.place, &arc.color, arc.weight))
+            .chain(variable_arcs);
+        for (place, color, weight) in arcs {
+            match demand
+                .iter_mut()
+                .find(|(p, c, _)| ptr::eq(*p, place) && *c == color)
+            {
+                Some((_, _, demand)) => *demand += u64::from(weight),
+                None => demand.push((place, color, u64::from(weight))),
+            }
+        }
+
+        demand
+            .iter()
+            .all(|&(place, color, weight)| u64::from(place.tokens(color)) >= weight)
+    }
+
+    /// The colors the variable can be bound to in the current marking, such
+    /// that the input places hold enough tokens for all input arcs, in
+    /// ascending order. Empty if no input arc binds the variable.
+    pub fn bindings(&self) -> Vec<T> {
+        let first = match self.variable_input_arcs.first() {
+            Some(arc) => arc,
+            None => return Vec::new(),
+        };
+
+        let candidates: Vec<T> = first.place.tokens.borrow().keys().cloned().collect();
+        candidates
+            .into_iter()
+            .filter(|color| self.has_tokens(Some(color)))
+            .collect()
+    }
+
+    /// A colored transition is enabled if every input place holds enough
+    /// tokens of the color of its arc. If the transition has a variable,
+    /// there must be a binding, see `bindings`, so a transition whose output
+    /// arcs use a variable no input arc binds is never enabled.
+    pub fn is_enabled(&self) -> bool {
+        match self.variable() {
+            Some(_) => !self.bindings().is_empty(),
+            None => self.has_tokens(None),
+        }
+    }
+
+    /// Consume the colored tokens of the input arcs and produce the colored
+    /// tokens of the output arcs. If the transition has a variable, it is
+    /// bound to the smallest of the `bindings`, use `fire_with_binding` to
+    /// choose another one.
+    ///
+    /// Fails with `FireError::Overflow` without touching any place if the
+    /// token count of a color would overflow.
+    pub fn fire(&mut self) -> result::Result<(), FireError> {
+        if self.variable().is_none() {
+            return self.fire_tokens(None);
+        }
+
+        match self.bindings().first() {
+            Some(binding) => self.fire_tokens(Some(binding)),
+            None => Err(FireError::NotEnabled),
+        }
+    }
+
+    /// Like `fire`, but bind the variable to `binding`. Fails if the input
+    /// places don't hold enough tokens for this binding, or the transition
+    /// has no variable input arc.
+    pub fn fire_with_binding(&mut self, binding: &T) -> result::Result<(), FireError> {
+        if self.variable_input_arcs.is_empty() {
+            return Err(FireError::NotEnabled);
+        }
+
+        self.fire_tokens(Some(binding))
+    }
+
+    /// The number of tokens of `color` the input arcs take from `place`, with
+    /// the variable bound to `binding`.
+    fn consumed(&self, place: &ColoredPlace<T>, color: &T, binding: Option<&T>) -> u64 {
+        let constant = self
+            .input_arcs
+            .iter()
+            .filter(|arc| ptr::eq(arc.place, place) && arc.color == *color)
+            .map(|arc| u64::from(arc.weight));
+        let variable = self
+            .variable_input_arcs
+            .iter()
+            .filter(|arc| ptr::eq(arc.place, place) && binding == Some(color))
+            .map(|arc| u64::from(arc.weight));
+        constant.chain(variable).sum()
+    }
+
+    fn fire_tokens(&self, binding: Option<&T>) -> result::Result<(), FireError> {
+        if !self.has_tokens(binding) {
+            return Err(FireError::NotEnabled);
+        }
+
+        let mut produced: Vec<(&ColoredPlace<T>, T, u64)> = Vec::new();
+        let constant = self
+            .output_arcs
+            .iter()
+            .map(|arc| (arc.place, arc.color.clone(), arc.weight));
+        let expressions = self.expression_output_arcs.iter().filter_map(|arc| {
+            binding.map(|binding| (arc.place, (arc.expression)(binding), arc.weight))
+        });
+        for (place, color, weight) in constant.chain(expressions) {
+            match produced
+                .iter_mut()
+                .find(|(p, c, _)| ptr::eq(*p, place) && *c == color)
+            {
+                Some((_, _, produced)) => *produced += u64::from(weight),
+                None => produced.push((place, color, u64::from(weight))),
+            }
+        }
+
+        let overflows = produced.iter().any(|(place, color, weight)| {
+            u64::from(place.tokens(color)) - self.consumed(place, color, binding) + weight
+                > u64::from(u32::MAX)
+        });
+        if overflows {
+            return Err(FireError::Overflow);
+        }
+
+        for arc in &self.input_arcs {
+            arc.place.remove_tokens(&arc.color, arc.weight);
+        }
+        if let Some(binding) = binding {
+            for arc in &self.variable_input_arcs {
+                arc.place.remove_tokens(binding, arc.weight);
+            }
+        }
+
+        for (place, color, weight) in produced {
+            place.add_tokens(color, weight as u32);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[test]
+    fn test_colored_firing() {
+        let p1 = ColoredPlace::new("p1");
+        let p2 = ColoredPlace::new("p2");
+        p1.add_tokens(Color::Red, 2);
+        p1.add_tokens(Color::Green, 1);
+
+        let a1 = ColoredArc::new(&p1, Color::Red, 2).expect("weight greater than zero");
+        let a2 = ColoredArc::new(&p2, Color::Green, 1).expect("weight greater than zero");
+        assert!(ColoredArc::new(&p1, Color::Red, 0).is_err());
+
+        let mut t = ColoredTransition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!(0, p1.tokens(&Color::Red));
+        assert_eq!(1, p1.tokens(&Color::Green));
+        assert_eq!(1, p2.tokens(&Color::Green));
+        assert_eq!(0, p2.tokens(&Color::Red));
+
+        assert!(!t.is_enabled(), "green tokens don't satisfy a red arc");
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+        assert_eq!(1, p1.token_count());
+    }
+
+    #[test]
+    fn test_colored_firing_overflow() {
+        let p1 = ColoredPlace::new("p1");
+        let p2 = ColoredPlace::new("p2");
+        p1.add_tokens(Color::Red, 1);
+        p2.add_tokens(Color::Green, u32::MAX);
+        p2.add_tokens(Color::Red, u32::MAX);
+
+        let a1 = ColoredArc::new(&p1, Color::Red, 1).expect("weight greater than zero");
+        let a2 = ColoredArc::new(&p2, Color::Green, 1).expect("weight greater than zero");
+        let mut t = ColoredTransition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        assert_eq!(Err(FireError::Overflow), t.fire());
+        assert_eq!(1, p1.tokens(&Color::Red), "no place changed");
+        assert_eq!(2 * u64::from(u32::MAX), p2.token_count());
+
+        // the tokens taken from a full color make room for the output
+        let a3 = ColoredArc::new(&p2, Color::Red, 1).expect("weight greater than zero");
+        let shift = ColoredExpressionArc::new(&p2, "x", 1, |x: &Color| x.clone())
+            .expect("weight greater than zero");
+        let take = ColoredVariableArc::new(&p2, "x", 1).expect("weight greater than zero");
+        let mut t = ColoredTransition::new();
+        t.add_variable_input(&take);
+        t.add_expression_output(&shift);
+        t.add_input(&a3);
+        t.add_output(&a3);
+        assert_eq!(Ok(()), t.fire_with_binding(&Color::Red));
+        assert_eq!(u32::MAX, p2.tokens(&Color::Red));
+    }
+
+    #[test]
+    fn test_colored_bindings() {
+        let numbers = ColoredPlace::new("numbers");
+        let squares = ColoredPlace::new("squares");
+        let pairs = ColoredPlace::new("pairs");
+        let fuel = ColoredPlace::new("fuel");
+        numbers.add_tokens(2u32, 1);
+        numbers.add_tokens(3, 2);
+        pairs.add_tokens(3, 1);
+        fuel.add_tokens(0, 1);
+
+        // square a number x, which needs a second token of x on pairs and a
+        // constant fuel token
+        let input = ColoredVariableArc::new(&numbers, "x", 1).expect("weight greater than zero");
+        let pair = ColoredVariableArc::new(&pairs, "x", 1).expect("weight greater than zero");
+        let fuel_arc = ColoredArc::new(&fuel, 0, 1).expect("weight greater than zero");
+        let square = ColoredExpressionArc::new(&squares, "x", 1, |x: &u32| x * x)
+            .expect("weight greater than zero");
+        assert!(ColoredVariableArc::new(&numbers, "x", 0).is_err());
+
+        let mut t = ColoredTransition::new();
+        t.add_variable_input(&input);
+        t.add_variable_input(&pair);
+        t.add_input(&fuel_arc);
+        t.add_expression_output(&square);
+        assert_eq!(Some("x"), t.variable());
+        assert_eq!(vec![3], t.bindings(), "only 3 is on pairs");
+        pairs.add_tokens(2, 1);
+        assert_eq!(vec![2, 3], t.bindings());
+        pairs.remove_tokens(&2, 1);
+        assert!(t.is_enabled());
+
+        assert_eq!(Err(FireError::NotEnabled), t.fire_with_binding(&2));
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!((1, 1), (numbers.tokens(&2), numbers.tokens(&3)));
+        assert_eq!(1, squares.tokens(&9));
+        assert_eq!(0, fuel.token_count());
+        assert!(!t.is_enabled(), "no binding left");
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+
+        // a transition whose output uses an unbound variable never fires
+        let mut unbound = ColoredTransition::new();
+        unbound.add_expression_output(&square);
+        assert!(!unbound.is_enabled());
+        assert_eq!(Err(FireError::NotEnabled), unbound.fire_with_binding(&2));
+    }
+
+    #[test]
+    #[should_panic(expected = "a transition can only have a single variable")]
+    fn test_colored_bindings_single_variable() {
+        let p = ColoredPlace::<u32>::new("p");
+        let x = ColoredVariableArc::new(&p, "x", 1).expect("weight greater than zero");
+        let y = ColoredVariableArc::new(&p, "y", 1).expect("weight greater than zero");
+        let mut t = ColoredTransition::new();
+        t.add_variable_input(&x);
+        t.add_variable_input(&y);
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::VecDeque;
+
+use crate::Petrinet;
+
+/// A marking which may hold ω, an unbounded number of tokens, on some
+/// places. `None` stands for ω.
+pub type OmegaMarking = Vec<Option<u32>>;
+
+/// Whether `a` is covered by `b`, with ω greater than any number.
+fn covered(a: &[Option<u32>], b: &[Option<u32>]) -> bool {
+    a.iter().zip(b).all(|(x, y)| match (x, y) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(x), Some(y)) => x <= y,
+    })
+}
+
+/// The Karp–Miller coverability tree of a net.
+///
+/// Node 0 is the root with the initial marking. Whenever a marking strictly
+/// covers the marking of one of its ancestors, the places which grew are set
+/// to ω, which makes the tree finite.
+#[derive(Debug, Clone, Default)]
+pub struct CoverabilityTree {
+    markings: Vec<OmegaMarking>,
+    parents: Vec<Option<usize>>,
+    edges: Vec<Vec<(usize, usize)>>,
+}
+
+impl CoverabilityTree {
+    fn add_node(&mut self, marking: OmegaMarking, parent: Option<usize>) -> usize {
+        self.markings.push(marking);
+        self.parents.push(parent);
+        self.edges.push(Vec::new());
+        self.markings.len() - 1
+    }
+
+    fn ancestors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.parents[node], move |&n| self.parents[n])
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.markings.len()
+    }
+
+    /// The marking of every node, indexed by node.
+    pub fn markings(&self) -> &[OmegaMarking] {
+        &self.markings
+    }
+
+    /// The parent of `node`, or `None` for the root.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.parents[node]
+    }
+
+    /// The outgoing edges of `node` as pairs of fired transition and child
+    /// node.
+    pub fn edges(&self, node: usize) -> &[(usize, usize)] {
+        &self.edges[node]
+    }
+
+    /// Whether the net is bounded, i.e. no ω occurs in the tree.
+    pub fn is_bounded(&self) -> bool {
+        self.markings
+            .iter()
+            .all(|marking| marking.iter().all(Option::is_some))
+    }
+
+    /// The maximum number of tokens of every place in any reachable marking,
+    /// or `None` if the place is unbounded.
+    pub fn bounds(&self) -> OmegaMarking {
+        let places = self.markings.first().map_or(0, Vec::len);
+        (0..places)
+            .map(|p| {
+                self.markings
+                    .iter()
+                    .map(|marking| marking[p])
+                    .try_fold(0, |max, tokens| tokens.map(|tokens| max.max(tokens)))
+            })
+            .collect()
+    }
+
+    /// Whether some reachable marking covers `target`, i.e. holds at least as
+    /// many tokens on every place.
+    pub fn covers(&self, target: &[u32]) -> bool {
+        let target: OmegaMarking = target.iter().copied().map(Some).collect();
+        self.markings
+            .iter()
+            .any(|marking| covered(&target, marking))
+    }
+}
+
+impl Petrinet<'_> {
+    /// The marking after firing `transition` in the ω-marking `marking`, or
+    /// `None` if the transition is not enabled or a token count would
+    /// overflow, like `TokenPolicy::Checked`.
+    fn fire_omega(&self, transition: usize, marking: &[Option<u32>]) -> Option<OmegaMarking> {
+        let transition = &self.transitions[transition];
+        let mut successor = marking.to_vec();
+
+        for (place, weight) in transition.weighted_inputs() {
+            let p = self.place_index(place).expect("registered place");
+            if let Some(tokens) = successor[p] {
+                successor[p] = Some(tokens.checked_sub(weight)?);
+            }
+        }
+
+        for arc in &transition.read_arcs {
+            let p = self.place_index(arc.place).expect("registered place");
+            if marking[p].is_some_and(|tokens| tokens < arc.weight) {
+                return None;
+            }
+        }
+
+        for (place, weight) in transition.weighted_outputs() {
+            let p = self.place_index(place).expect("registered place");
+            if let Some(tokens) = successor[p] {
+                successor[p] = Some(tokens.checked_add(weight)?);
+            }
+        }
+
+        Some(successor)
+    }
+
+    /// Build the Karp–Miller coverability tree from the current marking. The
+    /// tree is finite even for unbounded nets.
+    ///
+    /// Nodes whose marking equals the marking of an ancestor are not
+    /// expanded. The tree can grow very large, as the algorithm is not
+    /// primitive recursive in general.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a transition has an inhibitor, reset, zero-test, variable,
+    /// sampled or overflow arc, an expression or a guard, or if a place has a
+    /// capacity.
+    /// These break the monotonicity the algorithm relies on.
+    pub fn coverability_tree(&self) -> CoverabilityTree {
+        let monotonic = self.transitions.iter().all(|transition| {
+            transition.inhibitor_arcs.is_empty()
+                && transition.reset_places.is_empty()
+                && transition.zero_test_places.is_empty()
+                && !transition.has_dynamic_arcs()
+                && transition.expression.is_none()
+                && transition.guard.is_none()
+        }) && self.places.iter().all(|place| place.capacity().is_none());
+        assert!(
+            monotonic,
+            "coverability requires a net with only input, output and read arcs"
+        );
+
+        let mut tree = CoverabilityTree::default();
+        let root = tree.add_node(self.marking().into_iter().map(Some).collect(), None);
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+
+        while let Some(node) = queue.pop_front() {
+            let marking = tree.markings[node].clone();
+            if tree
+                .ancestors(node)
+                .any(|ancestor| tree.markings[ancestor] == marking)
+            {
+                continue;
+            }
+
+            for transition in 0..self.transitions.len() {
+                let mut successor = match self.fire_omega(transition, &marking) {
+                    Some(successor) => successor,
+                    None => continue,
+                };
+
+                for ancestor in std::iter::once(node).chain(tree.ancestors(node)) {
+                    let smaller = &tree.markings[ancestor];
+                    if covered(smaller, &successor) && *smaller != successor {
+                        for (tokens, smaller) in successor.iter_mut().zip(smaller) {
+                            if *smaller != *tokens {
+                                *tokens = None;
+                            }
+                        }
+                    }
+                }
+
+                let child = tree.add_node(successor, Some(node));
+                tree.edges[node].push((transition, child));
+                queue.push_back(child);
+            }
+        }
+
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_coverability_tree_unbounded() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // t1 keeps the token on p1 and produces on p2, t2 removes the
+        // token from p1
+        let mut t1 = Transition::new();
+        t1.add_loop(&p1, 1).expect("weight greater than zero");
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let tree = net.coverability_tree();
+        assert!(!tree.is_bounded());
+        assert_eq!(vec![Some(1), None], tree.bounds());
+        assert_eq!(
+            &[
+                vec![Some(1), Some(0)],
+                vec![Some(1), None],
+                vec![Some(0), Some(0)],
+                vec![Some(1), None],
+                vec![Some(0), None],
+            ],
+            tree.markings()
+        );
+        assert_eq!(&[(0, 1), (1, 2)], tree.edges(0));
+        assert!(tree.edges(3).is_empty(), "repeated marking isn't expanded");
+        assert_eq!(Some(1), tree.parent(3));
+        assert_eq!(None, tree.parent(0));
+
+        assert!(tree.covers(&[1, 1000]));
+        assert!(tree.covers(&[0, 5]));
+        assert!(!tree.covers(&[2, 0]));
+        assert_eq!(vec![1, 0], net.marking(), "marking is untouched");
+    }
+
+    #[test]
+    fn test_coverability_tree_bounded() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let tree = net.coverability_tree();
+        assert!(tree.is_bounded());
+        assert_eq!(vec![Some(2), Some(2)], tree.bounds());
+        assert!(tree.covers(&[0, 2]));
+        assert!(!tree.covers(&[1, 2]));
+    }
+
+    #[test]
+    fn test_coverability_tree_overflow() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(u32::MAX, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // firing would overflow p2, so the root has no successor
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        let tree = net.coverability_tree();
+        assert!(tree.is_bounded());
+        assert_eq!(vec![Some(1), Some(u32::MAX)], tree.bounds());
+    }
+
+    #[test]
+    #[should_panic(expected = "coverability requires a net with only input, output and read arcs")]
+    fn test_coverability_tree_inhibitor() {
+        let p = Place::new(0, "p");
+        let a = Arc::new(&p, 1).expect("weight greater than zero");
+        let mut t = Transition::new();
+        t.add_inhibitor(&a);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        net.coverability_tree();
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt::Write;
+
+use crate::Petrinet;
+
+pub(crate) fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn weight_attribute(weight: u32) -> String {
+    if weight == 1 {
+        String::new()
+    } else {
+        format!(", label=\"{}\"", weight)
+    }
+}
+
+fn weight_label(weight: u32) -> String {
+    if weight == 1 {
+        String::new()
+    } else {
+        format!(" [label=\"{}\"]", weight)
+    }
+}
+
+impl Petrinet<'_> {
+    /// Render the net in the Graphviz DOT format.
+    ///
+    /// Places are drawn as circles labeled with their name and token count,
+    /// transitions as boxes labeled with their name (or their index, if they
+    /// are unnamed). Arcs with weight 1 carry no label. Inhibitor arcs end in
+    /// a circle, reset arcs in a double arrow head, read arcs are dashed lines
+    /// without arrow head. Zero-test arcs are drawn like inhibitor arcs with
+    /// label 0.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph petrinet {\n");
+
+        for (i, place) in self.places.iter().enumerate() {
+            writeln!(
+                dot,
+                "    p{} [shape=circle, label=\"{}\\n{}\"];",
+                i,
+                escape(place.name()),
+                place.tokens()
+            )
+            .unwrap();
+        }
+
+        for (i, transition) in self.transitions.iter().enumerate() {
+            let label = match transition.name() {
+                Some(name) => escape(name),
+                None => format!("t{}", i),
+            };
+            writeln!(dot, "    t{} [shape=box, label=\"{}\"];", i, label).unwrap();
+        }
+
+        for (i, transition) in self.transitions.iter().enumerate() {
+            for (place, weight) in transition.weighted_inputs() {
+                let p = self.place_index(place).expect("registered place");
+                writeln!(dot, "    p{} -> t{}{};", p, i, weight_label(weight)).unwrap();
+            }
+
+            for arc in &transition.inhibitor_arcs {
+                let p = self.place_index(arc.place).expect("registered place");
+                writeln!(
+                    dot,
+                    "    p{} -> t{} [arrowhead=odot, label=\"{}\"];",
+                    p, i, arc.weight
+                )
+                .unwrap();
+            }
+
+            for place in &transition.zero_test_places {
+                let p = self.place_index(place).expect("registered place");
+                writeln!(dot, "    p{} -> t{} [arrowhead=odot, label=\"0\"];", p, i).unwrap();
+            }
+
+            for arc in &transition.read_arcs {
+                let p = self.place_index(arc.place).expect("registered place");
+                writeln!(
+                    dot,
+                    "    p{} -> t{} [arrowhead=none, style=dashed{}];",
+                    p,
+                    i,
+                    weight_attribute(arc.weight)
+                )
+                .unwrap();
+            }
+
+            for (place, weight) in transition.weighted_outputs() {
+                let p = self.place_index(place).expect("registered place");
+                writeln!(dot, "    t{} -> p{}{};", i, p, weight_label(weight)).unwrap();
+            }
+
+            for place in &transition.reset_places {
+                let p = self.place_index(place).expect("registered place");
+                writeln!(dot, "    t{} -> p{} [arrowhead=normalnormal];", i, p).unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_to_dot() {
+        let p1 = Place::new(2, "P1");
+        let p2 = Place::new(0, "P\"2\"");
+
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::with_name("produce");
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_inhibitor(&a2);
+        t2.add_read(&a1);
+        t2.add_reset(&p1);
+        t2.add_zero_test(&p2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let expected = concat!(
+            "digraph petrinet {\n",
+            "    p0 [shape=circle, label=\"P1\\n2\"];\n",
+            "    p1 [shape=circle, label=\"P\\\"2\\\"\\n0\"];\n",
+            "    t0 [shape=box, label=\"produce\"];\n",
+            "    t1 [shape=box, label=\"t1\"];\n",
+            "    p0 -> t0 [label=\"2\"];\n",
+            "    t0 -> p1;\n",
+            "    p1 -> t1 [arrowhead=odot, label=\"1\"];\n",
+            "    p1 -> t1 [arrowhead=odot, label=\"0\"];\n",
+            "    p0 -> t1 [arrowhead=none, style=dashed, label=\"2\"];\n",
+            "    t1 -> p0 [arrowhead=normalnormal];\n",
+            "}\n",
+        );
+        assert_eq!(expected, net.to_dot());
+    }
+}
@@ -0,0 +1,144 @@
+//! Nets of a size fixed at compile time, for embedded simulation.
+//!
+//! The firing rule is the one of `Transition` and `OwnedPetrinet`, and only
+//! uses `core`. It never allocates and never panics, so
+//! `FixedPetrinet::step` and `FixedPetrinet::is_enabled` can run on targets
+//! like microcontrollers. Transitions are plain weight vectors, so reading,
+//! inhibitor and reset arcs, guards and expressions are not supported.
+//!
+//! The rest of the crate needs the default `std` feature. Without it, the
+//! crate is `no_std` and only provides this module and `FireError`. As the
+//! sizes are fixed, there is no need for the collections of `heapless`
+//! either.
+
+use core::result;
+
+use crate::{covers, fired_tokens, FireError};
+
+/// A transition of a `FixedPetrinet` with `P` places, given by the weights
+/// of its input and output arcs per place. A weight of 0 stands for no arc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedTransition<const P: usize> {
+    name: &'static str,
+    pre: [u32; P],
+    post: [u32; P],
+}
+
+impl<const P: usize> FixedTransition<P> {
+    /// Create a transition consuming `pre[p]` and producing `post[p]` tokens
+    /// on place `p`.
+    pub const fn new(name: &'static str, pre: [u32; P], post: [u32; P]) -> Self {
+        Self { name, pre, post }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// A net with `P` places and `T` transitions, which owns its marking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPetrinet<const P: usize, const T: usize> {
+    marking: [u32; P],
+    transitions: [FixedTransition<P>; T],
+}
+
+impl<const P: usize, const T: usize> FixedPetrinet<P, T> {
+    pub const fn new(marking: [u32; P], transitions: [FixedTransition<P>; T]) -> Self {
+        Self {
+            marking,
+            transitions,
+        }
+    }
+
+    /// The token counts of all places.
+    pub fn marking(&self) -> &[u32; P] {
+        &self.marking
+    }
+
+    pub fn transitions(&self) -> &[FixedTransition<P>; T] {
+        &self.transitions
+    }
+
+    /// A transition is enabled if every place holds at least the weight of
+    /// its input arc. Like for `Transition::is_enabled`, an overflowing token
+    /// count doesn't disable a transition, but makes `fire` fail. Returns
+    /// `false` if there is no transition at `index`.
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.transitions.get(index).is_some_and(|transition| {
+            self.marking
+                .iter()
+                .zip(&transition.pre)
+                .all(|(&tokens, &pre)| covers(tokens, u64::from(pre)))
+        })
+    }
+
+    /// Fire the transition at `index`. Fails without changing the marking if
+    /// there is no such transition, it is not enabled, or a token count would
+    /// overflow.
+    pub fn fire(&mut self, index: usize) -> result::Result<(), FireError> {
+        let transition = self
+            .transitions
+            .get(index)
+            .ok_or(FireError::NoSuchTransition(index))?;
+        if !self.is_enabled(index) {
+            return Err(FireError::NotEnabled);
+        }
+
+        let mut successor = self.marking;
+        for ((tokens, &pre), &post) in successor
+            .iter_mut()
+            .zip(&transition.pre)
+            .zip(&transition.post)
+        {
+            *tokens = fired_tokens(*tokens, u64::from(pre), u64::from(post))?;
+        }
+        self.marking = successor;
+        Ok(())
+    }
+
+    /// Fire the first transition which can fire. Returns whether a
+    /// transition fired.
+    pub fn step(&mut self) -> bool {
+        (0..T).any(|t| self.fire(t).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_petrinet() {
+        const PRODUCE: FixedTransition<2> = FixedTransition::new("produce", [2, 0], [0, 1]);
+        const CONSUME: FixedTransition<2> = FixedTransition::new("consume", [0, 1], [0, 0]);
+        let mut net = FixedPetrinet::new([3, 0], [CONSUME, PRODUCE]);
+
+        assert_eq!("produce", net.transitions()[1].name());
+        assert!(!net.is_enabled(0));
+        assert!(net.is_enabled(1));
+        assert!(!net.is_enabled(2), "no such transition");
+
+        assert!(net.step());
+        assert_eq!(&[1, 1], net.marking());
+        assert!(net.step());
+        assert_eq!(&[1, 0], net.marking());
+        assert!(!net.step(), "deadlocked");
+
+        assert_eq!(Err(FireError::NotEnabled), net.fire(1));
+        assert_eq!(Err(FireError::NoSuchTransition(2)), net.fire(2));
+        assert_eq!(&[1, 0], net.marking());
+    }
+
+    #[test]
+    fn test_fixed_petrinet_overflow() {
+        let source = FixedTransition::new("source", [0], [1]);
+        let mut net = FixedPetrinet::new([u32::MAX - 1], [source]);
+
+        assert!(net.step());
+        assert!(net.is_enabled(0), "an overflow doesn't disable");
+        assert_eq!(Err(FireError::Overflow), net.fire(0));
+        assert!(!net.step());
+        assert_eq!(&[u32::MAX], net.marking());
+    }
+}
@@ -0,0 +1,260 @@
+//! An immutable, index-based snapshot of a net for repeated analysis.
+
+use std::collections::HashMap;
+
+use crate::invariants::{null_space, semi_positive_invariants};
+use crate::rules::Rules;
+use crate::structure::{is_siphon, is_trap, minimal_place_sets, Flow};
+use crate::{Marking, Petrinet, ReachabilityGraph};
+
+/// A snapshot of the structure and marking of a net, see
+/// `Petrinet::freeze`.
+///
+/// The firing rule, the incidence matrix, presets, postsets and name lookups
+/// are computed once, so repeated analyses don't derive them again, and the
+/// reachability graph is explored on plain markings without touching any
+/// place. Places and transitions keep their indices in the net.
+#[derive(Debug, Clone)]
+pub struct FrozenNet {
+    rules: Rules,
+    marking: Marking,
+    incidence: Vec<Vec<i64>>,
+    flow: Vec<(Vec<usize>, Vec<usize>)>,
+    place_presets: Vec<Vec<usize>>,
+    place_postsets: Vec<Vec<usize>>,
+    place_names: Vec<String>,
+    transition_names: Vec<Option<String>>,
+    place_indices: HashMap<String, usize>,
+    transition_indices: HashMap<String, usize>,
+}
+
+impl Petrinet<'_> {
+    /// Take a snapshot of the net and its current marking for analysis, or
+    /// return `None` if a transition has an expression, a guard, a variable,
+    /// sampled or overflow arc, which depend on more than the marking.
+    ///
+    /// Later changes to the net or its marking don't affect the snapshot.
+    pub fn freeze(&self) -> Option<FrozenNet> {
+        let rules = Rules::new(self)?;
+
+        let mut place_indices = HashMap::new();
+        for (p, place) in self.places.iter().enumerate().rev() {
+            place_indices.insert(place.name().to_string(), p);
+        }
+        let mut transition_indices = HashMap::new();
+        for (t, transition) in self.transitions.iter().enumerate().rev() {
+            if let Some(name) = transition.name() {
+                transition_indices.insert(name.to_string(), t);
+            }
+        }
+
+        Some(FrozenNet {
+            rules,
+            marking: self.marking(),
+            incidence: self.incidence_matrix(),
+            flow: self.flow(),
+            place_presets: (0..self.places.len())
+                .map(|p| self.place_preset(p))
+                .collect(),
+            place_postsets: (0..self.places.len())
+                .map(|p| self.place_postset(p))
+                .collect(),
+            place_names: self
+                .places
+                .iter()
+                .map(|place| place.name().to_string())
+                .collect(),
+            transition_names: self
+                .transitions
+                .iter()
+                .map(|transition| transition.name.clone())
+                .collect(),
+            place_indices,
+            transition_indices,
+        })
+    }
+}
+
+impl FrozenNet {
+    pub fn place_count(&self) -> usize {
+        self.place_names.len()
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.transition_names.len()
+    }
+
+    /// The marking of the net when it was frozen.
+    pub fn marking(&self) -> &[u32] {
+        &self.marking
+    }
+
+    /// The index of the first place named `name`.
+    pub fn place_index(&self, name: &str) -> Option<usize> {
+        self.place_indices.get(name).copied()
+    }
+
+    /// The index of the first transition named `name`.
+    pub fn transition_index(&self, name: &str) -> Option<usize> {
+        self.transition_indices.get(name).copied()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if there is no place at index `place`.
+    pub fn place_name(&self, place: usize) -> &str {
+        &self.place_names[place]
+    }
+
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn transition_name(&self, transition: usize) -> Option<&str> {
+        self.transition_names[transition].as_deref()
+    }
+
+    /// See `Petrinet::incidence_matrix`.
+    pub fn incidence_matrix(&self) -> &[Vec<i64>] {
+        &self.incidence
+    }
+
+    /// See `Petrinet::transition_preset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn transition_preset(&self, transition: usize) -> &[usize] {
+        &self.flow[transition].0
+    }
+
+    /// See `Petrinet::transition_postset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn transition_postset(&self, transition: usize) -> &[usize] {
+        &self.flow[transition].1
+    }
+
+    /// See `Petrinet::place_preset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no place at index `place`.
+    pub fn place_preset(&self, place: usize) -> &[usize] {
+        &self.place_presets[place]
+    }
+
+    /// See `Petrinet::place_postset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no place at index `place`.
+    pub fn place_postset(&self, place: usize) -> &[usize] {
+        &self.place_postsets[place]
+    }
+
+    /// See `Petrinet::reachability_graph`, starting from the marking of the
+    /// net when it was frozen.
+    pub fn reachability_graph(&self, max_states: usize) -> ReachabilityGraph {
+        ReachabilityGraph::explore(
+            self.marking.clone(),
+            self.transition_names.clone(),
+            max_states,
+            |marking| self.rules.successors_of(marking),
+        )
+    }
+
+    /// See `Petrinet::place_invariants`.
+    pub fn place_invariants(&self) -> Vec<Vec<i64>> {
+        null_space(&self.transposed_incidence(), self.place_count())
+    }
+
+    /// See `Petrinet::transition_invariants`.
+    pub fn transition_invariants(&self) -> Vec<Vec<i64>> {
+        semi_positive_invariants(&self.transposed_incidence())
+    }
+
+    fn transposed_incidence(&self) -> Vec<Vec<i64>> {
+        (0..self.transition_count())
+            .map(|t| self.incidence.iter().map(|row| row[t]).collect())
+            .collect()
+    }
+
+    /// See `Petrinet::siphons`.
+    pub fn siphons(&self) -> Vec<Vec<usize>> {
+        let flow: &Flow = &self.flow;
+        minimal_place_sets(self.place_count(), |set| is_siphon(flow, set))
+    }
+
+    /// See `Petrinet::traps`.
+    pub fn traps(&self) -> Vec<Vec<usize>> {
+        let flow: &Flow = &self.flow;
+        minimal_place_sets(self.place_count(), |set| is_trap(flow, set))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_freeze() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::with_capacity(0, "p3", 1);
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // a token moving p1 -> p2 -> p1, and copy, which puts a token on p3
+        // while p2 is marked, up to its capacity
+        let mut forth = Transition::with_name("forth");
+        forth.add_input(&a1);
+        forth.add_output(&a2);
+        let mut back = Transition::with_name("back");
+        back.add_input(&a2);
+        back.add_output(&a1);
+        let mut copy = Transition::new();
+        copy.add_read(&a2);
+        copy.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(forth);
+        net.add_transition(back);
+        net.add_transition(copy);
+        let frozen = net.freeze().expect("no guards or dynamic arcs");
+        p1.set_tokens(5);
+
+        assert_eq!((3, 3), (frozen.place_count(), frozen.transition_count()));
+        assert_eq!(&[1, 0, 0], frozen.marking(), "frozen before the change");
+        assert_eq!(Some(1), frozen.place_index("p2"));
+        assert_eq!(Some(1), frozen.transition_index("back"));
+        assert_eq!(None, frozen.transition_index("copy"));
+        assert_eq!(
+            ("p3", None),
+            (frozen.place_name(2), frozen.transition_name(2))
+        );
+        assert_eq!(&[1], frozen.transition_postset(0));
+        assert_eq!(&[1], frozen.place_preset(0));
+        assert_eq!(&[0], frozen.place_postset(0));
+
+        assert_eq!(net.incidence_matrix(), frozen.incidence_matrix());
+        assert_eq!(net.place_invariants(), frozen.place_invariants());
+        assert_eq!(net.transition_invariants(), frozen.transition_invariants());
+        assert_eq!(net.siphons(), frozen.siphons());
+        assert_eq!(net.traps(), frozen.traps());
+
+        p1.set_tokens(1);
+        let graph = frozen.reachability_graph(100);
+        assert_eq!(net.reachability_graph(100).markings(), graph.markings());
+        assert_eq!(4, graph.state_count());
+        assert!(graph.is_complete());
+        assert!(!frozen.reachability_graph(2).is_complete());
+
+        let mut guarded = Transition::new_with_guard(|_| true);
+        guarded.add_input(&a1);
+        net.add_transition(guarded);
+        assert!(net.freeze().is_none());
+    }
+}
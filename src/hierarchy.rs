@@ -0,0 +1,249 @@
+//! Hierarchical nets, in which a single transition of the parent net stands
+//! for a whole subnet.
+
+use std::ptr;
+use std::result;
+
+use crate::{FireError, Petrinet, Place};
+
+/// A transition which stands for a whole subnet, like the substitution
+/// transitions of hierarchical colored nets.
+///
+/// The subnet is connected to the parent net through pairs of a parent place
+/// and an interface place of the subnet. Before the subnet is inspected or
+/// stepped, the interface places take over the token counts of their parent
+/// places, and after a step the parent places take over the token counts of
+/// the interface places. Only a single level of nesting is supported.
+///
+/// Substitution transitions take part in stepping the parent net once they
+/// are added with `Petrinet::add_substitution`.
+pub struct SubstitutionTransition<'a> {
+    subnet: Petrinet<'a>,
+    interface: Vec<(&'a Place, &'a Place)>,
+}
+
+impl<'a> SubstitutionTransition<'a> {
+    pub fn new(subnet: Petrinet<'a>) -> Self {
+        Self {
+            subnet,
+            interface: Vec::new(),
+        }
+    }
+
+    /// Connect the place `parent` of the parent net to the place `interface`
+    /// of the subnet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interface` is not a place of the subnet.
+    pub fn map_place(&mut self, parent: &'a Place, interface: &'a Place) {
+        assert!(
+            self.subnet.place_index(interface).is_some(),
+            "interface place must belong to the subnet"
+        );
+        self.interface.push((parent, interface));
+    }
+
+    pub fn subnet(&self) -> &Petrinet<'a> {
+        &self.subnet
+    }
+
+    /// Hand the tokens of the parent places to the interface places, and
+    /// return the previous token counts of the interface places.
+    fn sync_in(&self) -> Vec<u32> {
+        let previous = self
+            .interface
+            .iter()
+            .map(|(_, interface)| interface.tokens())
+            .collect();
+        for (parent, interface) in &self.interface {
+            interface.tokens.set(parent.tokens());
+        }
+        previous
+    }
+
+    fn restore_interface(&self, previous: &[u32]) {
+        for ((_, interface), &tokens) in self.interface.iter().zip(previous) {
+            interface.tokens.set(tokens);
+        }
+    }
+
+    fn sync_out(&self) {
+        for (parent, interface) in &self.interface {
+            if !ptr::eq(*parent, *interface) {
+                parent.tokens.set(interface.tokens());
+            }
+        }
+    }
+
+    /// The substitution transition is enabled if, with the tokens of the
+    /// parent places, some transition of the subnet is enabled. The token
+    /// counts of the interface places are restored afterwards.
+    pub fn is_enabled(&self) -> bool {
+        let previous = self.sync_in();
+        let enabled = !self.subnet.is_deadlocked();
+        self.restore_interface(&previous);
+        enabled
+    }
+
+    /// Make one step of the subnet, see `Petrinet::step`, and hand the
+    /// resulting tokens of the interface places back to the parent places.
+    /// If the subnet can't step, no place changes.
+    pub fn fire(&mut self) -> result::Result<(), FireError> {
+        let previous = self.sync_in();
+        if !self.subnet.step() {
+            self.restore_interface(&previous);
+            return Err(FireError::NotEnabled);
+        }
+
+        self.sync_out();
+        Ok(())
+    }
+}
+
+impl<'a> Petrinet<'a> {
+    /// Add a substitution transition to the net and return its index among
+    /// the substitution transitions. Its parent places are registered with
+    /// the net, after the places of the transitions added so far.
+    ///
+    /// Stepping fires a substitution transition when no transition of the
+    /// net is enabled, see `step_with`. Firings of the subnet don't show up
+    /// in the trace or the undo history, and analyses of the state space
+    /// ignore substitution transitions.
+    pub fn add_substitution(&mut self, substitution: SubstitutionTransition<'a>) -> usize {
+        for &(parent, _) in &substitution.interface {
+            self.register_place(parent);
+        }
+
+        self.substitutions.push(substitution);
+        self.substitutions.len() - 1
+    }
+
+    pub fn substitutions(&self) -> &[SubstitutionTransition<'a>] {
+        &self.substitutions
+    }
+
+    /// Indices of all substitution transitions which are enabled in the
+    /// current marking.
+    pub fn enabled_substitutions(&self) -> Vec<usize> {
+        (0..self.substitutions.len())
+            .filter(|&i| self.substitutions[i].is_enabled())
+            .collect()
+    }
+
+    /// Fire the substitution transition at `index`, see
+    /// `SubstitutionTransition::fire`.
+    pub fn fire_substitution(&mut self, index: usize) -> result::Result<(), FireError> {
+        self.substitutions
+            .get_mut(index)
+            .ok_or(FireError::NoSuchTransition(index))?
+            .fire()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arc, Transition};
+
+    #[test]
+    fn test_substitution_transition() {
+        let input = Place::new(1, "input");
+        let output = Place::new(0, "output");
+
+        // the subnet moves a token from its input through an internal place
+        // to its output
+        let sub_input = Place::new(0, "sub_input");
+        let internal = Place::new(0, "internal");
+        let sub_output = Place::new(0, "sub_output");
+        let a1 = Arc::new(&sub_input, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&internal, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&sub_output, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        let mut subnet = Petrinet::new();
+        subnet.add_transition(t1);
+        subnet.add_transition(t2);
+
+        let mut substitution = SubstitutionTransition::new(subnet);
+        substitution.map_place(&input, &sub_input);
+        substitution.map_place(&output, &sub_output);
+
+        assert!(substitution.is_enabled());
+        assert_eq!(Ok(()), substitution.fire());
+        assert_eq!((0, 0), (input.tokens(), output.tokens()));
+        assert_eq!(vec![0, 1, 0], substitution.subnet().marking());
+
+        assert_eq!(Ok(()), substitution.fire());
+        assert_eq!((0, 1), (input.tokens(), output.tokens()));
+        assert!(!substitution.is_enabled());
+        assert_eq!(Err(FireError::NotEnabled), substitution.fire());
+
+        // tokens put on the parent place reach the subnet, but checking
+        // doesn't touch the interface places
+        input.add_tokens(1);
+        assert!(substitution.is_enabled());
+        assert_eq!(vec![0, 0, 1], substitution.subnet().marking());
+    }
+
+    #[test]
+    fn test_step_into_substitution() {
+        let input = Place::new(1, "input");
+        let middle = Place::new(0, "middle");
+        let output = Place::new(0, "output");
+        let a1 = Arc::new(&input, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&middle, 1).expect("weight greater than zero");
+
+        // the subnet moves a token from its input to its output
+        let sub_input = Place::new(0, "sub_input");
+        let sub_output = Place::new(0, "sub_output");
+        let b1 = Arc::new(&sub_input, 1).expect("weight greater than zero");
+        let b2 = Arc::new(&sub_output, 1).expect("weight greater than zero");
+        let mut inner = Transition::new();
+        inner.add_input(&b1);
+        inner.add_output(&b2);
+        let mut subnet = Petrinet::new();
+        subnet.add_transition(inner);
+
+        let mut substitution = SubstitutionTransition::new(subnet);
+        substitution.map_place(&middle, &sub_input);
+        substitution.map_place(&output, &sub_output);
+
+        // input -> t -> middle -> [subnet] -> output
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        assert_eq!(0, net.add_substitution(substitution));
+        assert_eq!(3, net.place_count());
+
+        assert!(net.enabled_substitutions().is_empty());
+        assert!(net.step());
+        assert_eq!(vec![0], net.enabled_substitutions());
+        assert!(!net.is_deadlocked());
+        assert!(net.step(), "the subnet steps");
+        assert_eq!(vec![0, 0, 1], net.marking());
+        assert!(net.is_deadlocked());
+        assert!(!net.step());
+        assert_eq!(Err(FireError::NotEnabled), net.fire_substitution(0));
+        assert_eq!(
+            Err(FireError::NoSuchTransition(1)),
+            net.fire_substitution(1)
+        );
+        assert_eq!(1, net.substitutions().len());
+    }
+
+    #[test]
+    #[should_panic(expected = "interface place must belong to the subnet")]
+    fn test_map_unknown_place() {
+        let place = Place::new(0, "p");
+        let mut substitution = SubstitutionTransition::new(Petrinet::new());
+        substitution.map_place(&place, &place);
+    }
+}
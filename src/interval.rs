@@ -0,0 +1,272 @@
+//! Time Petri nets in the sense of Merlin and Farber, where tokens carry the
+//! time they arrived and transitions fire within an interval
+//! `[earliest, latest]` of the age of their input tokens.
+//!
+//! Places hold at most one token.
+
+use std::cell::Cell;
+use std::ptr;
+use std::result;
+
+use crate::FireError;
+
+/// A token, stamped with the time it arrived on its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedToken {
+    arrival: u64,
+}
+
+impl TimedToken {
+    pub fn arrival(&self) -> u64 {
+        self.arrival
+    }
+}
+
+/// A place of a `TimePetrinet`, which holds at most one token.
+#[derive(Debug)]
+pub struct AgedPlace {
+    token: Cell<Option<TimedToken>>,
+    name: String,
+}
+
+impl AgedPlace {
+    /// Create a place, marked with a token which arrived at time 0 if
+    /// `marked` is true.
+    pub fn new(marked: bool, name: &str) -> Self {
+        Self {
+            token: Cell::new(if marked {
+                Some(TimedToken { arrival: 0 })
+            } else {
+                None
+            }),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn token(&self) -> Option<TimedToken> {
+        self.token.get()
+    }
+}
+
+/// A transition of a `TimePetrinet` with a firing interval.
+pub struct IntervalTransition<'a> {
+    inputs: Vec<&'a AgedPlace>,
+    outputs: Vec<&'a AgedPlace>,
+    earliest: u64,
+    latest: u64,
+}
+
+impl<'a> IntervalTransition<'a> {
+    /// Create a transition which may fire once its youngest input token is
+    /// at least `earliest` old, and must fire before it is older than
+    /// `latest`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `earliest` is greater than `latest`.
+    pub fn new(earliest: u64, latest: u64) -> Self {
+        assert!(
+            earliest <= latest,
+            "earliest firing time must not exceed latest firing time"
+        );
+        Self {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            earliest,
+            latest,
+        }
+    }
+
+    pub fn add_input(&mut self, place: &'a AgedPlace) {
+        self.inputs.push(place);
+    }
+
+    pub fn add_output(&mut self, place: &'a AgedPlace) {
+        self.outputs.push(place);
+    }
+}
+
+/// A time Petri net with a global clock.
+///
+/// A transition is token-enabled if all its input places are marked and all
+/// its output places, which aren't input places as well, are empty. Its age
+/// is the age of its youngest input token, or the current time if it has no
+/// inputs. It is enabled if it is token-enabled and its age lies within its
+/// firing interval. Time can't pass beyond the latest firing time of a
+/// token-enabled transition, so enabled transitions are urgent.
+#[derive(Default)]
+pub struct TimePetrinet<'a> {
+    transitions: Vec<IntervalTransition<'a>>,
+    time: u64,
+}
+
+impl<'a> TimePetrinet<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_transition(&mut self, transition: IntervalTransition<'a>) {
+        self.transitions.push(transition);
+    }
+
+    /// The current value of the clock.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// The age of the transition at `index`, or `None` if it is not
+    /// token-enabled.
+    fn age(&self, index: usize) -> Option<u64> {
+        let transition = &self.transitions[index];
+        let outputs_free = transition.outputs.iter().all(|output| {
+            output.token().is_none() || transition.inputs.iter().any(|i| ptr::eq(*i, *output))
+        });
+        if !outputs_free {
+            return None;
+        }
+
+        let mut arrival = 0;
+        for input in &transition.inputs {
+            arrival = arrival.max(input.token()?.arrival);
+        }
+        Some(self.time - arrival)
+    }
+
+    /// Whether the transition at `index` may fire now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at `index`.
+    pub fn is_enabled(&self, index: usize) -> bool {
+        let transition = &self.transitions[index];
+        self.age(index)
+            .is_some_and(|age| transition.earliest <= age && age <= transition.latest)
+    }
+
+    /// Fire the transition at `index`. Its input tokens are consumed and
+    /// fresh tokens, arriving now, are put on its output places.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at `index`.
+    pub fn fire(&mut self, index: usize) -> result::Result<(), FireError> {
+        if !self.is_enabled(index) {
+            return Err(FireError::NotEnabled);
+        }
+
+        let transition = &self.transitions[index];
+        for input in &transition.inputs {
+            input.token.set(None);
+        }
+        for output in &transition.outputs {
+            output.token.set(Some(TimedToken { arrival: self.time }));
+        }
+
+        Ok(())
+    }
+
+    /// Let `delta` time units pass, which ages all tokens. Fails without
+    /// changing the clock, and returns `false`, if a token-enabled transition
+    /// would miss its latest firing time, or the clock would overflow.
+    pub fn advance(&mut self, delta: u64) -> bool {
+        let urgent = (0..self.transitions.len()).any(|i| {
+            self.age(i).is_some_and(|age| {
+                age.checked_add(delta)
+                    .is_none_or(|age| age > self.transitions[i].latest)
+            })
+        });
+        if urgent {
+            return false;
+        }
+
+        match self.time.checked_add(delta) {
+            Some(time) => {
+                self.time = time;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_petrinet() {
+        let p1 = AgedPlace::new(true, "p1");
+        let p2 = AgedPlace::new(false, "p2");
+        let p3 = AgedPlace::new(false, "p3");
+
+        let mut t1 = IntervalTransition::new(2, 4);
+        t1.add_input(&p1);
+        t1.add_output(&p2);
+        let mut t2 = IntervalTransition::new(0, 1);
+        t2.add_input(&p2);
+        t2.add_output(&p3);
+
+        let mut net = TimePetrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        assert!(!net.is_enabled(0), "token too young");
+        assert_eq!(Err(FireError::NotEnabled), net.fire(0));
+        assert!(net.advance(3));
+        assert!(net.is_enabled(0));
+        assert!(!net.advance(2), "t1 must fire by time 4");
+        assert_eq!(3, net.time());
+
+        assert_eq!(Ok(()), net.fire(0));
+        assert_eq!(None, p1.token());
+        assert_eq!(Some(3), p2.token().map(|token| token.arrival()));
+
+        assert!(net.is_enabled(1), "zero earliest firing time");
+        assert!(net.advance(1));
+        assert!(!net.advance(1));
+        assert_eq!(Ok(()), net.fire(1));
+        assert_eq!(Some(4), p3.token().map(|token| token.arrival()));
+        assert!(net.advance(100), "nothing is token-enabled");
+
+        assert!(!net.advance(u64::MAX), "the clock would overflow");
+        assert_eq!(104, net.time());
+    }
+
+    #[test]
+    fn test_advance_overflow() {
+        let p1 = AgedPlace::new(true, "p1");
+        let mut t = IntervalTransition::new(0, u64::MAX);
+        t.add_input(&p1);
+
+        let mut net = TimePetrinet::new();
+        net.add_transition(t);
+        assert!(net.advance(u64::MAX));
+        assert!(!net.advance(1), "the token would be too old");
+        assert_eq!(u64::MAX, net.time());
+    }
+
+    #[test]
+    fn test_occupied_output_place() {
+        let p1 = AgedPlace::new(true, "p1");
+        let p2 = AgedPlace::new(true, "p2");
+
+        let mut t = IntervalTransition::new(0, 0);
+        t.add_input(&p1);
+        t.add_output(&p2);
+
+        let mut net = TimePetrinet::new();
+        net.add_transition(t);
+        assert!(!net.is_enabled(0));
+        assert!(net.advance(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "earliest firing time must not exceed latest firing time")]
+    fn test_invalid_interval() {
+        IntervalTransition::new(2, 1);
+    }
+}
@@ -0,0 +1,532 @@
+//! Place and transition invariants, derived from the incidence matrix.
+
+use std::convert::TryFrom;
+
+use crate::Petrinet;
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Divide a vector by the greatest common divisor of its entries, and make its
+/// first non-zero entry positive.
+fn normalize(vector: &mut [i128]) {
+    let divisor = vector.iter().fold(0, |d, &x| gcd(d, x));
+    let sign = match vector.iter().find(|&&x| x != 0) {
+        Some(&x) if x < 0 => -1,
+        _ => 1,
+    };
+
+    if divisor > 0 {
+        for x in vector.iter_mut() {
+            *x = sign * *x / divisor;
+        }
+    }
+}
+
+fn to_i64(vector: Vec<i128>) -> Vec<i64> {
+    vector
+        .into_iter()
+        .map(|x| i64::try_from(x).expect("invariant entry fits into i64"))
+        .collect()
+}
+
+/// A basis of the integer vectors `x` with `matrix · x = 0`, where every row
+/// of `matrix` has `columns` entries.
+///
+/// The matrix is brought into reduced row echelon form by fraction-free
+/// Gaussian elimination. Every free column then yields a basis vector.
+pub(crate) fn null_space(matrix: &[Vec<i64>], columns: usize) -> Vec<Vec<i64>> {
+    let mut rows: Vec<Vec<i128>> = matrix
+        .iter()
+        .map(|row| row.iter().map(|&x| i128::from(x)).collect())
+        .collect();
+    let mut pivots = Vec::new();
+
+    for column in 0..columns {
+        let rank = pivots.len();
+        let pivot = match (rank..rows.len()).find(|&r| rows[r][column] != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+
+        let pivot_row = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r == rank || row[column] == 0 {
+                continue;
+            }
+
+            let factor = row[column];
+            for (x, &p) in row.iter_mut().zip(&pivot_row) {
+                *x = *x * pivot_row[column] - p * factor;
+            }
+            normalize(row);
+        }
+
+        pivots.push(column);
+    }
+
+    let lcm = pivots.iter().enumerate().fold(1, |lcm, (r, &c)| {
+        let p = rows[r][c].abs();
+        lcm / gcd(lcm, p) * p
+    });
+
+    (0..columns)
+        .filter(|c| !pivots.contains(c))
+        .map(|free| {
+            let mut vector = vec![0; columns];
+            vector[free] = lcm;
+            for (r, &c) in pivots.iter().enumerate() {
+                vector[c] = -rows[r][free] * lcm / rows[r][c];
+            }
+            normalize(&mut vector);
+            to_i64(vector)
+        })
+        .collect()
+}
+
+/// The minimal non-negative integer vectors `y` with `yᵀ · matrix = 0`, where
+/// `matrix` has a row per entry of `y`.
+///
+/// This is the Farkas algorithm: starting from `[matrix | I]`, every column
+/// of `matrix` is zeroed in turn by adding up pairs of rows with opposite signs
+/// in that column and dropping the rows which are non-zero in it. Rows whose
+/// support contains the support of another row are dropped along the way, so
+/// only invariants with minimal support remain.
+pub(crate) fn semi_positive_invariants(matrix: &[Vec<i64>]) -> Vec<Vec<i64>> {
+    let n = matrix.len();
+    let columns = matrix.first().map_or(0, Vec::len);
+
+    let mut rows: Vec<(Vec<i128>, Vec<i128>)> = matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut unit = vec![0; n];
+            unit[i] = 1;
+            (row.iter().map(|&x| i128::from(x)).collect(), unit)
+        })
+        .collect();
+
+    for column in 0..columns {
+        let mut next: Vec<(Vec<i128>, Vec<i128>)> = rows
+            .iter()
+            .filter(|(row, _)| row[column] == 0)
+            .cloned()
+            .collect();
+
+        for (a, a_unit) in rows.iter().filter(|(row, _)| row[column] > 0) {
+            for (b, b_unit) in rows.iter().filter(|(row, _)| row[column] < 0) {
+                let (fa, fb) = (-b[column], a[column]);
+                let mut row: Vec<i128> = a.iter().zip(b).map(|(x, y)| fa * x + fb * y).collect();
+                let mut unit: Vec<i128> = a_unit
+                    .iter()
+                    .zip(b_unit)
+                    .map(|(x, y)| fa * x + fb * y)
+                    .collect();
+
+                let divisor = row.iter().chain(&unit).fold(0, |d, &x| gcd(d, x));
+                for x in row.iter_mut().chain(unit.iter_mut()) {
+                    *x /= divisor;
+                }
+                next.push((row, unit));
+            }
+        }
+
+        let support =
+            |unit: &[i128]| -> Vec<usize> { (0..unit.len()).filter(|&i| unit[i] != 0).collect() };
+        let supports: Vec<Vec<usize>> = next.iter().map(|(_, unit)| support(unit)).collect();
+        let mut minimal = Vec::new();
+        for (i, row) in next.into_iter().enumerate() {
+            let dominated = supports.iter().enumerate().any(|(j, other)| {
+                j != i
+                    && other.iter().all(|p| supports[i].contains(p))
+                    && (other.len() < supports[i].len() || j < i)
+            });
+            if !dominated {
+                minimal.push(row);
+            }
+        }
+        rows = minimal;
+    }
+
+    rows.into_iter().map(|(_, unit)| to_i64(unit)).collect()
+}
+
+/// Sum of the token counts of `marking`, weighted by `invariant`.
+pub fn weighted_token_sum(invariant: &[i64], marking: &[u32]) -> i64 {
+    invariant
+        .iter()
+        .zip(marking)
+        .map(|(&weight, &tokens)| weight * i64::from(tokens))
+        .sum()
+}
+
+impl Petrinet<'_> {
+    /// A basis of the place invariants of the net, that is, of the integer
+    /// vectors `y` with `yᵀ · C = 0` for the incidence matrix `C`. For every
+    /// place invariant, the token counts weighted by the invariant sum up to
+    /// the same value in every reachable marking.
+    ///
+    /// The entries of the basis vectors may be negative.
+    pub fn place_invariants(&self) -> Vec<Vec<i64>> {
+        let incidence = self.incidence_matrix();
+        let transposed: Vec<Vec<i64>> = (0..self.transitions.len())
+            .map(|t| incidence.iter().map(|row| row[t]).collect())
+            .collect();
+
+        null_space(&transposed, self.places.len())
+    }
+
+    /// The minimal transition invariants of the net, that is, the minimal
+    /// non-negative integer vectors `x` with `C · x = 0` for the incidence
+    /// matrix `C`. Firing every transition as often as a transition invariant
+    /// says, in any feasible order, leads back to the marking started from.
+    pub fn transition_invariants(&self) -> Vec<Vec<i64>> {
+        let incidence = self.incidence_matrix();
+        let transposed: Vec<Vec<i64>> = (0..self.transitions.len())
+            .map(|t| incidence.iter().map(|row| row[t]).collect())
+            .collect();
+
+        semi_positive_invariants(&transposed)
+    }
+
+    /// Whether the net has a place invariant with a positive entry for every
+    /// place, so the token counts weighted by it never change.
+    ///
+    /// The sum of all minimal semi-positive place invariants is such an
+    /// invariant if and only if one exists. Reset, variable, sampled and
+    /// overflow arcs can change any weighted token sum, so nets with one are
+    /// never conservative.
+    pub fn is_conservative(&self) -> bool {
+        if !self.has_fixed_token_flow() {
+            return false;
+        }
+
+        let mut covered = vec![false; self.places.len()];
+        for invariant in semi_positive_invariants(&self.incidence_matrix()) {
+            for (covered, &weight) in covered.iter_mut().zip(&invariant) {
+                *covered |= weight > 0;
+            }
+        }
+        covered.into_iter().all(|covered| covered)
+    }
+
+    /// Whether the total number of tokens never changes, that is, whether
+    /// the vector of all ones is a place invariant. Like `is_conservative`,
+    /// nets with reset, variable, sampled or overflow arcs are never strictly
+    /// conservative.
+    pub fn is_strictly_conservative(&self) -> bool {
+        let incidence = self.incidence_matrix();
+        self.has_fixed_token_flow()
+            && (0..self.transitions.len())
+                .all(|t| incidence.iter().map(|row| row[t]).sum::<i64>() == 0)
+    }
+
+    /// Indices of the places which hold a token in every marking reachable
+    /// from the current one, as far as the place invariants tell.
+    ///
+    /// Every minimal semi-positive place invariant bounds the token count of
+    /// each place in its support by its weighted token sum, and capacities
+    /// bound them further. A place is never empty if it is in the support of
+    /// an invariant whose weighted token sum exceeds the largest possible
+    /// contribution of all other places. This is sound but incomplete: the
+    /// result may miss places which are never empty, but all places it
+    /// returns are. Nets with reset, variable, sampled or overflow arcs have
+    /// no such places, like `is_conservative`.
+    pub fn never_empty_places(&self) -> Vec<usize> {
+        if !self.has_fixed_token_flow() {
+            return Vec::new();
+        }
+
+        let marking = self.marking();
+        let invariants: Vec<(Vec<i64>, i64)> = semi_positive_invariants(&self.incidence_matrix())
+            .into_iter()
+            .map(|invariant| {
+                let sum = weighted_token_sum(&invariant, &marking);
+                (invariant, sum)
+            })
+            .collect();
+
+        // the largest token count of every place, if it is bounded
+        let bounds: Vec<Option<i64>> = self
+            .places
+            .iter()
+            .enumerate()
+            .map(|(p, place)| {
+                invariants
+                    .iter()
+                    .filter(|(invariant, _)| invariant[p] > 0)
+                    .map(|(invariant, sum)| sum / invariant[p])
+                    .chain(place.capacity().map(i64::from))
+                    .min()
+            })
+            .collect();
+
+        (0..self.places.len())
+            .filter(|&p| {
+                invariants.iter().any(|(invariant, sum)| {
+                    invariant[p] > 0
+                        && invariant
+                            .iter()
+                            .zip(&bounds)
+                            .enumerate()
+                            .filter(|&(q, (&weight, _))| q != p && weight > 0)
+                            .try_fold(0, |others, (_, (&weight, bound))| {
+                                bound.map(|bound| others + weight * bound)
+                            })
+                            .is_some_and(|others| others < *sum)
+                })
+            })
+            .collect()
+    }
+
+    /// Whether every transition moves the same tokens in every marking, as
+    /// the incidence matrix says.
+    fn has_fixed_token_flow(&self) -> bool {
+        self.transitions
+            .iter()
+            .all(|transition| transition.reset_places.is_empty() && !transition.has_dynamic_arcs())
+    }
+
+    /// Whether firing any transition enabled in the current marking leaves
+    /// the token sum weighted by `invariant` unchanged.
+    ///
+    /// The marking of the net is restored afterwards.
+    pub fn preserves_invariant(&mut self, invariant: &[i64]) -> bool {
+        let marking = self.snapshot();
+        let sum = weighted_token_sum(invariant, &marking);
+
+        let preserved = (0..self.transitions.len()).all(|t| {
+            self.restore(&marking);
+            self.transitions[t].fire_tokens(self.token_policy).is_err()
+                || weighted_token_sum(invariant, &self.marking()) == sum
+        });
+
+        self.restore(&marking);
+        preserved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Arc, Place, Transition};
+
+    #[test]
+    fn test_null_space() {
+        assert_eq!(
+            vec![vec![1, 1, 0], vec![0, 0, 1]],
+            null_space(&[vec![2, -2, 0]], 3)
+        );
+        assert_eq!(
+            vec![vec![1, 2, 3]],
+            null_space(&[vec![3, 0, -1], vec![0, 3, -2]], 3)
+        );
+        assert!(null_space(&[vec![1, 0], vec![0, 1]], 2).is_empty());
+        assert_eq!(vec![vec![1, 0], vec![0, 1]], null_space(&[], 2));
+    }
+
+    #[test]
+    fn test_semi_positive_invariants() {
+        // y1 = y2 + y3 has the minimal solutions (1, 1, 0) and (1, 0, 1)
+        assert_eq!(
+            vec![vec![1, 1, 0], vec![1, 0, 1]],
+            semi_positive_invariants(&[vec![-1], vec![1], vec![1]])
+        );
+        assert!(semi_positive_invariants(&[vec![1], vec![1]]).is_empty());
+        assert_eq!(
+            vec![vec![2, 1]],
+            semi_positive_invariants(&[vec![1, 0], vec![-2, 0]])
+        );
+    }
+
+    #[test]
+    fn test_place_invariants() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let p3 = Place::new(2, "P3");
+        let p4 = Place::new(1, "P4");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+        let a4 = Arc::new(&p4, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        t1.add_output(&a3);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_input(&a3);
+        t2.add_output(&a4);
+        t2.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let invariants = net.place_invariants();
+        assert_eq!(vec![vec![1, 1, 0, 0], vec![1, 0, 1, 0]], invariants);
+
+        for invariant in &invariants {
+            let sum = weighted_token_sum(invariant, &net.marking());
+            for _ in 0..5 {
+                assert!(net.preserves_invariant(invariant));
+                net.step();
+                assert_eq!(sum, weighted_token_sum(invariant, &net.marking()));
+            }
+        }
+
+        net.restore(&[0, 1, 3, 1]);
+        assert!(!net.preserves_invariant(&[0, 0, 0, 1]), "P4 grows");
+        assert_eq!(vec![0, 1, 3, 1], net.marking(), "marking is restored");
+    }
+
+    #[test]
+    fn test_is_conservative() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let p3 = Place::new(0, "P3");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 2).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // P1 -> P2 turns one token into two, P2 -> P1 back into one
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        assert!(net.is_conservative(), "2 * P1 + P2 is constant");
+        assert!(!net.is_strictly_conservative());
+
+        // moving single tokens between P1 and P3 keeps the count
+        let mut forth = Transition::new();
+        forth.add_input(&a1);
+        forth.add_output(&a3);
+        let mut back = Transition::new();
+        back.add_input(&a3);
+        back.add_output(&a1);
+        let mut cycle = Petrinet::new();
+        cycle.add_transition(forth);
+        cycle.add_transition(back);
+        assert!(cycle.is_conservative());
+        assert!(cycle.is_strictly_conservative());
+
+        let mut reset = Transition::new();
+        reset.add_reset(&p3);
+        cycle.add_transition(reset);
+        assert!(!cycle.is_conservative());
+        assert!(!cycle.is_strictly_conservative());
+
+        // P3 only ever grows
+        let mut source = Transition::new();
+        source.add_output(&a3);
+        net.add_transition(source);
+        assert!(!net.is_conservative());
+    }
+
+    #[test]
+    fn test_never_empty_places() {
+        let free = Place::new(3, "free");
+        let used = Place::with_capacity(0, "used", 2);
+        let idle = Place::new(1, "idle");
+        let busy = Place::new(0, "busy");
+        let a1 = Arc::new(&free, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&used, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&idle, 1).expect("weight greater than zero");
+        let a4 = Arc::new(&busy, 1).expect("weight greater than zero");
+
+        // three resources, of which at most two can be in use at a time,
+        // and a worker switching between idle and busy
+        let mut acquire = Transition::new();
+        acquire.add_input(&a1);
+        acquire.add_output(&a2);
+        let mut release = Transition::new();
+        release.add_input(&a2);
+        release.add_output(&a1);
+        let mut start = Transition::new();
+        start.add_input(&a3);
+        start.add_output(&a4);
+        let mut stop = Transition::new();
+        stop.add_input(&a4);
+        stop.add_output(&a3);
+        let mut net = Petrinet::new();
+        net.add_transition(acquire);
+        net.add_transition(release);
+        net.add_transition(start);
+        net.add_transition(stop);
+        assert_eq!(vec![0], net.never_empty_places(), "free keeps a resource");
+
+        free.set_tokens(2);
+        assert!(net.never_empty_places().is_empty());
+
+        // a self-loop never takes the token from idle, which is registered
+        // after the input place busy
+        let mut net = Petrinet::new();
+        let mut t = Transition::new();
+        t.add_loop(&idle, 1).expect("weight greater than zero");
+        t.add_input(&a4);
+        net.add_transition(t);
+        assert_eq!(vec![1], net.never_empty_places());
+
+        let mut reset = Transition::new();
+        reset.add_reset(&idle);
+        net.add_transition(reset);
+        assert!(net.never_empty_places().is_empty());
+    }
+
+    #[test]
+    fn test_transition_invariants() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let p3 = Place::new(2, "P3");
+        let p4 = Place::new(1, "P4");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+        let a4 = Arc::new(&p4, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        t1.add_output(&a3);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_input(&a3);
+        t2.add_output(&a4);
+        t2.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        // t1 and t2 cycle, but every cycle leaves another token on P4, so the
+        // net never gets back to a marking
+        assert!(net.transition_invariants().is_empty());
+
+        // consuming the token from P4 again closes the cycle
+        let mut t3 = Transition::new();
+        t3.add_input(&a4);
+        net.add_transition(t3);
+        assert_eq!(vec![vec![1, 1, 1]], net.transition_invariants());
+
+        let marking = net.marking();
+        for transition in &mut net.transitions {
+            transition.fire().expect("transition is enabled");
+        }
+        assert_eq!(marking, net.marking());
+    }
+}
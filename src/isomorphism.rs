@@ -0,0 +1,294 @@
+//! Structural isomorphism of nets, up to renaming and reordering of places
+//! and transitions.
+
+use crate::Petrinet;
+
+/// The kind of an arc, so arcs of different kinds never match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ArcKind {
+    Input,
+    Output,
+    Inhibitor,
+    Read,
+    Loop,
+    Reset,
+    ZeroTest,
+    VariableInput,
+    VariableOutput,
+    SampledInput,
+    SampledOutput,
+    OverflowPrimary,
+    Overflow,
+}
+
+/// Kinds and weights of the arcs between a transition and a place, sorted.
+type Arcs = Vec<(ArcKind, u32)>;
+
+/// The arcs between every transition and every place, indexed by transition
+/// and then by place.
+type Incidence = Vec<Vec<Arcs>>;
+
+/// The arcs of the place `p` to all transitions, sorted, so only places
+/// with equal signatures can be mapped to each other.
+fn signature(incidence: &Incidence, p: usize) -> Vec<&Arcs> {
+    let mut arcs: Vec<&Arcs> = incidence
+        .iter()
+        .map(|row| &row[p])
+        .filter(|arcs| !arcs.is_empty())
+        .collect();
+    arcs.sort();
+    arcs
+}
+
+/// Whether the rows of `a`, restricted to the places `0..mapping.len()`,
+/// equal the rows of `b`, restricted to the places they are mapped to, up to
+/// reordering the rows.
+fn rows_match(a: &Incidence, b: &Incidence, mapping: &[usize]) -> bool {
+    let mut rows_a: Vec<Vec<&Arcs>> = a
+        .iter()
+        .map(|row| row[..mapping.len()].iter().collect())
+        .collect();
+    let mut rows_b: Vec<Vec<&Arcs>> = b
+        .iter()
+        .map(|row| mapping.iter().map(|&q| &row[q]).collect())
+        .collect();
+    rows_a.sort();
+    rows_b.sort();
+    rows_a == rows_b
+}
+
+/// Extend `mapping`, which maps the first places of `a` to places of `b`,
+/// to all places, such that the rows of both incidences match.
+fn extend(
+    a: &Incidence,
+    b: &Incidence,
+    candidates: &[Vec<usize>],
+    mapping: &mut Vec<usize>,
+    used: &mut [bool],
+) -> bool {
+    let p = mapping.len();
+    if p == candidates.len() {
+        return true;
+    }
+
+    for &q in &candidates[p] {
+        if used[q] {
+            continue;
+        }
+
+        mapping.push(q);
+        used[q] = true;
+        if rows_match(a, b, mapping) && extend(a, b, candidates, mapping, used) {
+            return true;
+        }
+        mapping.pop();
+        used[q] = false;
+    }
+
+    false
+}
+
+impl Petrinet<'_> {
+    fn incidence(&self) -> Incidence {
+        let mut incidence = vec![vec![Vec::new(); self.places.len()]; self.transitions.len()];
+        for (t, transition) in self.transitions.iter().enumerate() {
+            let arcs = transition
+                .input_arcs
+                .iter()
+                .map(|arc| (arc.place, ArcKind::Input, arc.weight))
+                .chain(
+                    transition
+                        .output_arcs
+                        .iter()
+                        .map(|arc| (arc.place, ArcKind::Output, arc.weight)),
+                )
+                .chain(
+                    transition
+                        .inhibitor_arcs
+                        .iter()
+                        .map(|arc| (arc.place, ArcKind::Inhibitor, arc.weight)),
+                )
+                .chain(
+                    transition
+                        .read_arcs
+                        .iter()
+                        .map(|arc| (arc.place, ArcKind::Read, arc.weight)),
+                )
+                .chain(
+                    transition
+                        .loop_arcs
+                        .iter()
+                        .map(|&(place, weight)| (place, ArcKind::Loop, weight)),
+                )
+                .chain(
+                    transition
+                        .reset_places
+                        .iter()
+                        .map(|&place| (place, ArcKind::Reset, 0)),
+                )
+                .chain(
+                    transition
+                        .zero_test_places
+                        .iter()
+                        .map(|&place| (place, ArcKind::ZeroTest, 0)),
+                )
+                .chain(
+                    transition
+                        .variable_input_arcs
+                        .iter()
+                        .map(|arc| (arc.place, ArcKind::VariableInput, 0)),
+                )
+                .chain(
+                    transition
+                        .variable_output_arcs
+                        .iter()
+                        .map(|arc| (arc.place, ArcKind::VariableOutput, 0)),
+                )
+                .chain(
+                    transition
+                        .sampled_input_arcs
+                        .iter()
+                        .map(|arc| (arc.place, ArcKind::SampledInput, 0)),
+                )
+                .chain(
+                    transition
+                        .sampled_output_arcs
+                        .iter()
+                        .map(|arc| (arc.place, ArcKind::SampledOutput, 0)),
+                )
+                .chain(transition.overflow_arcs.iter().flat_map(|arc| {
+                    vec![
+                        (arc.primary, ArcKind::OverflowPrimary, arc.weight),
+                        (arc.overflow, ArcKind::Overflow, arc.weight),
+                    ]
+                }));
+
+            for (place, kind, weight) in arcs {
+                let p = self.place_index(place).expect("registered place");
+                incidence[t][p].push((kind, weight));
+            }
+            for arcs in &mut incidence[t] {
+                arcs.sort();
+            }
+        }
+
+        incidence
+    }
+
+    /// Whether `other` has the same structure as the net, i.e. there are
+    /// bijections between the places and between the transitions of both
+    /// nets which preserve the kinds, weights and directions of all arcs.
+    ///
+    /// Names, token counts, expressions, guards, the weight functions of
+    /// variable arcs and the samplers of sampled arcs are not compared. The
+    /// search backtracks over mappings of places, pruned by the arcs of each
+    /// place and by the transitions the mapped places are connected to. This
+    /// is fast for small and medium nets, but exponential in the worst case.
+    pub fn is_isomorphic(&self, other: &Petrinet<'_>) -> bool {
+        if self.places.len() != other.places.len()
+            || self.transitions.len() != other.transitions.len()
+        {
+            return false;
+        }
+
+        let a = self.incidence();
+        let b = other.incidence();
+        let candidates: Vec<Vec<usize>> = (0..self.places.len())
+            .map(|p| {
+                let signature_a = signature(&a, p);
+                (0..other.places.len())
+                    .filter(|&q| signature(&b, q) == signature_a)
+                    .collect()
+            })
+            .collect();
+
+        let mut used = vec![false; other.places.len()];
+        rows_match(&a, &b, &[]) && extend(&a, &b, &candidates, &mut Vec::new(), &mut used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_is_isomorphic() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 2).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // p1 -> t1 -> p2 =2=> t2 -> p3
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        // the same structure, built the other way round with other names
+        let q1 = Place::new(0, "q1");
+        let q2 = Place::new(0, "q2");
+        let q3 = Place::new(5, "q3");
+        let b1 = Arc::new(&q1, 1).expect("weight greater than zero");
+        let b2 = Arc::new(&q2, 2).expect("weight greater than zero");
+        let b3 = Arc::new(&q3, 1).expect("weight greater than zero");
+
+        let mut u2 = Transition::new();
+        u2.add_output(&b1);
+        u2.add_input(&b2);
+        let mut u1 = Transition::new();
+        u1.add_output(&b2);
+        u1.add_input(&b3);
+        let mut other = Petrinet::new();
+        other.add_transition(u2);
+        other.add_transition(u1);
+
+        assert!(net.is_isomorphic(&other));
+        assert!(other.is_isomorphic(&net));
+        assert!(net.is_isomorphic(&net));
+
+        // reversing the arcs of t2 breaks the isomorphism
+        let mut reversed = Transition::new();
+        reversed.add_output(&a2);
+        reversed.add_input(&a3);
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut different = Petrinet::new();
+        different.add_transition(t1);
+        different.add_transition(reversed);
+        assert!(!net.is_isomorphic(&different));
+    }
+
+    #[test]
+    fn test_is_isomorphic_needs_backtracking() {
+        // two cycles p1 -> p2 -> p1 and p3 -> p4 -> p3, which only differ in
+        // the weight of one arc each, so the first candidate may fail
+        let places: Vec<Place> = (0..4).map(|i| Place::new(0, &i.to_string())).collect();
+        let arcs: Vec<Arc<'_>> = places
+            .iter()
+            .map(|place| Arc::new(place, 1).expect("weight greater than zero"))
+            .collect();
+        let heavy = Arc::new(&places[3], 2).expect("weight greater than zero");
+
+        let cycles = |order: &[usize]| {
+            let mut net = Petrinet::new();
+            for &(from, to) in order.iter().map(|i| &[(0, 1), (1, 0), (2, 3), (3, 2)][*i]) {
+                let mut t = Transition::new();
+                t.add_input(&arcs[from]);
+                t.add_output(if to == 3 { &heavy } else { &arcs[to] });
+                net.add_transition(t);
+            }
+            net
+        };
+
+        assert!(cycles(&[0, 1, 2, 3]).is_isomorphic(&cycles(&[3, 2, 1, 0])));
+        assert!(!cycles(&[0, 1, 2, 3]).is_isomorphic(&cycles(&[0, 1, 2])));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::Write;
+
+use crate::Petrinet;
+
+/// Escape the characters with a special meaning in LaTeX.
+fn escape(label: &str) -> String {
+    let mut escaped = String::new();
+    for c in label.chars() {
+        match c {
+            '\\' => escaped.push_str("\\backslash "),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\sim "),
+            '^' => escaped.push_str("\\hat{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A matrix environment with one line per row and `&` between entries.
+fn matrix<T: ToString>(environment: &str, rows: &[Vec<T>]) -> String {
+    let rows: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" & ")
+        })
+        .collect();
+    format!(
+        "\\begin{{{0}}}\n{1}\n\\end{{{0}}}",
+        environment,
+        rows.join(" \\\\\n")
+    )
+}
+
+impl Petrinet<'_> {
+    /// Render the incidence matrix `C` and the initial marking `M_0` of the
+    /// net as LaTeX display math, for the `amsmath` package.
+    ///
+    /// The matrices are `pmatrix` environments. The place names label the
+    /// rows of `C` on the left, and the transition names, or their indices
+    /// if they are unnamed, label the columns above. Names are set upright,
+    /// with LaTeX special characters escaped.
+    pub fn to_latex(&self) -> String {
+        let label = |name: &str| format!("\\mathrm{{{}}}", escape(name));
+        let places: Vec<Vec<String>> = self
+            .places
+            .iter()
+            .map(|place| vec![label(place.name())])
+            .collect();
+        let transitions: Vec<String> = self
+            .transitions
+            .iter()
+            .enumerate()
+            .map(|(i, transition)| match transition.name() {
+                Some(name) => label(name),
+                None => format!("t_{{{}}}", i),
+            })
+            .collect();
+        let marking: Vec<Vec<u32>> = self
+            .initial_marking
+            .iter()
+            .map(|&tokens| vec![tokens])
+            .collect();
+
+        let mut latex = String::from("\\[\n");
+        writeln!(latex, "C = \\begin{{array}}{{@{{}}c@{{}}c@{{}}}}").unwrap();
+        writeln!(latex, "& {} \\\\", matrix("matrix", &[transitions])).unwrap();
+        writeln!(latex, "{} &", matrix("matrix", &places)).unwrap();
+        writeln!(latex, "{}", matrix("pmatrix", &self.incidence_matrix())).unwrap();
+        writeln!(latex, "\\end{{array}}").unwrap();
+        writeln!(latex, "\\qquad").unwrap();
+        writeln!(latex, "M_0 = {}", matrix("pmatrix", &marking)).unwrap();
+        latex.push_str("\\]\n");
+        latex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_to_latex() {
+        let p1 = Place::new(2, "p_1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::with_name("produce");
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        p1.set_tokens(0);
+
+        let expected = concat!(
+            "\\[\n",
+            "C = \\begin{array}{@{}c@{}c@{}}\n",
+            "& \\begin{matrix}\n",
+            "\\mathrm{produce} & t_{1}\n",
+            "\\end{matrix} \\\\\n",
+            "\\begin{matrix}\n",
+            "\\mathrm{p\\_1} \\\\\n",
+            "\\mathrm{p2}\n",
+            "\\end{matrix} &\n",
+            "\\begin{pmatrix}\n",
+            "-2 & 0 \\\\\n",
+            "1 & -1\n",
+            "\\end{pmatrix}\n",
+            "\\end{array}\n",
+            "\\qquad\n",
+            "M_0 = \\begin{pmatrix}\n",
+            "2 \\\\\n",
+            "0\n",
+            "\\end{pmatrix}\n",
+            "\\]\n",
+        );
+        assert_eq!(expected, net.to_latex(), "uses the initial marking");
+    }
+}
@@ -1,422 +1,143 @@
 #![warn(rust_2018_idioms)]
-
-use std::cell::Cell;
-use std::result;
-use std::vec::Vec;
-
-use rand::seq::SliceRandom;
-use rand::Rng;
-
-#[derive(Debug)]
-pub struct Arc<'a> {
-    weight: u32,
-    place: &'a Place,
-}
-
-impl<'a> Arc<'a> {
-    pub fn new(place: &'a Place, weight: u32) -> result::Result<Self, &str> {
-        if weight < 1 {
-            Err("weight must be greater 0")
-        } else {
-            Ok(Self { weight, place })
-        }
-    }
-
-    pub fn can_provide_required_tokens(&self) -> bool {
-        self.place.tokens.get() >= self.weight
-    }
-
-    pub fn consume_tokens(&self) {
-        self.place.tokens.set(self.place.tokens.get() - self.weight);
-    }
-
-    pub fn produce_tokens(&self) {
-        self.place.tokens.set(self.place.tokens.get() + self.weight);
-    }
-}
-
-#[derive(Debug)]
-pub struct Place {
-    tokens: Cell<u32>,
-    name: String,
-}
-
-impl Place {
-    pub fn new(tokens: u32, name: &str) -> Self {
-        Self {
-            tokens: Cell::new(tokens),
-            name: name.to_string(),
-        }
-    }
-
-    pub fn tokens(&self) -> u32 {
-        self.tokens.get()
-    }
-}
-
-#[derive(Default)]
-pub struct Transition<'a> {
-    input_arcs: Vec<&'a Arc<'a>>,
-    output_arcs: Vec<&'a Arc<'a>>,
-    expression: Option<Box<dyn Fn() -> bool + 'a>>,
-}
-
-impl<'a> Transition<'a> {
-    pub fn new() -> Self {
-        Self {
-            input_arcs: Vec::new(),
-            output_arcs: Vec::new(),
-            expression: None,
-        }
-    }
-
-    pub fn new_with_expression(func: impl Fn() -> bool + 'a) -> Self {
-        Self {
-            input_arcs: Vec::new(),
-            output_arcs: Vec::new(),
-            expression: Some(Box::new(func)),
-        }
-    }
-
-    pub fn add_input(&mut self, arc: &'a Arc<'a>) {
-        self.input_arcs.push(arc);
-    }
-
-    pub fn add_output(&mut self, arc: &'a Arc<'a>) {
-        self.output_arcs.push(arc);
-    }
-
-    pub fn is_enabled(&self) -> bool {
-        let all_arcs_enabled = self
-            .input_arcs
-            .iter()
-            .all(|arc| arc.can_provide_required_tokens());
-
-        if let Some(ref f) = self.expression {
-            all_arcs_enabled && f()
-        } else {
-            all_arcs_enabled
-        }
-    }
-
-    pub fn fire(&mut self) {
-        for v in self.input_arcs.iter_mut() {
-            v.consume_tokens();
-        }
-
-        for v in self.output_arcs.iter_mut() {
-            v.produce_tokens();
-        }
-    }
-}
-
-#[derive(Default)]
-pub struct Petrinet<'a> {
-    transitions: Vec<Transition<'a>>,
-}
-
-impl<'a> Petrinet<'a> {
-    pub fn new() -> Self {
-        Self {
-            transitions: Vec::new(),
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::result;
+
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod colored;
+#[cfg(feature = "std")]
+mod coverability;
+#[cfg(feature = "std")]
+mod dot;
+mod fixed;
+#[cfg(feature = "std")]
+mod frozen;
+#[cfg(feature = "std")]
+mod hierarchy;
+#[cfg(feature = "std")]
+mod interval;
+#[cfg(feature = "std")]
+mod invariants;
+#[cfg(feature = "std")]
+mod isomorphism;
+#[cfg(feature = "std")]
+mod latex;
+#[cfg(feature = "std")]
+mod lola;
+#[cfg(feature = "std")]
+mod marking;
+#[cfg(feature = "std")]
+mod monitor;
+#[cfg(feature = "std")]
+mod net;
+#[cfg(feature = "std")]
+mod owned;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "std")]
+mod pnml;
+#[cfg(feature = "std")]
+mod reachability;
+#[cfg(feature = "std")]
+mod reduction;
+#[cfg(feature = "std")]
+mod rules;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod stochastic;
+#[cfg(feature = "std")]
+mod structure;
+#[cfg(feature = "testgen")]
+mod testgen;
+#[cfg(feature = "std")]
+mod text;
+#[cfg(feature = "std")]
+mod timed;
+#[cfg(feature = "std")]
+mod validate;
+#[cfg(feature = "std")]
+mod wide;
+#[cfg(feature = "std")]
+mod workflow;
+
+#[cfg(feature = "std")]
+pub use crate::{
+    builder::{BuildError, NetBuilder},
+    colored::{
+        ColoredArc, ColoredExpressionArc, ColoredPlace, ColoredTransition, ColoredVariableArc,
+    },
+    coverability::{CoverabilityTree, OmegaMarking},
+    frozen::FrozenNet,
+    hierarchy::SubstitutionTransition,
+    interval::{AgedPlace, IntervalTransition, TimePetrinet, TimedToken},
+    invariants::weighted_token_sum,
+    lola::LolaError,
+    marking::{BitMarking, Marking, MarkingView},
+    monitor::InvariantViolated,
+    net::*,
+    owned::{ComposeError, MatrixError, OwnedArc, OwnedPetrinet, OwnedTransition},
+    pnml::PnmlError,
+    reachability::ReachabilityGraph,
+    stats::RunStats,
+    structure::Node,
+    text::{parse_net, ParseError, ParseErrorKind},
+    timed::TimedPetrinet,
+    validate::ValidationError,
+    wide::{Tokens, WidePetrinet},
+};
+pub use fixed::{FixedPetrinet, FixedTransition};
+#[cfg(feature = "testgen")]
+pub use testgen::random_net;
+
+/// Error returned when a transition cannot be fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FireError {
+    /// The transition is not enabled in the current marking.
+    NotEnabled,
+    /// There is no transition with this index.
+    NoSuchTransition(usize),
+    /// Firing would push the token count of a place beyond `u32::MAX`, see
+    /// `TokenPolicy::Checked`, or beyond the largest token count of a
+    /// `WidePetrinet`.
+    Overflow,
+}
+
+impl fmt::Display for FireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnabled => write!(f, "transition is not enabled"),
+            Self::NoSuchTransition(index) => write!(f, "no such transition {}", index),
+            Self::Overflow => write!(f, "token count would overflow"),
         }
     }
-
-    pub fn add_transition(&mut self, transition: Transition<'a>) {
-        self.transitions.push(transition)
-    }
-
-    pub fn step(&mut self) {
-        for transition in &mut self.transitions {
-            if transition.is_enabled() {
-                transition.fire();
-                break;
-            }
-        }
-    }
-}
-
-pub trait Scheduler {
-    fn schedule<'a>(&mut self, transitions: &'a [&Transition<'a>]) -> Option<&Transition<'a>>;
-}
-
-/// Choose a random transition firing order.
-pub struct RandomTransitionScheduler<R: Rng> {
-    rng: R,
-}
-
-impl<R: Rng> RandomTransitionScheduler<R> {
-    pub fn new(rng: R) -> Self {
-        Self { rng }
-    }
-}
-
-impl Default for RandomTransitionScheduler<rand::rngs::ThreadRng> {
-    fn default() -> Self {
-        Self::new(rand::thread_rng())
-    }
-}
-
-impl<R: Rng> Scheduler for RandomTransitionScheduler<R> {
-    fn schedule<'a>(&mut self, transitions: &'a [&Transition<'a>]) -> Option<&Transition<'a>> {
-        transitions.choose(&mut self.rng).copied()
-    }
-}
-
-/// Fire transitions of parallel branches in round-robin fashion.
-pub struct FairBranchScheduler {}
-
-impl FairBranchScheduler {
-    pub fn new() -> Self {
-        Self {}
-    }
-}
-
-impl Default for FairBranchScheduler {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Scheduler for FairBranchScheduler {
-    fn schedule<'a>(&mut self, _transitions: &'a [&Transition<'a>]) -> Option<&Transition<'a>> {
-        todo!("implement schedule");
-    }
-}
-
-/// Prefer one branch over the others when firing transitions.
-pub struct SequentialBranchScheduler {}
-
-impl SequentialBranchScheduler {
-    pub fn new() -> Self {
-        Self {}
-    }
 }
 
-impl Default for SequentialBranchScheduler {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Whether a place holding `tokens` covers the summed weight `demand` of the
+/// input arcs of a transition, the token check of the firing rule.
+pub(crate) fn covers(tokens: u32, demand: u64) -> bool {
+    u64::from(tokens) >= demand
 }
 
-impl Scheduler for SequentialBranchScheduler {
-    fn schedule<'a>(&mut self, _transitions: &'a [&Transition<'a>]) -> Option<&Transition<'a>> {
-        todo!("implement schedule");
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    use std::ptr;
-
-    use rand::rngs::mock::StepRng;
-
-    #[test]
-    fn it_works() {
-        let place = super::Place::new(1, "a");
-        let arc = super::Arc::new(&place, 1).expect("weight greater than zero");
-
-        let mut transition = super::Transition::new();
-        transition.add_input(&arc);
-
-        transition.fire();
-    }
-
-    #[test]
-    fn test_transition_without_expression() {
-        let p1 = super::Place::new(2, "p1");
-        let p2 = super::Place::new(2, "p2");
-        let p3 = super::Place::new(0, "p3");
-
-        let a1 = super::Arc::new(&p1, 2).expect("weight greater than zero");
-        let a2 = super::Arc::new(&p2, 1).expect("weight greater than zero");
-        let a3 = super::Arc::new(&p3, 2).expect("weight greater than zero");
-
-        let mut t = super::Transition::new();
-        t.add_input(&a1);
-        t.add_input(&a2);
-
-        t.add_output(&a3);
-
-        assert!(t.is_enabled());
-
-        t.fire();
-
-        assert_eq!(0, p1.tokens());
-        assert_eq!(1, p2.tokens());
-        assert_eq!(2, p3.tokens(), "number of tokens on place p3");
-
-        assert!(
-            !t.is_enabled(),
-            "must not fire because input tokens are not sufficient anymore"
-        );
-
-        assert_eq!(0, p1.tokens());
-        assert_eq!(1, p2.tokens());
-        assert_eq!(2, p3.tokens(), "number of tokens on place p3");
-    }
-
-    #[test]
-    fn test_transition_with_expression() {
-        let p1 = Place::new(2, "p1");
-        let p2 = Place::new(2, "p2");
-        let p3 = Place::new(0, "p3");
-
-        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
-        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
-        let a3 = Arc::new(&p3, 2).expect("weight greater than zero");
-
-        // with expression which evaluates to true
-        let mut t = Transition::new_with_expression(|| true);
-        t.add_input(&a1);
-        t.add_input(&a2);
-
-        t.add_output(&a3);
-
-        assert!(
-            t.is_enabled(),
-            "must be enabled since transition expression is true"
-        );
-
-        t.fire();
-
-        assert_eq!(2, p3.tokens(), "number of tokens on place p3");
-
-        let p1 = Place::new(2, "p1");
-        let p2 = Place::new(2, "p2");
-        let p3 = Place::new(0, "p3");
-
-        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
-        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
-        let a3 = Arc::new(&p3, 2).expect("weight greater than zero");
-
-        // with expression which evaluates to false
-        let mut t = Transition::new_with_expression(|| false);
-        t.add_input(&a1);
-        t.add_input(&a2);
-
-        t.add_output(&a3);
-
-        assert!(
-            !t.is_enabled(),
-            "must not be enabled since transition expression is false"
-        );
+/// The token count of a place holding `tokens` after firing a transition
+/// which takes `demand` tokens from it and puts `supply` tokens on it. Fails
+/// with `FireError::NotEnabled` if `tokens` don't cover `demand`, and with
+/// `FireError::Overflow` if the result doesn't fit into a `u32`.
+pub(crate) fn fired_tokens(
+    tokens: u32,
+    demand: u64,
+    supply: u64,
+) -> result::Result<u32, FireError> {
+    if !covers(tokens, demand) {
+        return Err(FireError::NotEnabled);
     }
 
-    #[test]
-    fn test_arc() {
-        let place = super::Place::new(2, "p");
-        let arc = super::Arc::new(&place, 2).expect("weight greater than zero");
-        assert!(arc.can_provide_required_tokens());
-
-        let place = super::Place::new(1, "p");
-        let arc = super::Arc::new(&place, 2).expect("weight greater than zero");
-        assert!(!arc.can_provide_required_tokens());
-
-        let place = super::Place::new(3, "p");
-        let arc = super::Arc::new(&place, 2).expect("weight greater than zero");
-        assert!(arc.can_provide_required_tokens());
-
-        let place = super::Place::new(2, "");
-        assert!(super::Arc::new(&place, 0).is_err());
-    }
-
-    #[test]
-    fn test_petrinet() {
-        let p1 = super::Place::new(2, "P1");
-        let p2 = super::Place::new(2, "P2");
-
-        let p3 = super::Place::new(0, "P3");
-
-        let a1 = super::Arc::new(&p1, 2).expect("weight greater than zero");
-        let a2 = super::Arc::new(&p2, 1).expect("weight greater than zero");
-
-        let a3 = super::Arc::new(&p3, 2).expect("weight greater than zero");
-
-        let mut t = super::Transition::new();
-        t.add_input(&a1);
-        t.add_input(&a2);
-
-        t.add_output(&a3);
-
-        let mut net = super::Petrinet::new();
-
-        net.add_transition(t);
-
-        net.step();
-
-        net.step();
-    }
-
-    // https://en.wikipedia.org/wiki/Petri_net#/media/File:Detailed_petri_net.png
-    #[test]
-    fn test_more_complex_petrinet() {
-        let p1 = super::Place::new(1, "P1");
-        let p2 = super::Place::new(0, "P2");
-        let p3 = super::Place::new(2, "P3");
-        let p4 = super::Place::new(1, "P4");
-
-        let a1 = super::Arc::new(&p1, 1).expect("weight greater than zero");
-
-        let a2 = super::Arc::new(&p2, 1).expect("weight greater than zero");
-        let a3 = super::Arc::new(&p3, 1).expect("weight greater than zero");
-
-        let a4 = super::Arc::new(&p2, 1).expect("weight greater than zero");
-        let a5 = super::Arc::new(&p3, 1).expect("weight greater than zero");
-
-        let a6 = super::Arc::new(&p4, 1).expect("weight greater than zero");
-
-        let a7 = super::Arc::new(&p1, 1).expect("weight greater than zero");
-
-        let mut t1 = super::Transition::new();
-        let mut t2 = super::Transition::new();
-
-        t1.add_input(&a1);
-        t1.add_output(&a2);
-        t1.add_output(&a3);
-
-        t2.add_input(&a4);
-        t2.add_input(&a5);
-        t2.add_output(&a6);
-        t2.add_output(&a7);
-
-        let mut petri = super::Petrinet::new();
-        petri.add_transition(t1);
-        petri.add_transition(t2);
-
-        petri.step();
-
-        assert_eq!(0, p1.tokens());
-        assert_eq!(1, p2.tokens());
-        assert_eq!(3, p3.tokens());
-
-        petri.step();
-
-        assert_eq!(1, p1.tokens());
-        assert_eq!(0, p2.tokens());
-        assert_eq!(2, p3.tokens());
-        assert_eq!(2, p4.tokens());
-
-        petri.step();
-        petri.step();
-
-        assert_eq!(3, p4.tokens());
-    }
-
-    #[test]
-    fn test_random_transition_scheduler() {
-        let rng = StepRng::new(2, 1);
-        let mut scheduler = super::RandomTransitionScheduler::<StepRng>::new(rng);
-
-        let t1 = super::Transition::new();
-        let t2 = super::Transition::new();
-
-        if let Some(t) = scheduler.schedule(&[&t1, &t2]) {
-            assert!(ptr::eq(t, &t1) || ptr::eq(t, &t2));
-        } else {
-            panic!("scheduler didn't return any valid transition");
-        }
-    }
+    (u64::from(tokens) - demand)
+        .checked_add(supply)
+        .and_then(|tokens| u32::try_from(tokens).ok())
+        .ok_or(FireError::Overflow)
 }
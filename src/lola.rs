@@ -0,0 +1,424 @@
+//! Reading and writing nets in the low-level input format of the LoLA model
+//! checker:
+//!
+//! ```text
+//! PLACE p1, p2;
+//! MARKING p1: 2;
+//! TRANSITION t1
+//! CONSUME p1: 1;
+//! PRODUCE p2: 2;
+//! ```
+//!
+//! Comments are enclosed in braces, or written as `/* */` or `//`. `SAFE`
+//! declarations of places and fairness assumptions of transitions are
+//! accepted, but ignored.
+
+use std::fmt::Write;
+use std::result;
+
+use crate::text::Position;
+use crate::{OwnedPetrinet, ParseError, ParseErrorKind};
+
+/// Error returned by `OwnedPetrinet::from_lola`, with the position it
+/// occurred at.
+pub type LolaError = ParseError;
+
+/// Characters which end a name.
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | ';' | ':' | '(' | ')' | '{' | '}')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Colon,
+    Semicolon,
+    Comma,
+}
+
+/// Split `input` into tokens with their positions, skipping comments, and
+/// return the position of the end of the input along with them.
+fn tokenize(input: &str) -> result::Result<(Vec<(Token, Position)>, Position), LolaError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut position = Position { line: 1, column: 1 };
+    let mut i = 0;
+
+    // advance past the character at `i`
+    let advance = |i: &mut usize, position: &mut Position| {
+        if chars[*i] == '\n' {
+            position.line += 1;
+            position.column = 1;
+        } else {
+            position.column += 1;
+        }
+        *i += 1;
+    };
+
+    while i < chars.len() {
+        let start = position;
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let token = match c {
+            c if c.is_whitespace() => {
+                advance(&mut i, &mut position);
+                continue;
+            }
+            '{' | '/' if c == '{' || next == Some('*') => {
+                let end = if c == '{' { "}" } else { "*/" };
+                let end: Vec<char> = end.chars().collect();
+                advance(&mut i, &mut position);
+                if c == '/' {
+                    advance(&mut i, &mut position);
+                }
+                while !chars[i..].starts_with(&end) {
+                    if i == chars.len() {
+                        return Err(start.error(ParseErrorKind::UnterminatedComment));
+                    }
+                    advance(&mut i, &mut position);
+                }
+                for _ in 0..end.len() {
+                    advance(&mut i, &mut position);
+                }
+                continue;
+            }
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    advance(&mut i, &mut position);
+                }
+                continue;
+            }
+            ':' => Token::Colon,
+            ';' => Token::Semicolon,
+            ',' => Token::Comma,
+            c if is_delimiter(c) => {
+                return Err(start.error(ParseErrorKind::UnexpectedCharacter(c)));
+            }
+            _ => {
+                let mut word = String::new();
+                while i < chars.len() && !is_delimiter(chars[i]) {
+                    word.push(chars[i]);
+                    advance(&mut i, &mut position);
+                }
+                tokens.push((Token::Word(word), start));
+                continue;
+            }
+        };
+        advance(&mut i, &mut position);
+        tokens.push((token, start));
+    }
+
+    Ok((tokens, position))
+}
+
+struct Parser {
+    tokens: Vec<(Token, Position)>,
+    next: usize,
+    end: Position,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word == keyword)
+    }
+
+    /// Position of the next token, or of the end of the input.
+    fn position(&self) -> Position {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |&(_, position)| position)
+    }
+
+    fn expect(&mut self, token: Token, expected: &'static str) -> result::Result<(), LolaError> {
+        if self.peek() == Some(&token) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(self.position().error(ParseErrorKind::Expected(expected)))
+        }
+    }
+
+    fn keyword(&mut self, keyword: &'static str) -> result::Result<(), LolaError> {
+        self.expect(Token::Word(keyword.to_string()), keyword)
+    }
+
+    fn word(&mut self, expected: &'static str) -> result::Result<(String, Position), LolaError> {
+        let position = self.position();
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                let word = word.clone();
+                self.next += 1;
+                Ok((word, position))
+            }
+            _ => Err(position.error(ParseErrorKind::Expected(expected))),
+        }
+    }
+
+    fn number(&mut self, expected: &'static str) -> result::Result<(u32, Position), LolaError> {
+        let (word, position) = self.word(expected)?;
+        word.parse()
+            .map(|number| (number, position))
+            .map_err(|_| position.error(ParseErrorKind::InvalidNumber(word)))
+    }
+
+    fn place(&mut self, net: &OwnedPetrinet) -> result::Result<usize, LolaError> {
+        let (name, position) = self.word("place name")?;
+        net.places()
+            .iter()
+            .position(|place| place.name() == name)
+            .ok_or_else(|| position.error(ParseErrorKind::UnknownPlace(name)))
+    }
+
+    /// A possibly empty, comma separated list of `place: number` pairs,
+    /// terminated by a semicolon.
+    fn place_numbers(
+        &mut self,
+        net: &OwnedPetrinet,
+        expected: &'static str,
+    ) -> result::Result<Vec<(usize, u32, Position)>, LolaError> {
+        let mut entries = Vec::new();
+        if self.peek() == Some(&Token::Semicolon) {
+            self.next += 1;
+            return Ok(entries);
+        }
+
+        loop {
+            let place = self.place(net)?;
+            self.expect(Token::Colon, "':'")?;
+            let (number, position) = self.number(expected)?;
+            entries.push((place, number, position));
+
+            if self.peek() != Some(&Token::Comma) {
+                self.expect(Token::Semicolon, "';'")?;
+                return Ok(entries);
+            }
+            self.next += 1;
+        }
+    }
+
+    /// The place declarations, optionally grouped by `SAFE` declarations.
+    fn places(&mut self, net: &mut OwnedPetrinet) -> result::Result<(), LolaError> {
+        self.keyword("PLACE")?;
+        loop {
+            if self.peek_keyword("SAFE") {
+                self.next += 1;
+                if self.peek() != Some(&Token::Colon) {
+                    self.number("capacity")?;
+                }
+                self.expect(Token::Colon, "':'")?;
+            }
+
+            loop {
+                let (name, position) = self.word("place name")?;
+                if net.places().iter().any(|place| place.name() == name) {
+                    return Err(position.error(ParseErrorKind::DuplicateName(name)));
+                }
+                net.add_place(0, &name);
+
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.next += 1;
+            }
+            self.expect(Token::Semicolon, "';'")?;
+
+            if self.peek_keyword("MARKING") {
+                return Ok(());
+            }
+        }
+    }
+
+    fn transition(&mut self, net: &mut OwnedPetrinet) -> result::Result<(), LolaError> {
+        if self.peek_keyword("STRONG") || self.peek_keyword("WEAK") {
+            self.next += 1;
+            self.keyword("FAIR")?;
+        } else if self.peek_keyword("FAIR") {
+            self.next += 1;
+        }
+
+        self.keyword("TRANSITION")?;
+        let (name, position) = self.word("transition name")?;
+        if net
+            .transitions()
+            .iter()
+            .any(|transition| transition.name() == Some(&name))
+        {
+            return Err(position.error(ParseErrorKind::DuplicateName(name)));
+        }
+
+        self.keyword("CONSUME")?;
+        let inputs = self.place_numbers(net, "arc weight")?;
+        self.keyword("PRODUCE")?;
+        let outputs = self.place_numbers(net, "arc weight")?;
+
+        let transition = net.add_transition(Some(&name));
+        for (place, weight, position) in inputs {
+            net.add_input(transition, place, weight)
+                .map_err(|_| position.error(ParseErrorKind::ZeroWeight))?;
+        }
+        for (place, weight, position) in outputs {
+            net.add_output(transition, place, weight)
+                .map_err(|_| position.error(ParseErrorKind::ZeroWeight))?;
+        }
+        Ok(())
+    }
+}
+
+impl OwnedPetrinet {
+    /// Parse a net in the LoLA format described in the module documentation.
+    /// Places not listed in the marking hold no tokens.
+    pub fn from_lola(input: &str) -> result::Result<Self, LolaError> {
+        let (tokens, end) = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            next: 0,
+            end,
+        };
+
+        let mut net = Self::new();
+        parser.places(&mut net)?;
+        parser.keyword("MARKING")?;
+        for (place, tokens, _) in parser.place_numbers(&net, "token count")? {
+            net.places()[place].set_tokens(tokens);
+        }
+        while parser.peek().is_some() {
+            parser.transition(&mut net)?;
+        }
+
+        Ok(net)
+    }
+
+    /// Write the net in the LoLA format, which `from_lola` reads back.
+    /// Unnamed transitions are named after their index, like `t0`, and
+    /// several arcs between the same place and transition are merged into
+    /// one. The merged weight may exceed `u32::MAX`, which `from_lola` then
+    /// rejects.
+    ///
+    /// Names are written as they are, so they must not contain whitespace or
+    /// any of `,;:(){}`, and must be distinct.
+    pub fn to_lola(&self) -> String {
+        let names: Vec<&str> = self.places().iter().map(|place| place.name()).collect();
+        let arcs = |arcs: &[crate::OwnedArc]| {
+            let mut weights: Vec<(usize, u64)> = Vec::new();
+            for arc in arcs {
+                match weights.iter_mut().find(|(place, _)| *place == arc.place()) {
+                    Some((_, weight)) => *weight += u64::from(arc.weight()),
+                    None => weights.push((arc.place(), u64::from(arc.weight()))),
+                }
+            }
+            weights
+                .iter()
+                .map(|&(place, weight)| format!("{}: {}", names[place], weight))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut lola = String::new();
+        writeln!(lola, "PLACE {};", names.join(", ")).unwrap();
+        let marking: Vec<String> = self
+            .places()
+            .iter()
+            .filter(|place| place.tokens() > 0)
+            .map(|place| format!("{}: {}", place.name(), place.tokens()))
+            .collect();
+        writeln!(lola, "MARKING {};", marking.join(", ")).unwrap();
+
+        for (i, transition) in self.transitions().iter().enumerate() {
+            lola.push('\n');
+            match transition.name() {
+                Some(name) => writeln!(lola, "TRANSITION {}", name).unwrap(),
+                None => writeln!(lola, "TRANSITION t{}", i).unwrap(),
+            }
+            writeln!(lola, "CONSUME {};", arcs(transition.inputs())).unwrap();
+            writeln!(lola, "PRODUCE {};", arcs(transition.outputs())).unwrap();
+        }
+
+        lola
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_lola() {
+        let net = OwnedPetrinet::from_lola(
+            "{ a producer } PLACE SAFE 1: p1; p2, p3;
+             MARKING p1: 2, p3: 1; // p2 is empty
+             TRANSITION t1 CONSUME p1: 1; PRODUCE p2: 2;
+             /* fairness is ignored */ STRONG FAIR
+             TRANSITION t2 CONSUME p2: 3, p3: 1; PRODUCE ;",
+        )
+        .expect("valid net");
+
+        assert_eq!(vec![2, 0, 1], net.marking());
+        assert_eq!(2, net.transitions().len());
+        assert_eq!(Some("t2"), net.transitions()[1].name());
+        assert_eq!(2, net.transitions()[0].outputs()[0].weight());
+        assert_eq!(2, net.transitions()[1].inputs()[1].place());
+        assert!(net.transitions()[1].outputs().is_empty());
+
+        assert_eq!(Ok(net.clone()), OwnedPetrinet::from_lola(&net.to_lola()));
+        assert_eq!(
+            concat!(
+                "PLACE p1, p2, p3;\n",
+                "MARKING p1: 2, p3: 1;\n",
+                "\n",
+                "TRANSITION t1\n",
+                "CONSUME p1: 1;\n",
+                "PRODUCE p2: 2;\n",
+                "\n",
+                "TRANSITION t2\n",
+                "CONSUME p2: 3, p3: 1;\n",
+                "PRODUCE ;\n",
+            ),
+            net.to_lola()
+        );
+    }
+
+    #[test]
+    fn test_to_lola_merged_weights() {
+        let mut net = OwnedPetrinet::new();
+        let p = net.add_place(0, "p");
+        let t = net.add_transition(Some("t"));
+        net.add_input(t, p, u32::MAX).expect("valid arc");
+        net.add_input(t, p, 1).expect("valid arc");
+
+        assert!(net.to_lola().contains("CONSUME p: 4294967296;"));
+    }
+
+    #[test]
+    fn test_from_lola_errors() {
+        let error = |input| OwnedPetrinet::from_lola(input).unwrap_err().to_string();
+
+        assert_eq!("1:1: expected PLACE", error("MARKING;"));
+        assert_eq!("1:19: unknown place p2", error("PLACE p1; MARKING p2: 1;"));
+        assert_eq!(
+            "2:1: expected ';'",
+            error("PLACE p1; MARKING p1: 1\nTRANSITION")
+        );
+        assert_eq!(
+            "1:43: arc weight must be greater than zero",
+            error("PLACE p; MARKING; TRANSITION t CONSUME p: 0; PRODUCE;")
+        );
+        assert_eq!(
+            "1:23: invalid number \"x\"",
+            error("PLACE p1; MARKING p1: x;")
+        );
+        assert_eq!(
+            "1:11: p1 is declared twice",
+            error("PLACE p1, p1; MARKING;")
+        );
+        assert_eq!("1:11: unterminated comment", error("PLACE p1; { MARKING;"));
+        assert_eq!(
+            "1:40: expected PRODUCE",
+            error("PLACE p; MARKING; TRANSITION t CONSUME;")
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::borrow::Borrow;
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::ptr;
+use std::slice;
+use std::vec;
+
+use crate::Place;
+
+/// Token counts of all places of a net, in the canonical place order of the
+/// net.
+///
+/// Markings can be used as keys of hash maps and are ordered
+/// lexicographically. They dereference to a slice of token counts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Marking(Vec<u32>);
+
+impl Marking {
+    /// The token counts, in the canonical place order.
+    pub fn into_vec(self) -> Vec<u32> {
+        self.0
+    }
+
+    /// Whether the marking holds at least as many tokens as `other` on
+    /// every place, as used to detect unboundedness in coverability
+    /// analysis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the markings have different lengths.
+    pub fn dominates(&self, other: &Self) -> bool {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "markings must have the same length"
+        );
+        self.iter().zip(other.iter()).all(|(a, b)| a >= b)
+    }
+}
+
+impl From<Vec<u32>> for Marking {
+    fn from(tokens: Vec<u32>) -> Self {
+        Self(tokens)
+    }
+}
+
+impl FromIterator<u32> for Marking {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Marking {
+    type Item = u32;
+    type IntoIter = vec::IntoIter<u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'m> IntoIterator for &'m Marking {
+    type Item = &'m u32;
+    type IntoIter = slice::Iter<'m, u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Deref for Marking {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl Borrow<[u32]> for Marking {
+    fn borrow(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl PartialEq<Vec<u32>> for Marking {
+    fn eq(&self, other: &Vec<u32>) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Marking> for Vec<u32> {
+    fn eq(&self, other: &Marking) -> bool {
+        *self == other.0
+    }
+}
+
+/// The marking of a safe net, where every place holds at most one token,
+/// with one bit per place in the canonical place order.
+///
+/// A bit marking takes a 32nd of the memory of a `Marking` and hashes
+/// faster, which matters when searching large state spaces, see
+/// `Petrinet::safe_reachable_markings`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BitMarking {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl BitMarking {
+    /// Pack `marking`, or return `None` if a place holds more than one
+    /// token.
+    pub fn from_marking(marking: &[u32]) -> Option<Self> {
+        let mut bits = vec![0; marking.len().div_ceil(64)];
+        for (p, &tokens) in marking.iter().enumerate() {
+            match tokens {
+                0 => {}
+                1 => bits[p / 64] |= 1 << (p % 64),
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            bits,
+            len: marking.len(),
+        })
+    }
+
+    /// Unpack the marking into token counts.
+    pub fn to_marking(&self) -> Marking {
+        (0..self.len)
+            .map(|p| u32::from(self.is_marked(p)))
+            .collect()
+    }
+
+    /// The number of places.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the place at `place` holds a token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no place at `place`.
+    pub fn is_marked(&self, place: usize) -> bool {
+        assert!(place < self.len, "no such place {}", place);
+        self.bits[place / 64] & (1 << (place % 64)) != 0
+    }
+}
+
+/// Read-only view of the token counts of a set of places, handed to guards.
+///
+/// Places are looked up by name. If several places share a name, the first
+/// one wins.
+#[derive(Debug)]
+pub struct MarkingView<'v> {
+    places: Vec<&'v Place>,
+}
+
+impl<'v> MarkingView<'v> {
+    /// A view of `places`, ignoring duplicates.
+    pub(crate) fn new(places: impl IntoIterator<Item = &'v Place>) -> Self {
+        let mut view = Self { places: Vec::new() };
+        for place in places {
+            if !view.places.iter().any(|p| ptr::eq(*p, place)) {
+                view.places.push(place);
+            }
+        }
+
+        view
+    }
+
+    /// The token count of the place with the given name, if the view has
+    /// such a place.
+    pub fn tokens(&self, name: &str) -> Option<u32> {
+        self.places
+            .iter()
+            .find(|place| place.name() == name)
+            .map(|place| place.tokens())
+    }
+
+    /// Names and token counts of all places of the view.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> + '_ {
+        self.places
+            .iter()
+            .map(|place| (place.name(), place.tokens()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marking_view() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(2, "p2");
+        let shadow = Place::new(3, "p1");
+
+        let view = MarkingView::new(vec![&p1, &p2, &p1, &shadow]);
+        assert_eq!(Some(1), view.tokens("p1"), "first place wins");
+        assert_eq!(Some(2), view.tokens("p2"));
+        assert_eq!(None, view.tokens("p3"));
+        assert_eq!(
+            vec![("p1", 1), ("p2", 2), ("p1", 3)],
+            view.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_marking() {
+        use std::collections::{BTreeSet, HashMap};
+
+        let a = Marking::from(vec![1, 2]);
+        let b: Marking = vec![1, 3].into_iter().collect();
+        assert!(b.dominates(&a));
+        assert!(!a.dominates(&b));
+        assert!(a.dominates(&a));
+        assert!(a < b, "lexicographic order");
+
+        let mut states = HashMap::new();
+        states.insert(a.clone(), 0);
+        assert_eq!(Some(&0), states.get(&[1, 2][..]), "looked up by slice");
+
+        let sorted: BTreeSet<_> = vec![b.clone(), a.clone()].into_iter().collect();
+        assert_eq!(vec![&a, &b], sorted.iter().collect::<Vec<_>>());
+        assert_eq!(vec![1, 2], a.into_vec());
+    }
+
+    #[test]
+    fn test_bit_marking() {
+        let mut tokens = vec![0; 70];
+        tokens[0] = 1;
+        tokens[63] = 1;
+        tokens[64] = 1;
+        let bits = BitMarking::from_marking(&tokens).expect("safe marking");
+        assert_eq!(70, bits.len());
+        assert!(bits.is_marked(63) && bits.is_marked(64));
+        assert!(!bits.is_marked(69));
+        assert_eq!(tokens, bits.to_marking());
+
+        tokens[5] = 2;
+        assert_eq!(None, BitMarking::from_marking(&tokens));
+        assert!(BitMarking::from_marking(&[])
+            .expect("safe marking")
+            .is_empty());
+    }
+}
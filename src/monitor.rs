@@ -0,0 +1,148 @@
+//! Runtime verification of invariants while stepping a net.
+
+use std::error;
+use std::fmt;
+use std::result;
+
+use crate::{Marking, Petrinet};
+
+/// Error returned by the checked stepping methods when an invariant
+/// registered with `Petrinet::add_invariant` doesn't hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantViolated {
+    invariant: usize,
+    step: usize,
+    marking: Marking,
+}
+
+impl InvariantViolated {
+    /// Index of the violated invariant, in the order they were added.
+    pub fn invariant(&self) -> usize {
+        self.invariant
+    }
+
+    /// The step of the run which violated the invariant, starting at 1.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// The marking violating the invariant.
+    pub fn marking(&self) -> &[u32] {
+        &self.marking
+    }
+}
+
+impl fmt::Display for InvariantViolated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invariant {} violated in step {} by marking {:?}",
+            self.invariant,
+            self.step,
+            self.marking()
+        )
+    }
+}
+
+impl error::Error for InvariantViolated {}
+
+impl<'a> Petrinet<'a> {
+    /// Register a predicate on the marking, in registration order, which
+    /// `try_step` and `try_run_until_deadlock` check after every step.
+    /// Plain stepping methods and analyses don't check invariants.
+    pub fn add_invariant(&mut self, f: impl Fn(&[u32]) -> bool + 'a) {
+        self.monitors.push(Box::new(f));
+    }
+
+    /// Check the invariants against the current marking.
+    fn check_invariants(&self, step: usize) -> result::Result<(), InvariantViolated> {
+        if self.monitors.is_empty() {
+            return Ok(());
+        }
+
+        let marking = self.marking();
+        match self.monitors.iter().position(|holds| !holds(&marking)) {
+            Some(invariant) => Err(InvariantViolated {
+                invariant,
+                step,
+                marking,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `step`, but fail if the marking after the step violates an
+    /// invariant. The net stays in the violating marking.
+    pub fn try_step(&mut self) -> result::Result<bool, InvariantViolated> {
+        if !self.step() {
+            return Ok(false);
+        }
+
+        self.check_invariants(1)?;
+        Ok(true)
+    }
+
+    /// Like `run_until_deadlock`, but stop with an error as soon as a step
+    /// violates an invariant.
+    pub fn try_run_until_deadlock(
+        &mut self,
+        max_steps: usize,
+    ) -> result::Result<usize, InvariantViolated> {
+        let mut steps = 0;
+        while steps < max_steps && self.step() {
+            steps += 1;
+            self.check_invariants(steps)?;
+        }
+
+        Ok(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_invariants() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(1, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // t1 moves tokens from p1 to p3, t2 from p3 to p2
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a3);
+        let mut t2 = Transition::new();
+        t2.add_input(&a3);
+        t2.add_output(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        assert_eq!(vec![("p1", 2), ("p3", 1), ("p2", 0)], net.named_marking());
+        net.add_invariant(|marking| marking.iter().sum::<u32>() == 3);
+        assert_eq!(Ok(5), net.try_run_until_deadlock(10));
+        assert_eq!(Ok(false), net.try_step());
+
+        net.reset();
+        net.add_invariant(|marking| marking[0] + marking[1] == 3);
+        assert_eq!(Ok(true), net.try_step(), "t1 keeps p1 + p3");
+        let error = net.try_run_until_deadlock(10).unwrap_err();
+        assert_eq!(1, error.invariant());
+        assert_eq!(2, error.step());
+        assert_eq!(&[0, 2, 1], error.marking());
+        assert_eq!(
+            "invariant 1 violated in step 2 by marking [0, 2, 1]",
+            error.to_string()
+        );
+        assert_eq!(
+            vec![0, 2, 1],
+            net.marking(),
+            "stays in the violating marking"
+        );
+    }
+}
@@ -0,0 +1,3871 @@
+use core::fmt;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::error;
+use std::ptr;
+use std::result;
+use std::vec::Vec;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+
+use crate::{covers, FireError, Marking, MarkingView, SubstitutionTransition};
+
+/// Error returned when an arc cannot be created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArcError {
+    /// Arcs must have a weight greater than 0.
+    ZeroWeight,
+    /// There is no place with this index.
+    NoSuchPlace(usize),
+    /// There is no transition with this index.
+    NoSuchTransition(usize),
+}
+
+impl fmt::Display for ArcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroWeight => write!(f, "weight must be greater 0"),
+            Self::NoSuchPlace(index) => write!(f, "no such place {}", index),
+            Self::NoSuchTransition(index) => write!(f, "no such transition {}", index),
+        }
+    }
+}
+
+impl error::Error for ArcError {}
+
+#[derive(Debug)]
+pub struct Arc<'a> {
+    pub(crate) weight: u32,
+    pub(crate) place: &'a Place,
+}
+
+impl<'a> Arc<'a> {
+    pub fn new(place: &'a Place, weight: u32) -> result::Result<Self, ArcError> {
+        if weight < 1 {
+            Err(ArcError::ZeroWeight)
+        } else {
+            Ok(Self { weight, place })
+        }
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    pub fn place(&self) -> &'a Place {
+        self.place
+    }
+
+    pub fn can_provide_required_tokens(&self) -> bool {
+        self.place.tokens.get() >= self.weight
+    }
+
+    pub fn consume_tokens(&self) {
+        self.place.tokens.set(self.place.tokens.get() - self.weight);
+    }
+
+    pub fn produce_tokens(&self) {
+        self.place.tokens.set(self.place.tokens.get() + self.weight);
+    }
+
+    /// Used as output arc, whether producing `weight` tokens would push the
+    /// place above its capacity.
+    pub fn would_exceed_capacity(&self) -> bool {
+        match self.place.capacity {
+            Some(capacity) => {
+                u64::from(self.place.tokens.get()) + u64::from(self.weight) > u64::from(capacity)
+            }
+            None => false,
+        }
+    }
+
+    /// Used as inhibitor arc, the arc blocks its transition while the place
+    /// holds at least `weight` tokens.
+    pub fn inhibits(&self) -> bool {
+        self.place.tokens.get() >= self.weight
+    }
+}
+
+/// A weight computed from the marking, see `VariableArc`.
+type WeightFn<'a> = Box<dyn Fn(&MarkingView<'_>) -> u32 + 'a>;
+
+/// An arc whose weight is computed from the marking, e.g. to move all tokens
+/// of a place at once. Arcs of constant weight should use `Arc`, which is
+/// cheaper.
+///
+/// Like a guard, the weight function sees the token counts of all places
+/// connected to the transition. It is evaluated once per enablement check
+/// and once per firing, before any token moves, and the firing uses the
+/// weight it checked enablement with. Hence a weight function on an input and
+/// one on an output arc see the same marking. A weight of 0 moves no tokens.
+pub struct VariableArc<'a> {
+    pub(crate) place: &'a Place,
+    weight: WeightFn<'a>,
+}
+
+impl<'a> VariableArc<'a> {
+    pub fn new(place: &'a Place, weight: impl Fn(&MarkingView<'_>) -> u32 + 'a) -> Self {
+        Self {
+            place,
+            weight: Box::new(weight),
+        }
+    }
+
+    pub fn place(&self) -> &'a Place {
+        self.place
+    }
+}
+
+/// A sampler drawing a weight, see `SampledArc`.
+type SampleFn<'a> = Box<dyn Fn(&mut dyn RngCore) -> u32 + 'a>;
+
+/// An arc whose weight is drawn at random, e.g. to consume a random number
+/// of tokens per firing.
+///
+/// The weight is drawn when it is first needed, and kept until the
+/// transition of the arc fires through `Transition::fire` or a stepping
+/// method of the net. Enablement is checked against the drawn weight, and
+/// firing moves exactly that many tokens, so a transition never flickers
+/// between enabled and not enabled without the marking changing. If the
+/// places don't hold enough tokens for the drawn weight, the transition
+/// waits until they do. Analyses like `Petrinet::reachability_graph` treat
+/// the drawn weights as constant.
+pub struct SampledArc<'a> {
+    pub(crate) place: &'a Place,
+    sampler: SampleFn<'a>,
+    // without the lifetime, which would make the arc invariant over it
+    rng: RefCell<Box<dyn RngCore>>,
+    weight: Cell<Option<u32>>,
+}
+
+impl<'a> SampledArc<'a> {
+    /// Create an arc whose weights `sampler` draws with `rng`.
+    pub fn new(
+        place: &'a Place,
+        rng: impl RngCore + 'static,
+        sampler: impl Fn(&mut dyn RngCore) -> u32 + 'a,
+    ) -> Self {
+        Self {
+            place,
+            sampler: Box::new(sampler),
+            rng: RefCell::new(Box::new(rng)),
+            weight: Cell::new(None),
+        }
+    }
+
+    pub fn place(&self) -> &'a Place {
+        self.place
+    }
+
+    /// The weight of the next firing, which is drawn if it wasn't yet.
+    pub fn weight(&self) -> u32 {
+        match self.weight.get() {
+            Some(weight) => weight,
+            None => {
+                let mut rng = self.rng.borrow_mut();
+                let weight = (self.sampler)(&mut **rng);
+                self.weight.set(Some(weight));
+                weight
+            }
+        }
+    }
+
+    /// Forget the drawn weight, so the next firing draws a new one.
+    fn redraw(&self) {
+        self.weight.set(None);
+    }
+}
+
+/// Error returned when the token count of a place cannot be changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// The place holds fewer tokens than requested.
+    Underflow,
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Underflow => write!(f, "place holds too few tokens"),
+        }
+    }
+}
+
+impl error::Error for TokenError {}
+
+/// Error returned when a marking cannot be applied to a net.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkingError {
+    /// The marking doesn't hold a token count for every place of the net.
+    LengthMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for MarkingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch { expected, actual } => write!(
+                f,
+                "marking holds {} token counts, but the net has {} places",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl error::Error for MarkingError {}
+
+/// Error returned by `Petrinet::replay` when a transition of the sequence
+/// can't fire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayError {
+    step: usize,
+    transition: usize,
+    error: FireError,
+}
+
+impl ReplayError {
+    /// The position of the transition in the sequence, starting at 0.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// The index of the transition which can't fire.
+    pub fn transition(&self) -> usize {
+        self.transition
+    }
+
+    /// Why the transition can't fire.
+    pub fn error(&self) -> FireError {
+        self.error
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "step {} of the replay failed to fire transition {}: {}",
+            self.step, self.transition, self.error
+        )
+    }
+}
+
+impl error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Place {
+    pub(crate) tokens: Cell<u32>,
+    name: String,
+    capacity: Option<u32>,
+}
+
+impl Place {
+    pub fn new(tokens: u32, name: &str) -> Self {
+        Self {
+            tokens: Cell::new(tokens),
+            name: name.to_string(),
+            capacity: None,
+        }
+    }
+
+    /// Create a place which never holds more than `capacity` tokens through
+    /// firing. Transitions which would exceed the capacity are not enabled.
+    pub fn with_capacity(tokens: u32, name: &str, capacity: u32) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new(tokens, name)
+        }
+    }
+
+    pub fn capacity(&self) -> Option<u32> {
+        self.capacity
+    }
+
+    pub fn tokens(&self) -> u32 {
+        self.tokens.get()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Set the token count of the place to `n`. The capacity of the place
+    /// only restricts firing, so it is not checked.
+    pub fn set_tokens(&self, n: u32) {
+        self.tokens.set(n);
+    }
+
+    /// Add `n` tokens to the place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token count would overflow.
+    pub fn add_tokens(&self, n: u32) {
+        let tokens = self
+            .tokens
+            .get()
+            .checked_add(n)
+            .expect("token count overflow");
+        self.tokens.set(tokens);
+    }
+
+    /// Remove `n` tokens from the place. Fails without changing the place if
+    /// it holds fewer than `n` tokens.
+    pub fn try_remove_tokens(&self, n: u32) -> result::Result<(), TokenError> {
+        let tokens = self
+            .tokens
+            .get()
+            .checked_sub(n)
+            .ok_or(TokenError::Underflow)?;
+        self.tokens.set(tokens);
+        Ok(())
+    }
+}
+
+impl fmt::Display for Place {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.name, self.tokens())
+    }
+}
+
+impl error::Error for FireError {}
+
+/// What happens if firing produces more tokens on a place than a `u32` can
+/// hold, see `Petrinet::set_token_policy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenPolicy {
+    /// The token count stays at `u32::MAX`.
+    Saturating,
+    /// Firing fails with `FireError::Overflow` without touching any place,
+    /// which surfaces modeling bugs like producers without consumers.
+    #[default]
+    Checked,
+    /// The token count wraps around to 0.
+    Wrapping,
+}
+
+/// Whether a transition is enabled, and if not, why. See
+/// `Transition::enablement_status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Enablement {
+    Enabled,
+    Disabled {
+        /// Places of input or read arcs with too few tokens, as triples of
+        /// place name, tokens held and tokens needed. The tokens needed by
+        /// several input arcs on the same place are summed up.
+        insufficient: Vec<(String, u32, u64)>,
+        /// Names of the places which block the transition through an
+        /// inhibitor arc, a zero-test arc or their capacity.
+        blocked: Vec<String>,
+        /// Whether the expression or the guard of the transition failed.
+        guard_failed: bool,
+    },
+}
+
+/// Whether a transition fires immediately or after a random delay, as in
+/// generalized stochastic petri nets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKind {
+    /// The transition fires without delay, before any timed transition.
+    /// Among the enabled immediate transitions, one is chosen with
+    /// probability proportional to its weight.
+    Immediate { weight: f64 },
+    /// The transition fires after an exponentially distributed delay with
+    /// the rate as parameter.
+    Timed { rate: f64 },
+}
+
+/// An output arc which puts its tokens on `primary` up to the capacity of the
+/// place, and the remaining ones on `overflow`.
+pub(crate) struct OverflowArc<'a> {
+    pub(crate) primary: &'a Place,
+    pub(crate) overflow: &'a Place,
+    pub(crate) weight: u32,
+}
+
+/// A guard deciding about enablement based on the simulation time and the
+/// marking.
+type Guard<'a> = Box<dyn Fn(f64, &MarkingView<'_>) -> bool + 'a>;
+
+/// A priority computed from the marking, see `Transition::set_dynamic_priority`.
+type PriorityFn<'a> = Box<dyn Fn(&MarkingView<'_>) -> i64 + 'a>;
+
+/// An action run after a transition fired.
+type Action<'a> = Box<dyn FnMut() + 'a>;
+
+pub struct Transition<'a> {
+    pub(crate) input_arcs: Vec<&'a Arc<'a>>,
+    pub(crate) output_arcs: Vec<&'a Arc<'a>>,
+    pub(crate) inhibitor_arcs: Vec<&'a Arc<'a>>,
+    pub(crate) read_arcs: Vec<&'a Arc<'a>>,
+    pub(crate) loop_arcs: Vec<(&'a Place, u32)>,
+    pub(crate) reset_places: Vec<&'a Place>,
+    pub(crate) zero_test_places: Vec<&'a Place>,
+    pub(crate) variable_input_arcs: Vec<&'a VariableArc<'a>>,
+    pub(crate) variable_output_arcs: Vec<&'a VariableArc<'a>>,
+    pub(crate) sampled_input_arcs: Vec<&'a SampledArc<'a>>,
+    pub(crate) sampled_output_arcs: Vec<&'a SampledArc<'a>>,
+    pub(crate) overflow_arcs: Vec<OverflowArc<'a>>,
+    pub(crate) expression: Option<Box<dyn Fn() -> bool + 'a>>,
+    pub(crate) guard: Option<Guard<'a>>,
+    pub(crate) on_fire: Option<Action<'a>>,
+    pub(crate) name: Option<String>,
+    pub(crate) priority: u32,
+    dynamic_priority: Option<PriorityFn<'a>>,
+    kind: TransitionKind,
+    pub(crate) delay: Option<u64>,
+    time: Cell<f64>,
+}
+
+impl Default for Transition<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Transition<'a> {
+    pub fn new() -> Self {
+        Self {
+            input_arcs: Vec::new(),
+            output_arcs: Vec::new(),
+            inhibitor_arcs: Vec::new(),
+            read_arcs: Vec::new(),
+            loop_arcs: Vec::new(),
+            reset_places: Vec::new(),
+            zero_test_places: Vec::new(),
+            variable_input_arcs: Vec::new(),
+            variable_output_arcs: Vec::new(),
+            sampled_input_arcs: Vec::new(),
+            sampled_output_arcs: Vec::new(),
+            overflow_arcs: Vec::new(),
+            expression: None,
+            guard: None,
+            on_fire: None,
+            name: None,
+            priority: 0,
+            dynamic_priority: None,
+            kind: TransitionKind::Timed { rate: 1.0 },
+            delay: None,
+            time: Cell::new(0.0),
+        }
+    }
+
+    pub fn new_with_expression(func: impl Fn() -> bool + 'a) -> Self {
+        Self {
+            expression: Some(Box::new(func)),
+            ..Self::new()
+        }
+    }
+
+    /// Create a transition with a guard, which is only enabled if the guard
+    /// returns true. The guard sees the token counts of all places connected
+    /// to the transition.
+    pub fn new_with_guard(guard: impl Fn(&MarkingView<'_>) -> bool + 'a) -> Self {
+        Self::new_with_timed_guard(move |_, marking| guard(marking))
+    }
+
+    /// Like `new_with_guard`, but the guard also sees the simulation time of
+    /// the net, see `Petrinet::set_time`, e.g. to enable the transition only
+    /// after a timeout.
+    ///
+    /// Only `simulate_ssa` and `TimedPetrinet` advance the clock. In untimed
+    /// stepping and in analyses, the guard sees the time last set, which is
+    /// 0 unless `Petrinet::set_time` was called.
+    pub fn new_with_timed_guard(guard: impl Fn(f64, &MarkingView<'_>) -> bool + 'a) -> Self {
+        Self {
+            guard: Some(Box::new(guard)),
+            ..Self::new()
+        }
+    }
+
+    /// Run `action` whenever the transition fired, e.g. to update state
+    /// outside of the net. The tokens are moved first, then the action runs.
+    /// A previously set action is replaced.
+    ///
+    /// Unlike the callback of `Petrinet::on_fire`, the action belongs to the
+    /// transition, so different transitions can act differently. Analyses
+    /// which fire transitions tentatively, like `reachability_graph`, don't
+    /// run it.
+    pub fn on_fire(&mut self, action: impl FnMut() + 'a) {
+        self.on_fire = Some(Box::new(action));
+    }
+
+    pub fn with_name(name: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            ..Self::new()
+        }
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Set the priority of the transition. Among simultaneously enabled
+    /// transitions, those with higher priority fire first. The default
+    /// priority is 0. A dynamic priority is replaced.
+    pub fn set_priority(&mut self, priority: u32) {
+        self.priority = priority;
+        self.dynamic_priority = None;
+    }
+
+    /// The constant priority, which is ignored while the transition has a
+    /// dynamic priority.
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    /// Let `f` compute the priority from the marking whenever a scheduler
+    /// compares priorities, e.g. to prefer draining the fuller of two
+    /// queues. Like a guard, `f` sees the token counts of all places
+    /// connected to the transition. Transitions with constant and dynamic
+    /// priorities can be mixed in a net.
+    ///
+    /// `f` may be called several times per step, so it must not have side
+    /// effects.
+    pub fn set_dynamic_priority(&mut self, f: impl Fn(&MarkingView<'_>) -> i64 + 'a) {
+        self.dynamic_priority = Some(Box::new(f));
+    }
+
+    /// The priority in the current marking, computed by the dynamic priority
+    /// if there is one.
+    pub fn current_priority(&self) -> i64 {
+        match self.dynamic_priority {
+            Some(ref f) => f(&MarkingView::new(self.places())),
+            None => i64::from(self.priority),
+        }
+    }
+
+    /// Set the firing rate of the transition, used by stochastic scheduling,
+    /// which makes it a timed transition. Transitions are timed with rate 1
+    /// by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is negative or not finite.
+    pub fn set_rate(&mut self, rate: f64) {
+        self.set_kind(TransitionKind::Timed { rate });
+    }
+
+    /// The rate of a timed transition, or the weight of an immediate one.
+    pub fn rate(&self) -> f64 {
+        match self.kind {
+            TransitionKind::Immediate { weight } => weight,
+            TransitionKind::Timed { rate } => rate,
+        }
+    }
+
+    /// Make the transition immediate or timed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the weight or rate is negative or not finite.
+    pub fn set_kind(&mut self, kind: TransitionKind) {
+        match kind {
+            TransitionKind::Immediate { weight } => assert!(
+                weight.is_finite() && weight >= 0.0,
+                "weight must be finite and non-negative"
+            ),
+            TransitionKind::Timed { rate } => assert!(
+                rate.is_finite() && rate >= 0.0,
+                "rate must be finite and non-negative"
+            ),
+        }
+        self.kind = kind;
+    }
+
+    pub fn kind(&self) -> TransitionKind {
+        self.kind
+    }
+
+    pub fn is_immediate(&self) -> bool {
+        matches!(self.kind, TransitionKind::Immediate { .. })
+    }
+
+    /// Set the delay, in abstract time units, after which the transition fires
+    /// once it got enabled. Used by `TimedPetrinet`, which treats transitions
+    /// without delay as immediate.
+    pub fn set_delay(&mut self, delay: u64) {
+        self.delay = Some(delay);
+    }
+
+    pub fn delay(&self) -> Option<u64> {
+        self.delay
+    }
+
+    pub fn add_input(&mut self, arc: &'a Arc<'a>) {
+        self.input_arcs.push(arc);
+    }
+
+    pub fn add_output(&mut self, arc: &'a Arc<'a>) {
+        self.output_arcs.push(arc);
+    }
+
+    /// Add a self-loop: `place` must hold `weight` tokens to enable the
+    /// transition, and firing takes and puts them back.
+    pub fn add_loop(&mut self, place: &'a Place, weight: u32) -> result::Result<(), ArcError> {
+        if weight < 1 {
+            return Err(ArcError::ZeroWeight);
+        }
+
+        self.loop_arcs.push((place, weight));
+        Ok(())
+    }
+
+    /// Add an inhibitor arc. The transition is only enabled while the place
+    /// of the arc holds fewer tokens than the arc weight, so the weight is a
+    /// threshold, e.g. the size of a buffer which must not be exceeded. An
+    /// arc of weight 1 inhibits while the place holds any token. Firing never
+    /// consumes or produces tokens through an inhibitor arc.
+    pub fn add_inhibitor(&mut self, arc: &'a Arc<'a>) {
+        self.inhibitor_arcs.push(arc);
+    }
+
+    /// Add a read arc, also known as test arc. The transition is only enabled
+    /// while the place of the arc holds at least as many tokens as the arc
+    /// weight, but firing doesn't consume them.
+    ///
+    /// A read arc and an input arc on the same place are checked
+    /// independently, so the place must hold at least the larger of both
+    /// weights, and firing consumes the weight of the input arc.
+    pub fn add_read(&mut self, arc: &'a Arc<'a>) {
+        self.read_arcs.push(arc);
+    }
+
+    /// Add a reset arc. Firing the transition empties the place, regardless
+    /// of how many tokens it holds. Reset arcs never block firing.
+    ///
+    /// Places are reset after the input tokens are consumed, but before the
+    /// output tokens are produced. Hence an output arc pointing to a reset
+    /// place leaves exactly its weight on the place.
+    pub fn add_reset(&mut self, place: &'a Place) {
+        self.reset_places.push(place);
+    }
+
+    /// Add a zero-test arc. The transition is only enabled while the place
+    /// is empty. Firing never consumes or produces tokens through a zero-test
+    /// arc.
+    pub fn add_zero_test(&mut self, place: &'a Place) {
+        self.zero_test_places.push(place);
+    }
+
+    /// Add an input arc whose weight is computed from the marking.
+    pub fn add_variable_input(&mut self, arc: &'a VariableArc<'a>) {
+        self.variable_input_arcs.push(arc);
+    }
+
+    /// Add an output arc whose weight is computed from the marking.
+    pub fn add_variable_output(&mut self, arc: &'a VariableArc<'a>) {
+        self.variable_output_arcs.push(arc);
+    }
+
+    /// Add an input arc whose weight is drawn at random, see `SampledArc`.
+    pub fn add_sampled_input(&mut self, arc: &'a SampledArc<'a>) {
+        self.sampled_input_arcs.push(arc);
+    }
+
+    /// Add an output arc whose weight is drawn at random, see `SampledArc`.
+    pub fn add_sampled_output(&mut self, arc: &'a SampledArc<'a>) {
+        self.sampled_output_arcs.push(arc);
+    }
+
+    /// Add an output arc of weight `weight` to `primary`, which routes the
+    /// tokens exceeding the capacity of `primary` to `overflow` instead of
+    /// blocking the transition. Firing fills `primary` up to its capacity,
+    /// taking the other arcs of the transition into account, and puts the
+    /// remaining tokens on `overflow`. If `primary` has no capacity, all
+    /// tokens go there.
+    ///
+    /// The transition is still blocked if the overflowing tokens exceed the
+    /// capacity of `overflow`.
+    pub fn add_output_with_overflow(
+        &mut self,
+        primary: &'a Place,
+        overflow: &'a Place,
+        weight: u32,
+    ) -> result::Result<(), ArcError> {
+        if weight < 1 {
+            return Err(ArcError::ZeroWeight);
+        }
+
+        self.overflow_arcs.push(OverflowArc {
+            primary,
+            overflow,
+            weight,
+        });
+        Ok(())
+    }
+
+    /// A source transition consumes no tokens, because it has no input
+    /// arcs, neither plain, variable nor sampled ones. Unless another arc,
+    /// the expression or the guard blocks it, a source is always enabled and
+    /// may produce tokens forever, see `Petrinet::set_source_limit`.
+    pub fn is_source(&self) -> bool {
+        self.input_arcs.is_empty()
+            && self.loop_arcs.is_empty()
+            && self.variable_input_arcs.is_empty()
+            && self.sampled_input_arcs.is_empty()
+    }
+
+    /// A sink transition produces no tokens, because it has no output arcs,
+    /// neither plain, variable, sampled nor overflow ones.
+    pub fn is_sink(&self) -> bool {
+        self.output_arcs.is_empty()
+            && self.loop_arcs.is_empty()
+            && self.variable_output_arcs.is_empty()
+            && self.sampled_output_arcs.is_empty()
+            && self.overflow_arcs.is_empty()
+    }
+
+    /// The input arcs, in the order they were added.
+    pub fn inputs(&self) -> &[&'a Arc<'a>] {
+        &self.input_arcs
+    }
+
+    /// The output arcs, in the order they were added.
+    pub fn outputs(&self) -> &[&'a Arc<'a>] {
+        &self.output_arcs
+    }
+
+    /// The places and weights of the self-loops, see `add_loop`.
+    pub fn loops(&self) -> &[(&'a Place, u32)] {
+        &self.loop_arcs
+    }
+
+    /// The places and weights of the input arcs, followed by the self-loops.
+    pub(crate) fn weighted_inputs(&self) -> impl Iterator<Item = (&'a Place, u32)> + '_ {
+        self.input_arcs
+            .iter()
+            .map(|arc| (arc.place, arc.weight))
+            .chain(self.loop_arcs.iter().copied())
+    }
+
+    /// The places and weights of the output arcs, followed by the
+    /// self-loops.
+    pub(crate) fn weighted_outputs(&self) -> impl Iterator<Item = (&'a Place, u32)> + '_ {
+        self.output_arcs
+            .iter()
+            .map(|arc| (arc.place, arc.weight))
+            .chain(self.loop_arcs.iter().copied())
+    }
+
+    /// All places connected to the transition, in the order their arcs were
+    /// added. A place connected by several arcs is returned several times.
+    pub(crate) fn places(&self) -> impl Iterator<Item = &'a Place> + '_ {
+        self.weighted_inputs()
+            .map(|(place, _)| place)
+            .chain(
+                self.output_arcs
+                    .iter()
+                    .chain(self.inhibitor_arcs.iter())
+                    .chain(self.read_arcs.iter())
+                    .map(|arc| arc.place),
+            )
+            .chain(self.reset_places.iter().copied())
+            .chain(self.zero_test_places.iter().copied())
+            .chain(self.dynamic_input_places())
+            .chain(self.dynamic_output_places())
+            .chain(
+                self.overflow_arcs
+                    .iter()
+                    .flat_map(|arc| vec![arc.primary, arc.overflow]),
+            )
+    }
+
+    /// The places of the variable and then the sampled input arcs, in the
+    /// order of `VariableWeights::inputs`.
+    fn dynamic_input_places(&self) -> impl Iterator<Item = &'a Place> + '_ {
+        self.variable_input_arcs
+            .iter()
+            .map(|arc| arc.place)
+            .chain(self.sampled_input_arcs.iter().map(|arc| arc.place))
+    }
+
+    /// Like `dynamic_input_places`, for the output arcs.
+    fn dynamic_output_places(&self) -> impl Iterator<Item = &'a Place> + '_ {
+        self.variable_output_arcs
+            .iter()
+            .map(|arc| arc.place)
+            .chain(self.sampled_output_arcs.iter().map(|arc| arc.place))
+    }
+
+    /// Whether the tokens the transition moves depend on more than the
+    /// weights of its input and output arcs.
+    pub(crate) fn has_dynamic_arcs(&self) -> bool {
+        !self.variable_input_arcs.is_empty()
+            || !self.variable_output_arcs.is_empty()
+            || !self.sampled_input_arcs.is_empty()
+            || !self.sampled_output_arcs.is_empty()
+            || !self.overflow_arcs.is_empty()
+    }
+
+    /// Evaluate the weights of the variable arcs, draw the weights of the
+    /// sampled arcs, and split the tokens of the overflow arcs, in the
+    /// current marking.
+    fn variable_weights(&self) -> VariableWeights {
+        if !self.has_dynamic_arcs() {
+            return VariableWeights::default();
+        }
+
+        let view = MarkingView::new(self.places());
+        let evaluate = |arcs: &[&VariableArc<'_>], sampled: &[&SampledArc<'_>]| {
+            arcs.iter()
+                .map(|arc| (arc.weight)(&view))
+                .chain(sampled.iter().map(|arc| arc.weight()))
+                .collect()
+        };
+        let mut weights = VariableWeights {
+            inputs: evaluate(&self.variable_input_arcs, &self.sampled_input_arcs),
+            outputs: evaluate(&self.variable_output_arcs, &self.sampled_output_arcs),
+            overflows: Vec::new(),
+        };
+
+        // fill each primary place up to its capacity, after the other arcs
+        // and the overflow arcs before took effect
+        let demand = self.input_demand(&weights);
+        let supply = self.output_supply(&weights);
+        for (i, arc) in self.overflow_arcs.iter().enumerate() {
+            let filled: u64 = self.overflow_arcs[..i]
+                .iter()
+                .zip(&weights.overflows)
+                .filter(|(other, _)| ptr::eq(other.primary, arc.primary))
+                .map(|(_, &(primary, _))| u64::from(primary))
+                .sum();
+            let supplied = supply
+                .iter()
+                .find(|(p, _)| ptr::eq(*p, arc.primary))
+                .map_or(0, |&(_, weight)| weight);
+            let tokens = self.remaining_tokens(arc.primary, &demand) + supplied + filled;
+            let room = arc.primary.capacity.map_or(u64::MAX, |capacity| {
+                u64::from(capacity).saturating_sub(tokens)
+            });
+
+            let primary = u64::from(arc.weight).min(room) as u32;
+            weights.overflows.push((primary, arc.weight - primary));
+        }
+
+        weights
+    }
+
+    /// The summed weight of the input arcs and self-loops of each input
+    /// place, in the order the places are first seen.
+    fn input_demand(&self, weights: &VariableWeights) -> Vec<(&'a Place, u64)> {
+        summed_weights(
+            self.weighted_inputs().chain(
+                self.dynamic_input_places()
+                    .zip(weights.inputs.iter().copied()),
+            ),
+        )
+    }
+
+    /// The summed weight of the output arcs of each output place, in the
+    /// order the places are first seen.
+    fn output_supply(&self, weights: &VariableWeights) -> Vec<(&'a Place, u64)> {
+        summed_weights(
+            self.weighted_outputs()
+                .chain(
+                    self.dynamic_output_places()
+                        .zip(weights.outputs.iter().copied()),
+                )
+                .chain(self.overflow_arcs.iter().zip(&weights.overflows).flat_map(
+                    |(arc, &(primary, overflow))| {
+                        vec![(arc.primary, primary), (arc.overflow, overflow)]
+                    },
+                )),
+        )
+    }
+
+    /// The tokens left on `place` after consuming `demand` and resetting.
+    fn remaining_tokens(&self, place: &Place, demand: &[(&'a Place, u64)]) -> u64 {
+        if self.reset_places.iter().any(|p| ptr::eq(*p, place)) {
+            0
+        } else {
+            let consumed = demand
+                .iter()
+                .find(|(p, _)| ptr::eq(*p, place))
+                .map_or(0, |&(_, weight)| weight);
+            u64::from(place.tokens()).saturating_sub(consumed)
+        }
+    }
+
+    /// The token count of each output place after firing, in the order the
+    /// places are first seen. Tokens consumed from or reset on a place make
+    /// room for the produced ones. The counts may exceed `u32::MAX`.
+    fn output_tokens_after_firing(&self, weights: &VariableWeights) -> Vec<(&'a Place, u64)> {
+        let demand = self.input_demand(weights);
+        self.output_supply(weights)
+            .into_iter()
+            .map(|(place, weight)| (place, self.remaining_tokens(place, &demand) + weight))
+            .collect()
+    }
+
+    /// The output places firing would leave with more tokens than their
+    /// capacity. The marking after firing is taken into account, so tokens
+    /// consumed from or reset on a place make room for the produced ones.
+    ///
+    /// Must only be called if the input arcs are satisfied.
+    fn exceeded_capacities(&self, weights: &VariableWeights) -> Vec<&'a Place> {
+        self.output_tokens_after_firing(weights)
+            .into_iter()
+            .filter(|&(place, tokens)| {
+                place
+                    .capacity
+                    .is_some_and(|capacity| tokens > u64::from(capacity))
+            })
+            .map(|(place, _)| place)
+            .collect()
+    }
+
+    /// A transition is enabled if every input place holds at least the summed
+    /// weight of its input arcs, all read arcs are satisfied, no capacity
+    /// would be exceeded, no inhibitor or zero-test arc blocks, and the
+    /// expression and guard hold.
+    ///
+    /// A place connected by an input and an output arc, a self-loop, needs
+    /// the tokens of the input arc to enable the transition. Capacities are
+    /// checked against the marking after firing, so a self-loop of equal
+    /// weights never exceeds the capacity of its place, even if the place is
+    /// full.
+    ///
+    /// A transition without input arcs, see `is_source`, needs no tokens,
+    /// so it is enabled unless another arc, the expression or the guard
+    /// blocks it.
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled_with(&self.variable_weights())
+    }
+
+    fn is_enabled_with(&self, weights: &VariableWeights) -> bool {
+        let all_arcs_enabled = self
+            .input_demand(weights)
+            .iter()
+            .all(|&(place, weight)| covers(place.tokens(), weight))
+            && self
+                .read_arcs
+                .iter()
+                .all(|arc| arc.can_provide_required_tokens())
+            && self.exceeded_capacities(weights).is_empty()
+            && !self.inhibitor_arcs.iter().any(|arc| arc.inhibits())
+            && self
+                .zero_test_places
+                .iter()
+                .all(|place| place.tokens() == 0);
+
+        let expression_holds = match self.expression {
+            Some(ref f) => f(),
+            None => true,
+        };
+
+        all_arcs_enabled
+            && expression_holds
+            && match self.guard {
+                Some(ref guard) => guard(self.time.get(), &MarkingView::new(self.places())),
+                None => true,
+            }
+    }
+
+    /// Like `is_enabled`, but a disabled transition reports every reason it
+    /// is disabled for.
+    pub fn enablement_status(&self) -> Enablement {
+        let weights = self.variable_weights();
+        let mut insufficient = Vec::new();
+        for (place, weight) in self.input_demand(&weights) {
+            if !covers(place.tokens(), weight) {
+                insufficient.push((place.name.clone(), place.tokens(), weight));
+            }
+        }
+        for arc in &self.read_arcs {
+            if !arc.can_provide_required_tokens() {
+                insufficient.push((
+                    arc.place.name.clone(),
+                    arc.place.tokens(),
+                    u64::from(arc.weight),
+                ));
+            }
+        }
+
+        let blocked: Vec<String> = self
+            .inhibitor_arcs
+            .iter()
+            .filter(|arc| arc.inhibits())
+            .map(|arc| arc.place)
+            .chain(
+                self.zero_test_places
+                    .iter()
+                    .copied()
+                    .filter(|place| place.tokens() > 0),
+            )
+            .chain(self.exceeded_capacities(&weights))
+            .map(|place| place.name.clone())
+            .collect();
+
+        let guard_failed = !self.expression.as_ref().is_none_or(|f| f())
+            || !self
+                .guard
+                .as_ref()
+                .is_none_or(|guard| guard(self.time.get(), &MarkingView::new(self.places())));
+
+        if insufficient.is_empty() && blocked.is_empty() && !guard_failed {
+            Enablement::Enabled
+        } else {
+            Enablement::Disabled {
+                insufficient,
+                blocked,
+                guard_failed,
+            }
+        }
+    }
+
+    /// Fire the transition, consuming tokens from the input places and
+    /// producing tokens on the output places.
+    ///
+    /// Fails with `FireError::NotEnabled` if the transition is not enabled,
+    /// in which case no place is touched. Firing is atomic: the enablement
+    /// check covers all input arcs together, so tokens are only consumed
+    /// once every input arc is known to be satisfiable. The action set with
+    /// `on_fire` runs after the tokens moved.
+    ///
+    /// Fails with `FireError::Overflow` if a token count would overflow, see
+    /// `TokenPolicy::Checked`. Nets apply their own `TokenPolicy` instead.
+    pub fn fire(&mut self) -> result::Result<(), FireError> {
+        self.fire_with(TokenPolicy::Checked)
+    }
+
+    fn fire_with(&mut self, policy: TokenPolicy) -> result::Result<(), FireError> {
+        self.fire_tokens(policy)?;
+        self.redraw_samples();
+        self.run_action();
+        Ok(())
+    }
+
+    /// Forget the drawn weights of the sampled arcs after firing.
+    fn redraw_samples(&self) {
+        for arc in self
+            .sampled_input_arcs
+            .iter()
+            .chain(&self.sampled_output_arcs)
+        {
+            arc.redraw();
+        }
+    }
+
+    /// The tokens firing in the current marking would take from and put on
+    /// each place, as consumed and produced amounts. Tokens removed by reset
+    /// arcs count as consumed.
+    pub(crate) fn token_flow(&self) -> (TokenFlow<'a>, TokenFlow<'a>) {
+        let weights = self.variable_weights();
+        let demand = self.input_demand(&weights);
+        let reset = self.reset_places.iter().map(|&place| {
+            let consumed = demand
+                .iter()
+                .find(|(p, _)| ptr::eq(*p, place))
+                .map_or(0, |&(_, weight)| weight);
+            (place, u64::from(place.tokens()).saturating_sub(consumed))
+        });
+        let consumed = summed_weights(demand.iter().copied().chain(reset));
+        (consumed, self.output_supply(&weights))
+    }
+
+    /// Like `fire`, but without running the action.
+    pub(crate) fn fire_tokens(&self, policy: TokenPolicy) -> result::Result<(), FireError> {
+        let weights = self.variable_weights();
+        if !self.is_enabled_with(&weights) {
+            return Err(FireError::NotEnabled);
+        }
+        if policy == TokenPolicy::Checked && self.would_overflow(&weights) {
+            return Err(FireError::Overflow);
+        }
+
+        self.consume_tokens(&weights);
+        self.reset_places();
+        self.produce_tokens(&weights, policy);
+        Ok(())
+    }
+
+    /// Whether firing would leave more tokens on a place than a `u32` holds.
+    fn would_overflow(&self, weights: &VariableWeights) -> bool {
+        self.output_tokens_after_firing(weights)
+            .iter()
+            .any(|&(_, tokens)| tokens > u64::from(u32::MAX))
+    }
+
+    fn run_action(&mut self) {
+        if let Some(action) = self.on_fire.as_mut() {
+            action();
+        }
+    }
+
+    fn consume_tokens(&self, weights: &VariableWeights) {
+        for v in self.input_arcs.iter() {
+            v.consume_tokens();
+        }
+
+        for &(place, weight) in &self.loop_arcs {
+            place.tokens.set(place.tokens() - weight);
+        }
+
+        for (place, &weight) in self.dynamic_input_places().zip(&weights.inputs) {
+            place.tokens.set(place.tokens() - weight);
+        }
+    }
+
+    fn reset_places(&self) {
+        for place in &self.reset_places {
+            place.tokens.set(0);
+        }
+    }
+
+    /// Produce the output tokens. With `TokenPolicy::Checked`, the caller
+    /// must make sure no token count overflows.
+    fn produce_tokens(&self, weights: &VariableWeights, policy: TokenPolicy) {
+        let produce = |place: &Place, weight: u32| {
+            let tokens = match policy {
+                TokenPolicy::Saturating => place.tokens().saturating_add(weight),
+                TokenPolicy::Checked | TokenPolicy::Wrapping => place.tokens().wrapping_add(weight),
+            };
+            place.tokens.set(tokens);
+        };
+
+        for (place, weight) in self.weighted_outputs() {
+            produce(place, weight);
+        }
+
+        for (place, &weight) in self.dynamic_output_places().zip(&weights.outputs) {
+            produce(place, weight);
+        }
+
+        for (arc, &(primary, overflow)) in self.overflow_arcs.iter().zip(&weights.overflows) {
+            produce(arc.primary, primary);
+            produce(arc.overflow, overflow);
+        }
+    }
+}
+
+/// The weights of the variable input and output arcs of a transition, and
+/// the tokens each overflow arc puts on its primary and overflow place, in
+/// the order the arcs were added.
+#[derive(Default)]
+struct VariableWeights {
+    inputs: Vec<u32>,
+    outputs: Vec<u32>,
+    overflows: Vec<(u32, u32)>,
+}
+
+/// Token amounts per place, moved by firing a transition.
+type TokenFlow<'a> = Vec<(&'a Place, u64)>;
+
+/// The summed weight per place of `arcs`, in the order the places are first
+/// seen.
+fn summed_weights<'a, W: Into<u64>>(
+    arcs: impl Iterator<Item = (&'a Place, W)>,
+) -> Vec<(&'a Place, u64)> {
+    let mut summed: Vec<(&'a Place, u64)> = Vec::new();
+    for (place, weight) in arcs {
+        let weight = weight.into();
+        match summed.iter_mut().find(|(p, _)| ptr::eq(*p, place)) {
+            Some((_, total)) => *total += weight,
+            None => summed.push((place, weight)),
+        }
+    }
+    summed
+}
+
+/// A single transition firing recorded by the firing trace of a net.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    transition: usize,
+    name: Option<String>,
+    marking: Marking,
+}
+
+impl TraceEntry {
+    /// Index of the fired transition.
+    pub fn transition(&self) -> usize {
+        self.transition
+    }
+
+    /// Name of the fired transition, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The marking of the net right after the firing.
+    pub fn marking(&self) -> &[u32] {
+        &self.marking
+    }
+}
+
+/// The state before a firing, see `Petrinet::undo`.
+struct UndoEntry {
+    /// The token counts of the places the firing touched, as pairs of place
+    /// index and token count.
+    tokens: Vec<(usize, u32)>,
+    source_firings: u64,
+}
+
+/// A predicate on the marking, see `Petrinet::add_invariant`.
+type Monitor<'a> = Box<dyn Fn(&[u32]) -> bool + 'a>;
+
+/// A predicate on a transition index and the marking, see
+/// `Petrinet::set_firing_constraint`.
+type FiringConstraint<'a> = Box<dyn Fn(usize, &[u32]) -> bool + 'a>;
+
+/// A callback invoked after a transition fired.
+type FireCallback<'a> = Box<dyn FnMut(usize, &[u32]) + 'a>;
+
+#[derive(Default)]
+pub struct Petrinet<'a> {
+    pub(crate) transitions: Vec<Transition<'a>>,
+    pub(crate) places: Vec<&'a Place>,
+    pub(crate) initial_marking: Vec<u32>,
+    trace: Option<Vec<TraceEntry>>,
+    on_fire: Option<FireCallback<'a>>,
+    pub(crate) token_policy: TokenPolicy,
+    undo_depth: usize,
+    history: VecDeque<UndoEntry>,
+    pub(crate) monitors: Vec<Monitor<'a>>,
+    source_limit: Option<u64>,
+    source_firings: u64,
+    firing_constraint: Option<FiringConstraint<'a>>,
+    time: f64,
+    pub(crate) substitutions: Vec<SubstitutionTransition<'a>>,
+}
+
+impl<'a> Petrinet<'a> {
+    pub fn new() -> Self {
+        Self {
+            transitions: Vec::new(),
+            places: Vec::new(),
+            initial_marking: Vec::new(),
+            trace: None,
+            on_fire: None,
+            token_policy: TokenPolicy::Checked,
+            undo_depth: 0,
+            history: VecDeque::new(),
+            monitors: Vec::new(),
+            source_limit: None,
+            source_firings: 0,
+            firing_constraint: None,
+            time: 0.0,
+            substitutions: Vec::new(),
+        }
+    }
+
+    /// Add a transition to the net. The places connected to the transition
+    /// are registered with the net in the order they are first seen.
+    pub fn add_transition(&mut self, transition: Transition<'a>) {
+        transition.time.set(self.time);
+        for place in transition.places() {
+            self.register_place(place);
+        }
+
+        self.transitions.push(transition)
+    }
+
+    /// Remove the transition at `index` and return it, or `None` if there is
+    /// no such transition.
+    ///
+    /// The transitions after `index` move down by one, so their indices
+    /// change. Places stay registered with the net and keep their indices,
+    /// even if no remaining transition is connected to them. Trace entries
+    /// recorded before the removal keep the old indices.
+    pub fn remove_transition(&mut self, index: usize) -> Option<Transition<'a>> {
+        if index < self.transitions.len() {
+            Some(self.transitions.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Remove the first transition named `name` and return it, like
+    /// `remove_transition`.
+    pub fn remove_transition_by_name(&mut self, name: &str) -> Option<Transition<'a>> {
+        let index = self
+            .transitions
+            .iter()
+            .position(|transition| transition.name() == Some(name))?;
+        self.remove_transition(index)
+    }
+
+    pub(crate) fn register_place(&mut self, place: &'a Place) {
+        if self.place_index(place).is_none() {
+            self.places.push(place);
+            self.initial_marking.push(place.tokens());
+        }
+    }
+
+    /// Index of a registered place in the canonical place order.
+    pub(crate) fn place_index(&self, place: &Place) -> Option<usize> {
+        self.places.iter().position(|p| ptr::eq(*p, place))
+    }
+
+    /// The token counts of all places of the net, in registration order.
+    pub fn marking(&self) -> Marking {
+        self.places.iter().map(|place| place.tokens()).collect()
+    }
+
+    /// Like `marking`, but each token count is paired with its place name.
+    pub fn named_marking(&self) -> Vec<(&str, u32)> {
+        self.places
+            .iter()
+            .map(|place| (place.name(), place.tokens()))
+            .collect()
+    }
+
+    /// The transitions of the net, in the order they were added.
+    pub fn transitions(&self) -> &[Transition<'a>] {
+        &self.transitions
+    }
+
+    /// The transition at index `index`, if there is one.
+    pub fn transition(&self, index: usize) -> Option<&Transition<'a>> {
+        self.transitions.get(index)
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Number of places registered by the transitions of the net.
+    pub fn place_count(&self) -> usize {
+        self.places.len()
+    }
+
+    /// Indices of all transitions which are enabled in the current marking.
+    /// Source transitions are not enabled once the limit set with
+    /// `set_source_limit` is reached, and no transition is enabled which the
+    /// constraint set with `set_firing_constraint` blocks.
+    pub fn enabled_transitions(&self) -> Vec<usize> {
+        self.transitions
+            .iter()
+            .enumerate()
+            .filter(|&(i, transition)| transition.is_enabled() && self.may_fire(i, 0))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// A net is deadlocked if none of its transitions and none of its
+    /// substitution transitions is enabled.
+    pub fn is_deadlocked(&self) -> bool {
+        self.enabled_transitions().is_empty() && self.enabled_substitutions().is_empty()
+    }
+
+    /// Let source transitions, see `Transition::is_source`, fire at most
+    /// `limit` times in total until the next `reset`, to avoid runaway token
+    /// growth. Stepping and `fire_transition` treat sources as disabled once
+    /// the limit is reached. Analyses of the state space ignore the limit.
+    pub fn set_source_limit(&mut self, limit: u64) {
+        self.source_limit = Some(limit);
+    }
+
+    /// The number of firings of source transitions since the last `reset`.
+    pub fn source_firings(&self) -> u64 {
+        self.source_firings
+    }
+
+    /// Block firings of structurally enabled transitions with a rule across
+    /// the net: the transition at index `t` may only fire in marking `m` if
+    /// `constraint(t, m)` holds. Like the source limit, the constraint
+    /// applies to `enabled_transitions`, `fire_transition` and all stepping,
+    /// but analyses of the state space ignore it. `step_maximal` evaluates it
+    /// against the marking before the step.
+    ///
+    /// Unlike a guard, the constraint belongs to the net and sees every
+    /// transition, so a single rule can cover many of them.
+    pub fn set_firing_constraint(&mut self, constraint: impl Fn(usize, &[u32]) -> bool + 'a) {
+        self.firing_constraint = Some(Box::new(constraint));
+    }
+
+    /// Whether the transition at `index` may fire after `pending` more
+    /// firings of source transitions, and the firing constraint allows it.
+    fn may_fire(&self, index: usize, pending: u64) -> bool {
+        let within_source_limit = !self.transitions[index].is_source()
+            || self
+                .source_limit
+                .is_none_or(|limit| self.source_firings + pending < limit);
+        within_source_limit
+            && self
+                .firing_constraint
+                .as_ref()
+                .is_none_or(|allows| allows(index, &self.marking()))
+    }
+
+    /// Set the simulation clock, which timed guards see, see
+    /// `Transition::new_with_timed_guard`.
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+        for transition in &self.transitions {
+            transition.time.set(time);
+        }
+    }
+
+    /// The simulation clock, 0 unless set with `set_time` or advanced by a
+    /// simulation.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Choose what happens when firing produces more tokens on a place than
+    /// a `u32` can hold. The default is `TokenPolicy::Checked`.
+    pub fn set_token_policy(&mut self, policy: TokenPolicy) {
+        self.token_policy = policy;
+    }
+
+    pub fn token_policy(&self) -> TokenPolicy {
+        self.token_policy
+    }
+
+    /// Start recording every transition firing. Recording is off by
+    /// default.
+    pub fn enable_trace(&mut self) {
+        if self.trace.is_none() {
+            self.trace = Some(Vec::new());
+        }
+    }
+
+    /// Call `f` with the index of the fired transition and the marking after
+    /// firing whenever a transition of the net fires, e.g. during `step` or
+    /// `run_until_deadlock`. A previously registered callback is replaced.
+    ///
+    /// Like the trace, the callback sees the marking after the whole step in
+    /// `step_maximal`. Analyses which fire transitions tentatively, like
+    /// `reachability_graph`, don't invoke it.
+    pub fn on_fire(&mut self, f: impl FnMut(usize, &[u32]) + 'a) {
+        self.on_fire = Some(Box::new(f));
+    }
+
+    /// Remember the token changes of the last `depth` firings, so `undo` can
+    /// revert them. Each firing only records the places of its transition.
+    /// The depth is 0 by default, which records nothing. Lowering the depth
+    /// forgets the oldest firings.
+    pub fn set_undo_depth(&mut self, depth: usize) {
+        self.undo_depth = depth;
+        while self.history.len() > depth {
+            self.history.pop_front();
+        }
+    }
+
+    /// Revert the last recorded firing, or the last step of `step_maximal`,
+    /// by setting the places it touched back to their token counts before.
+    /// Firings of source transitions it reverts no longer count towards the
+    /// source limit. Returns `false` if there is nothing to undo.
+    ///
+    /// Changing the marking by other means, like `restore`, is not recorded,
+    /// and transition actions are not reverted.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop_back() {
+            Some(entry) => {
+                for (p, tokens) in entry.tokens {
+                    self.places[p].tokens.set(tokens);
+                }
+                self.source_firings = entry.source_firings;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The token counts of the places of `transitions`, if undo is enabled.
+    fn undo_entry(&self, transitions: &[usize]) -> Option<UndoEntry> {
+        if self.undo_depth == 0 {
+            return None;
+        }
+
+        let mut tokens = Vec::new();
+        for &t in transitions {
+            for place in self.transitions[t].places() {
+                let p = self.place_index(place).expect("registered place");
+                if !tokens.iter().any(|&(q, _)| q == p) {
+                    tokens.push((p, place.tokens()));
+                }
+            }
+        }
+        Some(UndoEntry {
+            tokens,
+            source_firings: self.source_firings,
+        })
+    }
+
+    fn record_undo(&mut self, entry: Option<UndoEntry>) {
+        if let Some(entry) = entry {
+            if self.history.len() == self.undo_depth {
+                self.history.pop_front();
+            }
+            self.history.push_back(entry);
+        }
+    }
+
+    /// The firings recorded since `enable_trace` was called, in firing
+    /// order.
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or(&[])
+    }
+
+    /// Fire the transition at `index`, unlike `step` which picks the
+    /// transition itself. The firing is recorded in the trace.
+    ///
+    /// Fails with `FireError::NotEnabled` if the transition is not enabled,
+    /// and with `FireError::NoSuchTransition` if there is no transition at
+    /// `index`. In both cases the marking is left unchanged.
+    pub fn fire_transition(&mut self, index: usize) -> result::Result<(), FireError> {
+        if index >= self.transitions.len() {
+            return Err(FireError::NoSuchTransition(index));
+        }
+
+        self.fire_at(index)
+    }
+
+    /// Fire the transitions at the indices of `sequence` in order, e.g. as
+    /// recorded in the trace, to reproduce a run. Call `reset` before to
+    /// replay a run from the initial marking.
+    ///
+    /// Fails as soon as a transition can't fire, see `fire_transition`. The
+    /// transitions before it stay fired.
+    pub fn replay(&mut self, sequence: &[usize]) -> result::Result<(), ReplayError> {
+        for (step, &transition) in sequence.iter().enumerate() {
+            self.fire_transition(transition)
+                .map_err(|error| ReplayError {
+                    step,
+                    transition,
+                    error,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn fire_at(&mut self, index: usize) -> result::Result<(), FireError> {
+        if !self.may_fire(index, 0) {
+            return Err(FireError::NotEnabled);
+        }
+
+        let policy = self.token_policy;
+        let undo = self.undo_entry(&[index]);
+        self.transitions[index].fire_with(policy)?;
+        self.record_undo(undo);
+        self.fired(index);
+        Ok(())
+    }
+
+    /// Bookkeeping after the transition at `index` fired.
+    fn fired(&mut self, index: usize) {
+        if self.transitions[index].is_source() {
+            self.source_firings += 1;
+        }
+
+        if self.trace.is_some() {
+            let entry = TraceEntry {
+                transition: index,
+                name: self.transitions[index].name.clone(),
+                marking: self.marking(),
+            };
+            if let Some(trace) = self.trace.as_mut() {
+                trace.push(entry);
+            }
+        }
+
+        if self.on_fire.is_some() {
+            let marking = self.marking();
+            if let Some(on_fire) = self.on_fire.as_mut() {
+                on_fire(index, &marking);
+            }
+        }
+    }
+
+    /// An iterator which fires the first enabled transition on every call to
+    /// `next`, like `step`, and yields the index of the fired transition and
+    /// the marking after firing. It ends when the net is deadlocked, so it
+    /// may be endless.
+    pub fn run_iter(&mut self) -> RunIter<'_, 'a> {
+        RunIter { net: self }
+    }
+
+    /// Fire the first enabled transition. Returns whether a transition
+    /// fired.
+    pub fn step(&mut self) -> bool {
+        self.step_with(&mut FirstEnabledScheduler)
+    }
+
+    /// Fire a maximal set of concurrently enabled transitions in one step and
+    /// return how many fired.
+    ///
+    /// Transitions are considered in the order they were added. A transition
+    /// joins the step if it is enabled and the tokens not reserved by the
+    /// transitions already in the step suffice for its input arcs. Capacities
+    /// must hold for the marking after the whole step. Guards, inhibitor and
+    /// zero-test arcs, and the weights of variable arcs, are evaluated
+    /// against the marking before the step. Hence the result is
+    /// deterministic, and no further transition could fire concurrently with
+    /// the chosen ones, but the step doesn't necessarily fire the largest
+    /// possible number of transitions.
+    ///
+    /// All tokens are consumed before any reset arc or output arc takes
+    /// effect, so tokens produced in a step can only be used in the next
+    /// step.
+    pub fn step_maximal(&mut self) -> usize {
+        let mut available = self.marking().into_vec();
+        let mut produced = vec![0u64; self.places.len()];
+        let mut chosen = Vec::new();
+        let mut sources = 0;
+
+        for (i, transition) in self.transitions.iter().enumerate() {
+            let weights = transition.variable_weights();
+            if !transition.is_enabled_with(&weights) || !self.may_fire(i, sources) {
+                continue;
+            }
+
+            let mut demand = available.clone();
+            let sufficient = transition
+                .input_demand(&weights)
+                .iter()
+                .all(|&(place, weight)| {
+                    let p = self.place_index(place).expect("registered place");
+                    match u64::from(demand[p]).checked_sub(weight) {
+                        Some(tokens) => {
+                            demand[p] = tokens as u32;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+            if !sufficient {
+                continue;
+            }
+
+            let mut supply = produced.clone();
+            for (place, weight) in transition.output_supply(&weights) {
+                let p = self.place_index(place).expect("registered place");
+                supply[p] += weight;
+            }
+            let checked = self.token_policy == TokenPolicy::Checked;
+            let within_capacity = self.places.iter().enumerate().all(|(p, place)| {
+                let tokens = u64::from(demand[p]) + supply[p];
+                place
+                    .capacity
+                    .is_none_or(|capacity| tokens <= u64::from(capacity))
+                    && (!checked || tokens <= u64::from(u32::MAX))
+            });
+            if !within_capacity {
+                continue;
+            }
+
+            available = demand;
+            produced = supply;
+            if transition.is_source() {
+                sources += 1;
+            }
+            chosen.push((i, weights));
+        }
+
+        if chosen.is_empty() {
+            return 0;
+        }
+
+        let fired: Vec<usize> = chosen.iter().map(|&(i, _)| i).collect();
+        let undo = self.undo_entry(&fired);
+        for (i, weights) in &chosen {
+            self.transitions[*i].consume_tokens(weights);
+        }
+        for (i, _) in &chosen {
+            self.transitions[*i].reset_places();
+        }
+        for (i, weights) in &chosen {
+            self.transitions[*i].produce_tokens(weights, self.token_policy);
+        }
+        self.record_undo(undo);
+        for (i, _) in &chosen {
+            self.transitions[*i].redraw_samples();
+            self.transitions[*i].run_action();
+            self.fired(*i);
+        }
+
+        chosen.len()
+    }
+
+    /// Step the net until it is deadlocked, but at most `max_steps` times.
+    /// Returns the number of steps taken.
+    ///
+    /// The cap is needed because many nets never deadlock.
+    pub fn run_until_deadlock(&mut self, max_steps: usize) -> usize {
+        let mut steps = 0;
+        while steps < max_steps && self.step() {
+            steps += 1;
+        }
+
+        steps
+    }
+
+    /// Step the net up to `max_steps` times and return, per place, the
+    /// maximum number of tokens observed. This approximates whether the net is
+    /// bounded without exploring its whole state space.
+    ///
+    /// The marking of the net is restored afterwards.
+    pub fn max_marking(&mut self, max_steps: usize) -> Vec<u32> {
+        let initial = self.snapshot();
+        let mut max = initial.to_vec();
+
+        for _ in 0..max_steps {
+            if !self.step() {
+                break;
+            }
+
+            for (max, tokens) in max.iter_mut().zip(self.marking()) {
+                *max = (*max).max(tokens);
+            }
+        }
+
+        self.restore(&initial);
+        max
+    }
+
+    /// Save the current marking, so it can be restored later on.
+    pub fn snapshot(&self) -> Marking {
+        self.marking()
+    }
+
+    /// The token counts the places held when they were registered with the
+    /// net, in registration order.
+    pub fn initial_marking(&self) -> &[u32] {
+        &self.initial_marking
+    }
+
+    /// Restore the initial marking of the net, and start counting the
+    /// firings of source transitions from 0 again.
+    pub fn reset(&mut self) {
+        self.source_firings = 0;
+        for (place, &tokens) in self.places.iter().zip(&self.initial_marking) {
+            place.tokens.set(tokens);
+        }
+    }
+
+    /// Set the token count of every place to the one saved in `marking`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `marking` doesn't hold a token count for every place.
+    pub fn restore(&mut self, marking: &[u32]) {
+        assert_eq!(
+            self.places.len(),
+            marking.len(),
+            "marking must hold a token count for every place"
+        );
+
+        for (place, &tokens) in self.places.iter().zip(marking) {
+            place.tokens.set(tokens);
+        }
+    }
+
+    /// Set the token count of every place from `marking`, in the canonical
+    /// place order, e.g. as returned by `marking`. Unlike `restore`, fails
+    /// without touching any place if `marking` doesn't hold a token count
+    /// for every place.
+    pub fn set_marking(&mut self, marking: &[u32]) -> result::Result<(), MarkingError> {
+        if marking.len() != self.places.len() {
+            return Err(MarkingError::LengthMismatch {
+                expected: self.places.len(),
+                actual: marking.len(),
+            });
+        }
+
+        for (place, &tokens) in self.places.iter().zip(marking) {
+            place.set_tokens(tokens);
+        }
+        Ok(())
+    }
+
+    /// Fire the enabled transition with the highest priority. Ties are broken
+    /// in favor of the transition added first. Returns whether a transition
+    /// fired.
+    pub fn step_priority(&mut self) -> bool {
+        self.step_with(&mut PriorityScheduler)
+    }
+
+    /// Fire one enabled transition chosen uniformly at random among those
+    /// with the highest priority.
+    ///
+    /// Returns `false` without touching any place if no transition is
+    /// enabled.
+    pub fn step_random(&mut self, rng: &mut impl Rng) -> bool {
+        self.step_with(&mut RandomTransitionScheduler::new(rng))
+    }
+
+    /// Fire one enabled transition, chosen with probability proportional to
+    /// its rate, see `WeightedTransitionScheduler`. Returns `false` without
+    /// touching any place if no transition with a positive rate is enabled.
+    pub fn step_weighted(&mut self, rng: &mut impl Rng) -> bool {
+        self.step_with(&mut WeightedTransitionScheduler::new(rng))
+    }
+
+    /// Fire the transition chosen by `scheduler` among the enabled
+    /// transitions. Returns `false` without touching any place if the
+    /// scheduler chooses no transition, or one which is not enabled.
+    ///
+    /// If no transition is enabled, the first enabled substitution
+    /// transition fires instead, see `add_substitution`.
+    pub fn step_with(&mut self, scheduler: &mut impl Scheduler) -> bool {
+        let enabled = self.enabled_transitions();
+        if enabled.is_empty() {
+            return match self.enabled_substitutions().first() {
+                Some(&i) => self.fire_substitution(i).is_ok(),
+                None => false,
+            };
+        }
+
+        match scheduler.choose(&enabled, self) {
+            Some(i) => self.fire_transition(i).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Iterator over the firings of a net, see `Petrinet::run_iter`.
+pub struct RunIter<'n, 'a> {
+    net: &'n mut Petrinet<'a>,
+}
+
+impl Iterator for RunIter<'_, '_> {
+    type Item = (usize, Marking);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let enabled = self.net.enabled_transitions();
+        let i = FirstEnabledScheduler.choose(&enabled, self.net)?;
+        self.net.fire_at(i).ok()?;
+        Some((i, self.net.marking()))
+    }
+}
+
+/// The marking of the net on one line, like `[P1:2 P2:0]`, in the
+/// registration order of the places.
+impl fmt::Display for Petrinet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, place) in self.places.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}:{}", place.name(), place.tokens())?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// A policy choosing the transition to fire next, see
+/// `Petrinet::step_with`.
+pub trait Scheduler {
+    /// Return the index of the transition to fire next, or `None` if no
+    /// transition should fire. `enabled` holds the indices of the enabled
+    /// transitions of `net` in ascending order. The transition itself is not
+    /// fired.
+    fn choose(&mut self, enabled: &[usize], net: &Petrinet<'_>) -> Option<usize>;
+}
+
+/// Choose the enabled transition added first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstEnabledScheduler;
+
+impl Scheduler for FirstEnabledScheduler {
+    fn choose(&mut self, enabled: &[usize], _net: &Petrinet<'_>) -> Option<usize> {
+        enabled.first().copied()
+    }
+}
+
+/// Choose the enabled transition with the highest priority. Ties are broken
+/// in favor of the transition added first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PriorityScheduler;
+
+impl Scheduler for PriorityScheduler {
+    fn choose(&mut self, enabled: &[usize], net: &Petrinet<'_>) -> Option<usize> {
+        enabled
+            .iter()
+            .map(|&i| (net.transitions[i].current_priority(), i))
+            .max_by(|&(a_priority, a), &(b_priority, b)| {
+                a_priority.cmp(&b_priority).then(b.cmp(&a))
+            })
+            .map(|(_, i)| i)
+    }
+}
+
+/// Choose a random transition firing order.
+///
+/// Among all enabled transitions one is picked uniformly at random. Priority
+/// dominates randomness: only the enabled transitions with the highest
+/// priority are considered. The random source is injectable, so tests can pass
+/// a seeded RNG.
+pub struct RandomTransitionScheduler<R: Rng> {
+    rng: R,
+}
+
+impl<R: Rng> RandomTransitionScheduler<R> {
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+impl RandomTransitionScheduler<StdRng> {
+    /// A scheduler drawing from a `StdRng` seeded with `seed`. Schedulers
+    /// with the same seed make the same choices on every machine, as long
+    /// as the version of `rand` stays the same.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::new(StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Draws from the thread-local RNG, so runs are not reproducible. See
+/// `with_seed` for reproducible runs.
+impl Default for RandomTransitionScheduler<rand::rngs::ThreadRng> {
+    fn default() -> Self {
+        Self::new(rand::thread_rng())
+    }
+}
+
+impl<R: Rng> Scheduler for RandomTransitionScheduler<R> {
+    fn choose(&mut self, enabled: &[usize], net: &Petrinet<'_>) -> Option<usize> {
+        let priorities: Vec<i64> = enabled
+            .iter()
+            .map(|&i| net.transitions[i].current_priority())
+            .collect();
+        let priority = priorities.iter().max();
+        let enabled: Vec<usize> = enabled
+            .iter()
+            .zip(&priorities)
+            .filter(|&(_, p)| Some(p) == priority)
+            .map(|(&i, _)| i)
+            .collect();
+
+        enabled.choose(&mut self.rng).copied()
+    }
+}
+
+/// Choose among the enabled transitions with probability proportional to
+/// their rates.
+///
+/// Immediate transitions take precedence: while any of them is enabled, only
+/// immediate transitions are considered, chosen by their weights. If the
+/// rates or weights of all considered transitions sum up to 0, no transition
+/// is chosen.
+pub struct WeightedTransitionScheduler<R: Rng> {
+    rng: R,
+}
+
+impl<R: Rng> WeightedTransitionScheduler<R> {
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+impl WeightedTransitionScheduler<StdRng> {
+    /// A scheduler drawing from a `StdRng` seeded with `seed`, see
+    /// `RandomTransitionScheduler::with_seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::new(StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Draws from the thread-local RNG, so runs are not reproducible. See
+/// `with_seed` for reproducible runs.
+impl Default for WeightedTransitionScheduler<rand::rngs::ThreadRng> {
+    fn default() -> Self {
+        Self::new(rand::thread_rng())
+    }
+}
+
+impl<R: Rng> Scheduler for WeightedTransitionScheduler<R> {
+    fn choose(&mut self, enabled: &[usize], net: &Petrinet<'_>) -> Option<usize> {
+        let enabled = net.gspn_candidates(enabled);
+        let index = WeightedIndex::new(enabled.iter().map(|&i| net.transitions[i].rate())).ok()?;
+        Some(enabled[index.sample(&mut self.rng)])
+    }
+}
+
+/// Choose the enabled transition which waited the longest since it was last
+/// chosen, so no enabled transition is starved. Ties go to the transition
+/// with the lowest index. Priorities are ignored.
+///
+/// The scheduler counts the steps since each transition was chosen, so the
+/// same scheduler should be used for all steps of a net.
+#[derive(Debug, Clone, Default)]
+pub struct FairScheduler {
+    waiting: Vec<u64>,
+}
+
+impl FairScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Scheduler for FairScheduler {
+    fn choose(&mut self, enabled: &[usize], net: &Petrinet<'_>) -> Option<usize> {
+        self.waiting.resize(net.transitions.len(), 0);
+
+        let mut chosen: Option<usize> = None;
+        for &i in enabled {
+            if chosen.is_none_or(|c| self.waiting[i] > self.waiting[c]) {
+                chosen = Some(i);
+            }
+        }
+
+        let chosen = chosen?;
+        for waiting in &mut self.waiting {
+            *waiting = waiting.saturating_add(1);
+        }
+        self.waiting[chosen] = 0;
+        Some(chosen)
+    }
+}
+
+/// Fire transitions of parallel branches in round-robin fashion: choose the
+/// first enabled transition after the one chosen last, in index order,
+/// wrapping around at the end.
+///
+/// Unlike `FairScheduler`, the order is fixed, so a transition which is only
+/// enabled rarely may still be skipped every time it is enabled.
+#[derive(Debug, Clone, Default)]
+pub struct FairBranchScheduler {
+    last: Option<usize>,
+}
+
+impl FairBranchScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Scheduler for FairBranchScheduler {
+    fn choose(&mut self, enabled: &[usize], _net: &Petrinet<'_>) -> Option<usize> {
+        let chosen = match self.last {
+            Some(last) => enabled
+                .iter()
+                .copied()
+                .filter(|&i| i > last)
+                .min()
+                .or_else(|| enabled.iter().copied().min()),
+            None => enabled.iter().copied().min(),
+        }?;
+
+        self.last = Some(chosen);
+        Some(chosen)
+    }
+}
+
+/// Prefer one branch over the others when firing transitions: keep
+/// following the branch of the transition chosen last, by choosing an
+/// enabled transition with an input place the last one has an output arc
+/// to. If there is none, the branch has ended, and the enabled transition
+/// with the lowest index starts the next one.
+#[derive(Debug, Clone, Default)]
+pub struct SequentialBranchScheduler {
+    last: Option<usize>,
+}
+
+impl SequentialBranchScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Scheduler for SequentialBranchScheduler {
+    fn choose(&mut self, enabled: &[usize], net: &Petrinet<'_>) -> Option<usize> {
+        let follows = |last: usize, i: usize| {
+            net.transitions[last].output_arcs.iter().any(|output| {
+                net.transitions[i]
+                    .input_arcs
+                    .iter()
+                    .any(|input| ptr::eq(input.place, output.place))
+            })
+        };
+
+        let chosen = self
+            .last
+            .and_then(|last| enabled.iter().copied().filter(|&i| follows(last, i)).min())
+            .or_else(|| enabled.iter().copied().min())?;
+
+        self.last = Some(chosen);
+        Some(chosen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::mock::StepRng;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn it_works() {
+        let place = super::Place::new(1, "a");
+        let arc = super::Arc::new(&place, 1).expect("weight greater than zero");
+
+        let mut transition = super::Transition::new();
+        transition.add_input(&arc);
+
+        assert_eq!(Ok(()), transition.fire());
+        assert_eq!(0, place.tokens());
+    }
+
+    #[test]
+    fn test_fire_disabled_transition() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(3, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_input(&a2);
+        t.add_output(&a3);
+
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+        assert_eq!(1, p1.tokens());
+        assert_eq!(3, p2.tokens());
+        assert_eq!(0, p3.tokens());
+    }
+
+    #[test]
+    fn test_fire_is_atomic() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(2, "P2");
+        let p3 = Place::new(0, "P3");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 3).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // the second input arc can't be satisfied
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_input(&a2);
+        t1.add_output(&a3);
+        assert_eq!(Err(FireError::NotEnabled), t1.fire());
+        assert_eq!((1, 2, 0), (p1.tokens(), p2.tokens(), p3.tokens()));
+
+        // each arc alone could be satisfied, but not both together
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+        assert!(!t2.is_enabled());
+        assert_eq!(Err(FireError::NotEnabled), t2.fire());
+        assert_eq!((1, 2, 0), (p1.tokens(), p2.tokens(), p3.tokens()));
+
+        p1.add_tokens(1);
+        assert_eq!(Ok(()), t2.fire());
+        assert_eq!((0, 2, 1), (p1.tokens(), p2.tokens(), p3.tokens()));
+    }
+
+    #[test]
+    fn test_self_loop() {
+        let p = Place::with_capacity(1, "P", 1);
+        let q = Place::new(0, "Q");
+        let r = Place::new(0, "R");
+        let a1 = Arc::new(&q, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&r, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_loop(&p, 1).expect("weight greater than zero");
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        assert!(!t.is_enabled(), "Q holds no token");
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+        assert_eq!((1, 0, 0), (p.tokens(), q.tokens(), r.tokens()));
+
+        q.add_tokens(1);
+        assert!(
+            t.is_enabled(),
+            "the full place P doesn't block the self-loop"
+        );
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!((1, 0, 1), (p.tokens(), q.tokens(), r.tokens()));
+
+        p.tokens.set(0);
+        q.add_tokens(1);
+        assert!(!t.is_enabled(), "the self-loop needs a token on P");
+    }
+
+    #[test]
+    fn test_add_loop() {
+        let p = Place::new(2, "P");
+
+        let mut t = Transition::new();
+        assert_eq!(Err(ArcError::ZeroWeight), t.add_loop(&p, 0));
+        assert_eq!(Ok(()), t.add_loop(&p, 2));
+        assert!(t.inputs().is_empty() && t.outputs().is_empty());
+        assert_eq!(1, t.loops().len());
+        assert!(!t.is_source() && !t.is_sink());
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!(2, p.tokens());
+
+        p.tokens.set(1);
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+        assert_eq!(1, p.tokens());
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        assert_eq!(vec![vec![0]], net.incidence_matrix());
+        assert_eq!(vec![0], net.transition_preset(0));
+        assert_eq!(vec![0], net.transition_postset(0));
+    }
+
+    #[test]
+    fn test_transition_action() {
+        let p = Place::new(2, "P");
+        let a = Arc::new(&p, 1).expect("weight greater than zero");
+        let fired = Cell::new(0);
+        let seen = Cell::new(None);
+
+        let mut t = Transition::new();
+        t.add_input(&a);
+        t.on_fire(|| {
+            fired.set(fired.get() + 1);
+            seen.set(Some(p.tokens()));
+        });
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        assert_eq!(3, net.reachability_graph(10).state_count());
+        assert_eq!(0, fired.get(), "analyses don't run actions");
+
+        assert!(net.step());
+        assert_eq!(Some(1), seen.get(), "tokens move first");
+        assert_eq!(1, net.step_maximal());
+        assert!(!net.step());
+        assert_eq!(2, fired.get());
+    }
+
+    #[test]
+    fn test_sampled_arcs() {
+        let p1 = Place::new(100, "p1");
+        let p2 = Place::new(0, "p2");
+        let sampled = SampledArc::new(&p1, StdRng::seed_from_u64(3), |rng| rng.gen_range(1..=3));
+        let doubled = SampledArc::new(&p2, StdRng::seed_from_u64(3), |rng| {
+            2 * rng.gen_range(1..=3)
+        });
+
+        let mut t = Transition::new();
+        t.add_sampled_input(&sampled);
+        t.add_sampled_output(&doubled);
+
+        let mut drawn = Vec::new();
+        for _ in 0..20 {
+            let (weight, tokens) = (sampled.weight(), p1.tokens());
+            assert_eq!(weight, sampled.weight(), "drawn weights are kept");
+            assert_eq!(Ok(()), t.fire());
+            assert_eq!(tokens - weight, p1.tokens());
+            drawn.push(weight);
+        }
+        assert!(drawn.iter().all(|weight| (1..=3).contains(weight)));
+        assert!(drawn.iter().any(|&weight| weight != drawn[0]));
+        assert_eq!(2 * (100 - p1.tokens()), p2.tokens(), "same seed");
+
+        // the transition waits for enough tokens for the drawn weight
+        p1.set_tokens(sampled.weight() - 1);
+        assert!(!t.is_enabled());
+        assert!(!t.is_enabled());
+        p1.set_tokens(3);
+        assert_eq!(Ok(()), t.fire());
+    }
+
+    #[test]
+    fn test_variable_arcs() {
+        let p = Place::new(3, "P");
+        let q = Place::with_capacity(1, "Q", 5);
+        let all_of_p = |view: &MarkingView<'_>| view.tokens("P").expect("place P");
+        let a1 = VariableArc::new(&p, all_of_p);
+        let a2 = VariableArc::new(&q, all_of_p);
+
+        // move all tokens from P to Q, both weights see P before firing
+        let mut t = Transition::new();
+        t.add_variable_input(&a1);
+        t.add_variable_output(&a2);
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!((0, 4), (p.tokens(), q.tokens()));
+
+        // a weight of 0 moves nothing
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!((0, 4), (p.tokens(), q.tokens()));
+
+        // the computed weight counts against the capacity
+        p.tokens.set(2);
+        assert!(!t.is_enabled());
+        assert_eq!(
+            Enablement::Disabled {
+                insufficient: Vec::new(),
+                blocked: vec!["Q".to_string()],
+                guard_failed: false,
+            },
+            t.enablement_status()
+        );
+
+        let mut net = Petrinet::new();
+        p.tokens.set(1);
+        net.add_transition(t);
+        assert_eq!(vec![1, 4], net.marking());
+        assert_eq!(1, net.step_maximal());
+        assert_eq!(vec![0, 5], net.marking());
+    }
+
+    #[test]
+    fn test_enablement_status() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(1, "P2");
+        let p3 = Place::with_capacity(1, "P3", 1);
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 2).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new_with_expression(|| false);
+        t.add_input(&a1);
+        t.add_input(&a1);
+        t.add_read(&a2);
+        t.add_output(&a3);
+        t.add_zero_test(&p1);
+        assert_eq!(
+            Enablement::Disabled {
+                insufficient: vec![("P1".to_string(), 1, 2), ("P2".to_string(), 1, 2)],
+                blocked: vec!["P1".to_string(), "P3".to_string()],
+                guard_failed: true,
+            },
+            t.enablement_status()
+        );
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        assert_eq!(Enablement::Enabled, t.enablement_status());
+        assert!(t.is_enabled());
+    }
+
+    #[test]
+    fn test_capacity_of_several_output_arcs() {
+        let p = Place::with_capacity(0, "P", 1);
+        let a = Arc::new(&p, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_output(&a);
+        t.add_output(&a);
+        assert!(!t.is_enabled());
+    }
+
+    #[test]
+    fn test_transition_without_expression() {
+        let p1 = super::Place::new(2, "p1");
+        let p2 = super::Place::new(2, "p2");
+        let p3 = super::Place::new(0, "p3");
+
+        let a1 = super::Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = super::Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = super::Arc::new(&p3, 2).expect("weight greater than zero");
+
+        let mut t = super::Transition::new();
+        t.add_input(&a1);
+        t.add_input(&a2);
+
+        t.add_output(&a3);
+
+        assert!(t.is_enabled());
+
+        t.fire().expect("transition is enabled");
+
+        assert_eq!(0, p1.tokens());
+        assert_eq!(1, p2.tokens());
+        assert_eq!(2, p3.tokens(), "number of tokens on place p3");
+
+        assert!(
+            !t.is_enabled(),
+            "must not fire because input tokens are not sufficient anymore"
+        );
+
+        assert_eq!(0, p1.tokens());
+        assert_eq!(1, p2.tokens());
+        assert_eq!(2, p3.tokens(), "number of tokens on place p3");
+    }
+
+    #[test]
+    fn test_transition_with_expression() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(2, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 2).expect("weight greater than zero");
+
+        // with expression which evaluates to true
+        let mut t = Transition::new_with_expression(|| true);
+        t.add_input(&a1);
+        t.add_input(&a2);
+
+        t.add_output(&a3);
+
+        assert!(
+            t.is_enabled(),
+            "must be enabled since transition expression is true"
+        );
+
+        t.fire().expect("transition is enabled");
+
+        assert_eq!(2, p3.tokens(), "number of tokens on place p3");
+
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(2, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 2).expect("weight greater than zero");
+
+        // with expression which evaluates to false
+        let mut t = Transition::new_with_expression(|| false);
+        t.add_input(&a1);
+        t.add_input(&a2);
+
+        t.add_output(&a3);
+
+        assert!(
+            !t.is_enabled(),
+            "must not be enabled since transition expression is false"
+        );
+    }
+
+    #[test]
+    fn test_transition_with_guard() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::new(1, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // move tokens from p1 to p2 while p1 has more tokens than p2
+        let mut t = Transition::new_with_guard(|marking| {
+            marking.tokens("p1") > marking.tokens("p2") && marking.tokens("p3").is_none()
+        });
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        assert!(t.is_enabled());
+        t.fire().expect("transition is enabled");
+        assert_eq!((2, 2), (p1.tokens(), p2.tokens()));
+        assert!(!t.is_enabled(), "guard is false");
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+
+        // connecting p3 makes the place visible to the guard
+        p1.tokens.set(5);
+        t.add_output(&a3);
+        assert!(!t.is_enabled());
+    }
+
+    #[test]
+    fn test_transition_name() {
+        assert_eq!(None, Transition::new().name());
+        assert_eq!(None, Transition::default().name());
+        assert_eq!(Some("t1"), Transition::with_name("t1").name());
+
+        let mut t = Transition::new_with_expression(|| true);
+        t.set_name("guarded");
+        assert_eq!(Some("guarded"), t.name());
+    }
+
+    #[test]
+    fn test_inhibitor_arc() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_inhibitor(&a2);
+        t.add_output(&a3);
+
+        p2.tokens.set(1);
+        assert!(!t.is_enabled(), "p2 holds a token, so t is inhibited");
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+
+        p2.tokens.set(0);
+        assert!(t.is_enabled(), "p2 is empty, so t is not inhibited");
+        t.fire().expect("transition is enabled");
+
+        assert_eq!(0, p1.tokens());
+        assert_eq!(0, p2.tokens(), "inhibitor arcs never move tokens");
+        assert_eq!(1, p3.tokens());
+    }
+
+    #[test]
+    fn test_weighted_inhibitor_arc() {
+        let buffer = Place::new(2, "buffer");
+        let full = Arc::new(&buffer, 2).expect("weight greater than zero");
+        let larger = Arc::new(&buffer, 3).expect("weight greater than zero");
+        let output = Arc::new(&buffer, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_inhibitor(&full);
+        t.add_output(&output);
+        assert!(!t.is_enabled(), "the buffer is full");
+
+        buffer.tokens.set(1);
+        assert!(t.is_enabled(), "below the threshold");
+        assert_eq!(Ok(()), t.fire());
+        assert!(!t.is_enabled());
+
+        // raising the threshold enables the transition again
+        let mut t = Transition::new();
+        t.add_inhibitor(&larger);
+        t.add_output(&output);
+        assert!(t.is_enabled());
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!(3, buffer.tokens());
+        assert!(!t.is_enabled());
+    }
+
+    #[test]
+    fn test_read_arc() {
+        let resource = Place::new(1, "resource");
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&resource, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_read(&a1);
+        t.add_input(&a2);
+        t.add_output(&a3);
+
+        t.fire().expect("transition is enabled");
+        t.fire().expect("transition is enabled");
+        assert_eq!(1, resource.tokens(), "read arcs don't consume tokens");
+        assert_eq!(0, p1.tokens());
+        assert_eq!(2, p2.tokens());
+
+        resource.tokens.set(0);
+        p1.tokens.set(1);
+        assert!(!t.is_enabled(), "resource is missing");
+    }
+
+    #[test]
+    fn test_read_arc_with_input_arc_on_same_place() {
+        let p = Place::new(2, "p");
+
+        let read = Arc::new(&p, 2).expect("weight greater than zero");
+        let input = Arc::new(&p, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_read(&read);
+        t.add_input(&input);
+
+        t.fire().expect("transition is enabled");
+        assert_eq!(1, p.tokens(), "only the input arc consumes");
+        assert!(!t.is_enabled(), "the read arc needs two tokens");
+    }
+
+    #[test]
+    fn test_reset_arc() {
+        let p1 = Place::new(1, "p1");
+        let buffer = Place::new(0, "buffer");
+        let p3 = Place::new(7, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_reset(&buffer);
+        t.add_reset(&p3);
+        t.add_output(&a2);
+
+        assert!(t.is_enabled(), "reset arcs never block firing");
+        t.fire().expect("transition is enabled");
+
+        assert_eq!(0, p1.tokens());
+        assert_eq!(0, buffer.tokens());
+        assert_eq!(1, p3.tokens(), "outputs are produced after the reset");
+    }
+
+    #[test]
+    fn test_zero_test_arc() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        t.add_zero_test(&p2);
+
+        assert!(t.is_enabled());
+        assert_eq!(Ok(()), t.fire());
+        assert_eq!((0, 1), (p1.tokens(), p2.tokens()));
+
+        p1.add_tokens(1);
+        assert!(!t.is_enabled(), "p2 is not empty");
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+        assert_eq!((1, 1), (p1.tokens(), p2.tokens()));
+    }
+
+    #[test]
+    fn test_place_add_and_remove_tokens() {
+        let place = Place::new(1, "p");
+
+        place.add_tokens(2);
+        assert_eq!(3, place.tokens());
+
+        assert_eq!(Ok(()), place.try_remove_tokens(3));
+        assert_eq!(0, place.tokens());
+
+        assert_eq!(Err(TokenError::Underflow), place.try_remove_tokens(1));
+        assert_eq!(0, place.tokens());
+
+        place.set_tokens(7);
+        assert_eq!(7, place.tokens());
+    }
+
+    #[test]
+    #[should_panic(expected = "token count overflow")]
+    fn test_place_add_tokens_overflow() {
+        let place = Place::new(u32::MAX, "p");
+        place.add_tokens(1);
+    }
+
+    #[test]
+    fn test_place_capacity() {
+        let p1 = Place::new(5, "p1");
+        let buffer = Place::with_capacity(0, "buffer", 3);
+        assert_eq!(Some(3), buffer.capacity());
+        assert_eq!(None, p1.capacity());
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&buffer, 2).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        assert!(!a2.would_exceed_capacity());
+        t.fire().expect("transition is enabled");
+        assert_eq!(2, buffer.tokens());
+
+        assert!(a2.would_exceed_capacity());
+        assert!(!t.is_enabled(), "buffer would overflow");
+        assert_eq!(Err(FireError::NotEnabled), t.fire());
+        assert_eq!(4, p1.tokens());
+        assert_eq!(2, buffer.tokens());
+    }
+
+    #[test]
+    fn test_arc() {
+        let place = super::Place::new(2, "p");
+        let arc = super::Arc::new(&place, 2).expect("weight greater than zero");
+        assert!(arc.can_provide_required_tokens());
+
+        let place = super::Place::new(1, "p");
+        let arc = super::Arc::new(&place, 2).expect("weight greater than zero");
+        assert!(!arc.can_provide_required_tokens());
+
+        let place = super::Place::new(3, "p");
+        let arc = super::Arc::new(&place, 2).expect("weight greater than zero");
+        assert!(arc.can_provide_required_tokens());
+
+        let place = super::Place::new(2, "");
+        assert!(matches!(
+            super::Arc::new(&place, 0),
+            Err(ArcError::ZeroWeight)
+        ));
+        assert_eq!("weight must be greater 0", ArcError::ZeroWeight.to_string());
+    }
+
+    #[test]
+    fn test_petrinet() {
+        let p1 = super::Place::new(2, "P1");
+        let p2 = super::Place::new(2, "P2");
+
+        let p3 = super::Place::new(0, "P3");
+
+        let a1 = super::Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = super::Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let a3 = super::Arc::new(&p3, 2).expect("weight greater than zero");
+
+        let mut t = super::Transition::new();
+        t.add_input(&a1);
+        t.add_input(&a2);
+
+        t.add_output(&a3);
+
+        let mut net = super::Petrinet::new();
+
+        net.add_transition(t);
+
+        net.step();
+
+        net.step();
+    }
+
+    #[test]
+    fn test_remove_transition() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::with_name("t1");
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::with_name("t2");
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(Transition::with_name("t3"));
+
+        assert!(net.remove_transition(3).is_none());
+        let removed = net.remove_transition(0).expect("transition exists");
+        assert_eq!(Some("t1"), removed.name());
+        assert_eq!(Some("t2"), net.transitions[0].name());
+        assert_eq!(vec![1, 0], net.marking(), "places are kept");
+
+        assert!(net.remove_transition_by_name("t1").is_none());
+        let removed = net
+            .remove_transition_by_name("t3")
+            .expect("transition exists");
+        assert_eq!(Some("t3"), removed.name());
+        assert_eq!(1, net.transitions.len());
+        assert!(net.is_deadlocked());
+    }
+
+    #[test]
+    fn test_transition_arcs() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        t.add_output(&a1);
+
+        assert_eq!(1, t.inputs().len());
+        assert_eq!(2, t.inputs()[0].weight());
+        assert_eq!("P1", t.inputs()[0].place().name());
+        let outputs: Vec<_> = t.outputs().iter().map(|arc| arc.place().name()).collect();
+        assert_eq!(vec!["P2", "P1"], outputs);
+    }
+
+    #[test]
+    fn test_fire_transition() {
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.enable_trace();
+
+        assert_eq!(Err(FireError::NotEnabled), net.fire_transition(1));
+        assert_eq!(Err(FireError::NoSuchTransition(2)), net.fire_transition(2));
+        assert_eq!(vec![1, 0], net.marking());
+
+        assert_eq!(Ok(()), net.fire_transition(0));
+        assert_eq!(vec![0, 1], net.marking());
+        assert_eq!(vec![1], net.enabled_transitions());
+        assert_eq!(1, net.trace().len());
+    }
+
+    #[test]
+    fn test_display() {
+        let p1 = Place::new(2, "P1");
+        let p2 = Place::new(0, "P2");
+        assert_eq!("P1(2)", p1.to_string());
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        let mut net = Petrinet::new();
+        assert_eq!("[]", net.to_string());
+        net.add_transition(t);
+        assert_eq!("[P1:2 P2:0]", net.to_string());
+        net.step();
+        assert_eq!("[P1:1 P2:1]", net.to_string());
+    }
+
+    // https://en.wikipedia.org/wiki/Petri_net#/media/File:Detailed_petri_net.png
+    #[test]
+    fn test_more_complex_petrinet() {
+        let p1 = super::Place::new(1, "P1");
+        let p2 = super::Place::new(0, "P2");
+        let p3 = super::Place::new(2, "P3");
+        let p4 = super::Place::new(1, "P4");
+
+        let a1 = super::Arc::new(&p1, 1).expect("weight greater than zero");
+
+        let a2 = super::Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = super::Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let a4 = super::Arc::new(&p2, 1).expect("weight greater than zero");
+        let a5 = super::Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let a6 = super::Arc::new(&p4, 1).expect("weight greater than zero");
+
+        let a7 = super::Arc::new(&p1, 1).expect("weight greater than zero");
+
+        let mut t1 = super::Transition::new();
+        let mut t2 = super::Transition::new();
+
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        t1.add_output(&a3);
+
+        t2.add_input(&a4);
+        t2.add_input(&a5);
+        t2.add_output(&a6);
+        t2.add_output(&a7);
+
+        let mut petri = super::Petrinet::new();
+        petri.add_transition(t1);
+        petri.add_transition(t2);
+
+        assert_eq!(vec![1, 0, 2, 1], petri.marking());
+
+        petri.step();
+
+        assert_eq!(0, p1.tokens());
+        assert_eq!(1, p2.tokens());
+        assert_eq!(3, p3.tokens());
+
+        petri.step();
+
+        assert_eq!(1, p1.tokens());
+        assert_eq!(0, p2.tokens());
+        assert_eq!(2, p3.tokens());
+        assert_eq!(2, p4.tokens());
+
+        petri.step();
+        petri.step();
+
+        assert_eq!(3, p4.tokens());
+        assert_eq!(
+            vec![("P1", 1), ("P2", 0), ("P3", 2), ("P4", 3)],
+            petri.named_marking()
+        );
+
+        assert_eq!(100, petri.run_until_deadlock(100), "net never deadlocks");
+    }
+
+    #[test]
+    fn test_petrinet_step_random() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // two transitions in conflict for the token on p1
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        assert!(net.step_random(&mut rng));
+        assert_eq!(0, p1.tokens());
+        assert_eq!(1, p2.tokens() + p3.tokens());
+
+        assert!(
+            !net.step_random(&mut rng),
+            "no transition is enabled anymore"
+        );
+        assert_eq!(0, p1.tokens());
+        assert_eq!(1, p2.tokens() + p3.tokens());
+    }
+
+    #[test]
+    fn test_deadlock() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        assert_eq!(vec![0], net.enabled_transitions());
+        assert!(!net.is_deadlocked());
+
+        net.step();
+        assert_eq!(vec![1], net.enabled_transitions());
+        assert!(!net.is_deadlocked());
+
+        net.step();
+        assert!(net.enabled_transitions().is_empty());
+        assert!(net.is_deadlocked());
+        assert!(!net.step());
+    }
+
+    #[test]
+    fn test_run_until_deadlock() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        assert_eq!(2, net.run_until_deadlock(2), "capped at two steps");
+        assert_eq!(1, net.run_until_deadlock(10));
+        assert_eq!(0, net.run_until_deadlock(10));
+        assert_eq!(vec![0, 3], net.marking());
+    }
+
+    #[test]
+    fn test_trace() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::with_name("t1");
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        net.step();
+        assert!(net.trace().is_empty(), "trace is disabled by default");
+
+        net.enable_trace();
+        net.step();
+        net.step();
+
+        let trace = net.trace();
+        assert_eq!(2, trace.len());
+        assert_eq!(1, trace[0].transition());
+        assert_eq!(None, trace[0].name());
+        assert_eq!(&[1, 0], trace[0].marking());
+        assert_eq!(0, trace[1].transition());
+        assert_eq!(Some("t1"), trace[1].name());
+        assert_eq!(&[0, 1], trace[1].marking());
+    }
+
+    #[test]
+    fn test_on_fire() {
+        let p1 = Place::new(2, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        let fired = std::cell::RefCell::new(Vec::new());
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        net.on_fire(|i, marking: &[u32]| fired.borrow_mut().push((i, marking.to_vec())));
+
+        net.reachability_graph(10);
+        assert!(
+            fired.borrow().is_empty(),
+            "analyses don't invoke the callback"
+        );
+
+        assert_eq!(2, net.run_until_deadlock(10));
+        drop(net);
+        assert_eq!(vec![(0, vec![1, 1]), (0, vec![0, 2])], fired.into_inner());
+    }
+
+    #[test]
+    fn test_run_iter() {
+        let p1 = Place::new(2, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let run: Vec<_> = net.run_iter().map(|(i, m)| (i, m.into_vec())).collect();
+        assert_eq!(
+            vec![
+                (0, vec![1, 1]),
+                (0, vec![0, 2]),
+                (1, vec![0, 1]),
+                (1, vec![0, 0])
+            ],
+            run
+        );
+        assert!(net.is_deadlocked());
+
+        net.reset();
+        let markings: Vec<Marking> = net.run_iter().take(2).map(|(_, m)| m).collect();
+        assert_eq!(vec![vec![1, 1], vec![0, 2]], markings);
+    }
+
+    #[test]
+    fn test_max_marking() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // p1 and p2 pass a single token back and forth, while every round
+        // trip adds a token to p3
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+        t2.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        assert_eq!(vec![1, 1, 3], net.max_marking(6));
+        assert_eq!(vec![1, 0, 0], net.marking(), "marking is restored");
+        assert_eq!(vec![1, 0, 0], net.max_marking(0));
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        let snapshot = net.snapshot();
+        assert_eq!(vec![2, 0], snapshot);
+
+        net.run_until_deadlock(10);
+        assert_eq!(vec![0, 2], net.marking());
+
+        net.restore(&snapshot);
+        assert_eq!(2, p1.tokens());
+        assert_eq!(0, p2.tokens());
+    }
+
+    #[test]
+    fn test_set_marking() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        let marking: Vec<u32> = (3..5).collect();
+        assert_eq!(Ok(()), net.set_marking(&marking));
+        assert_eq!(vec![3, 4], net.marking());
+
+        let error = net.set_marking(&[1]).unwrap_err();
+        assert_eq!(
+            MarkingError::LengthMismatch {
+                expected: 2,
+                actual: 1
+            },
+            error
+        );
+        assert_eq!(
+            "marking holds 1 token counts, but the net has 2 places",
+            error.to_string()
+        );
+        assert_eq!(vec![3, 4], net.marking(), "marking is untouched");
+    }
+
+    #[test]
+    fn test_counts() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut net = Petrinet::new();
+        assert_eq!((0, 0), (net.transition_count(), net.place_count()));
+
+        let mut t1 = Transition::new();
+        t1.set_name("t1");
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        assert_eq!((2, 2), (net.transition_count(), net.place_count()));
+        assert_eq!(Some("t1"), net.transition(0).and_then(Transition::name));
+        assert!(net.transition(2).is_none());
+    }
+
+    #[test]
+    fn test_replay() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut forth = Transition::new();
+        forth.add_input(&a1);
+        forth.add_output(&a2);
+        let mut back = Transition::new();
+        back.add_input(&a2);
+        back.add_output(&a1);
+        let mut net = Petrinet::new();
+        net.add_transition(forth);
+        net.add_transition(back);
+
+        net.enable_trace();
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..10 {
+            net.step_random(&mut rng);
+        }
+        let sequence: Vec<usize> = net.trace().iter().map(TraceEntry::transition).collect();
+        let marking = net.marking();
+
+        net.reset();
+        assert_eq!(Ok(()), net.replay(&sequence));
+        assert_eq!(marking, net.marking());
+        assert_eq!(
+            2 * sequence.len(),
+            net.trace().len(),
+            "the replay is traced"
+        );
+
+        net.reset();
+        let error = net.replay(&[0, 1, 1]).unwrap_err();
+        assert_eq!(
+            (2, 1, FireError::NotEnabled),
+            (error.step(), error.transition(), error.error())
+        );
+        assert_eq!(
+            "step 2 of the replay failed to fire transition 1: transition is not enabled",
+            error.to_string()
+        );
+        assert_eq!(vec![2, 0], net.marking(), "the first two steps fired");
+        assert_eq!(
+            Err(FireError::NoSuchTransition(2)),
+            net.replay(&[2]).map_err(|error| error.error())
+        );
+    }
+
+    #[test]
+    fn test_source_and_sink_transitions() {
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // the source produces on p1, t moves the tokens to p2 and the sink
+        // consumes them
+        let mut source = Transition::new();
+        source.add_output(&a1);
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        let mut sink = Transition::new();
+        sink.add_input(&a2);
+        assert!(source.is_source() && !source.is_sink());
+        assert!(!t.is_source() && !t.is_sink());
+        assert!(!sink.is_source() && sink.is_sink());
+        assert!(source.is_enabled(), "a source needs no tokens");
+
+        let mut net = Petrinet::new();
+        net.add_transition(source);
+        net.add_transition(t);
+        net.add_transition(sink);
+        assert_eq!(10, net.run_until_deadlock(10), "the source never stops");
+        assert_eq!(10, net.source_firings());
+        assert_eq!(vec![10, 0], net.marking());
+
+        net.reset();
+        net.set_source_limit(2);
+        assert_eq!(1, net.step_maximal());
+        assert_eq!(2, net.step_maximal(), "source and t fire");
+        assert_eq!(2, net.step_maximal(), "the source reached its limit");
+        assert_eq!(1, net.step_maximal());
+        assert_eq!(Err(FireError::NotEnabled), net.fire_transition(0));
+        assert_eq!(2, net.source_firings());
+        assert!(net.is_deadlocked());
+        assert_eq!(vec![0, 0], net.marking());
+
+        net.reset();
+        assert_eq!(6, net.run_until_deadlock(10));
+        assert_eq!(vec![0, 0], net.marking());
+    }
+
+    #[test]
+    fn test_undo_source_firing() {
+        let p1 = Place::new(0, "p1");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let mut source = Transition::new();
+        source.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(source);
+        net.set_source_limit(1);
+        net.set_undo_depth(1);
+        assert!(net.step());
+        assert!(!net.step(), "the source reached its limit");
+
+        assert!(net.undo());
+        assert_eq!(0, net.source_firings());
+        assert_eq!(vec![0], net.marking());
+        assert!(net.step(), "the undone firing doesn't count");
+        assert_eq!(1, net.source_firings());
+    }
+
+    #[test]
+    fn test_firing_constraint() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // t1 moves tokens from p1 to p2, t2 back, but p2 may hold at most
+        // two tokens and t2 may only fire once p2 is full
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.set_firing_constraint(|t, marking: &[u32]| match t {
+            0 => marking[1] < 2,
+            _ => marking[1] == 2,
+        });
+
+        assert_eq!(vec![0], net.enabled_transitions());
+        assert_eq!(Err(FireError::NotEnabled), net.fire_transition(1));
+        assert!(net.step());
+        assert_eq!(1, net.step_maximal());
+        assert_eq!(vec![1, 2], net.marking());
+        assert_eq!(vec![1], net.enabled_transitions(), "t1 is blocked");
+        assert!(net.step());
+        assert_eq!(vec![2, 1], net.marking());
+        assert!(!net.is_deadlocked());
+    }
+
+    #[test]
+    fn test_overflow_arc() {
+        let source = Place::new(3, "source");
+        let buffer = Place::with_capacity(0, "buffer", 2);
+        let dropped = Place::with_capacity(0, "dropped", 2);
+        let a1 = Arc::new(&source, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        assert_eq!(
+            Err(ArcError::ZeroWeight),
+            t.add_output_with_overflow(&buffer, &dropped, 0)
+        );
+        assert_eq!(Ok(()), t.add_output_with_overflow(&buffer, &dropped, 1));
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        assert_eq!(
+            vec![("source", 3), ("buffer", 0), ("dropped", 0)],
+            net.named_marking()
+        );
+
+        assert_eq!(3, net.run_until_deadlock(10));
+        assert_eq!(vec![0, 2, 1], net.marking(), "the third token overflows");
+
+        // two tokens at once, with room for one of them next to the output
+        // arc on the buffer
+        let a2 = Arc::new(&buffer, 1).expect("weight greater than zero");
+        let mut burst = Transition::new();
+        burst.add_output(&a2);
+        burst
+            .add_output_with_overflow(&buffer, &dropped, 2)
+            .expect("weight greater than zero");
+        buffer.set_tokens(0);
+        dropped.set_tokens(0);
+        assert!(burst.is_enabled());
+        assert_eq!(Ok(()), burst.fire());
+        assert_eq!((2, 1), (buffer.tokens(), dropped.tokens()));
+
+        assert!(!burst.is_enabled(), "the plain arc doesn't overflow");
+
+        buffer.set_tokens(0);
+        dropped.set_tokens(2);
+        assert!(!burst.is_enabled(), "the overflow place is full");
+    }
+
+    #[test]
+    fn test_undo() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        t2.add_reset(&p1);
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        net.step();
+        assert!(!net.undo(), "undo is disabled by default");
+
+        net.set_undo_depth(2);
+        assert_eq!(Ok(()), net.fire_transition(0));
+        assert_eq!(Ok(()), net.fire_transition(1));
+        assert_eq!(Err(FireError::NotEnabled), net.fire_transition(0));
+        assert_eq!(vec![0, 1, 1], net.marking());
+
+        assert!(net.undo());
+        assert_eq!(vec![1, 2, 0], net.marking(), "the reset is reverted");
+        assert!(net.undo());
+        assert_eq!(vec![2, 1, 0], net.marking());
+        assert!(!net.undo());
+
+        assert_eq!(2, net.step_maximal());
+        assert!(net.undo());
+        assert_eq!(vec![2, 1, 0], net.marking(), "the whole step is reverted");
+
+        // only the last firing is remembered
+        net.set_undo_depth(1);
+        assert_eq!(2, net.step_maximal());
+        assert_eq!(vec![0, 1, 1], net.marking());
+        assert!(net.step());
+        assert!(!net.step());
+        assert_eq!(vec![0, 0, 2], net.marking());
+        assert!(net.undo());
+        assert_eq!(vec![0, 1, 1], net.marking());
+        assert!(!net.undo());
+    }
+
+    #[test]
+    fn test_token_policy() {
+        let p1 = Place::new(u32::MAX - 1, "p1");
+        let p2 = Place::new(1, "p2");
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+
+        // an unbounded producer without any consumer
+        let mut t = Transition::new();
+        t.add_loop(&p2, 1).expect("weight greater than zero");
+        t.add_output(&a1);
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        assert_eq!(TokenPolicy::Checked, net.token_policy());
+        assert_eq!(Err(FireError::Overflow), net.fire_transition(0));
+        assert_eq!(
+            "token count would overflow",
+            FireError::Overflow.to_string()
+        );
+        assert_eq!(vec![1, u32::MAX - 1], net.marking(), "marking is untouched");
+        assert!(!net.step());
+        assert_eq!(0, net.step_maximal());
+
+        net.set_token_policy(TokenPolicy::Saturating);
+        assert_eq!(Ok(()), net.fire_transition(0));
+        assert_eq!(vec![1, u32::MAX], net.marking());
+
+        net.set_token_policy(TokenPolicy::Wrapping);
+        assert_eq!(Ok(()), net.fire_transition(0));
+        assert_eq!(vec![1, 1], net.marking());
+    }
+
+    #[test]
+    fn test_reset() {
+        let p1 = Place::new(2, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+
+        p1.tokens.set(1);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        net.add_transition(t2);
+
+        assert_eq!(&[2, 0], net.initial_marking(), "recorded on registration");
+        assert_eq!(2, net.run_until_deadlock(10));
+        assert_eq!(vec![0, 0], net.marking());
+        net.reset();
+        assert_eq!(vec![2, 0], net.marking());
+    }
+
+    #[test]
+    #[should_panic(expected = "marking must hold a token count for every place")]
+    fn test_restore_wrong_length() {
+        let p = Place::new(2, "p");
+        let a = Arc::new(&p, 1).expect("weight greater than zero");
+        let mut t = Transition::new();
+        t.add_input(&a);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        net.restore(&[1, 2]);
+    }
+
+    #[test]
+    fn test_step_priority() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+        let p4 = Place::new(0, "p4");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+        let a4 = Arc::new(&p4, 1).expect("weight greater than zero");
+
+        let mut low = Transition::new();
+        low.add_input(&a1);
+        low.add_output(&a2);
+        let mut high = Transition::new();
+        high.set_priority(2);
+        high.add_input(&a1);
+        high.add_output(&a3);
+        let mut tie = Transition::new();
+        tie.set_priority(2);
+        tie.add_input(&a1);
+        tie.add_output(&a4);
+
+        assert_eq!(0, low.priority());
+        assert_eq!(2, high.priority());
+
+        let mut net = Petrinet::new();
+        net.add_transition(low);
+        net.add_transition(high);
+        net.add_transition(tie);
+
+        assert!(net.step_priority());
+        assert_eq!(vec![2, 0, 1, 0], net.marking());
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..2 {
+            assert!(net.step_random(&mut rng));
+        }
+        assert_eq!(0, p2.tokens(), "random steps respect priorities");
+        assert!(!net.step_priority());
+    }
+
+    #[test]
+    fn test_dynamic_priority() {
+        let q1 = Place::new(2, "q1");
+        let q2 = Place::new(3, "q2");
+        let a1 = Arc::new(&q1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&q2, 1).expect("weight greater than zero");
+
+        // drain q2 while it holds more tokens than the constant priority of
+        // t1, which wins ties as it was added first
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.set_priority(2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.set_dynamic_priority(|view| i64::from(view.tokens("q2").unwrap_or(0)));
+        assert_eq!(3, t2.current_priority());
+        assert_eq!(2, t1.current_priority());
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.enable_trace();
+        while net.step_priority() {}
+        let fired: Vec<usize> = net.trace().iter().map(|e| e.transition()).collect();
+        assert_eq!(vec![1, 0, 0, 1, 1], fired);
+
+        net.transitions[1].set_priority(0);
+        assert_eq!(0, net.transitions[1].current_priority(), "constant again");
+    }
+
+    #[test]
+    fn test_weighted_transition_scheduler() {
+        let p = Place::new(1, "p");
+        let a = Arc::new(&p, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a);
+        t1.set_rate(0.0);
+        let mut t2 = Transition::new();
+        t2.add_input(&a);
+        t2.set_rate(3.0);
+        let mut t3 = Transition::new();
+        t3.add_input(&a);
+        assert_eq!(3.0, t2.rate());
+        assert_eq!(1.0, t3.rate(), "default rate");
+        assert_eq!(1.0, Transition::default().rate(), "default rate");
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+        let mut scheduler = WeightedTransitionScheduler::new(StdRng::seed_from_u64(3));
+        let mut counts = [0; 3];
+        for _ in 0..1000 {
+            let i = scheduler
+                .choose(&[0, 1, 2], &net)
+                .expect("enabled transition");
+            counts[i] += 1;
+        }
+        assert_eq!(0, counts[0], "zero rate never fires");
+        assert!(counts[1] > 2 * counts[2], "counts {:?}", counts);
+
+        // only a single transition with zero rate is enabled
+        assert_eq!(None, scheduler.choose(&[0], &net));
+        assert_eq!(None, scheduler.choose(&[], &net));
+    }
+
+    #[test]
+    fn test_seeded_schedulers() {
+        let p = Place::new(1, "p");
+        let a = Arc::new(&p, 1).expect("weight greater than zero");
+        let mut net = Petrinet::new();
+        for _ in 0..4 {
+            let mut t = Transition::new();
+            t.add_input(&a);
+            net.add_transition(t);
+        }
+
+        let enabled = [0, 1, 2, 3];
+        let choices = |scheduler: &mut dyn Scheduler| -> Vec<Option<usize>> {
+            (0..20).map(|_| scheduler.choose(&enabled, &net)).collect()
+        };
+        assert_eq!(
+            choices(&mut RandomTransitionScheduler::with_seed(7)),
+            choices(&mut RandomTransitionScheduler::with_seed(7))
+        );
+        assert_eq!(
+            choices(&mut WeightedTransitionScheduler::with_seed(7)),
+            choices(&mut WeightedTransitionScheduler::with_seed(7))
+        );
+        assert_eq!(
+            choices(&mut RandomTransitionScheduler::with_seed(7)),
+            choices(&mut RandomTransitionScheduler::new(StdRng::seed_from_u64(
+                7
+            )))
+        );
+    }
+
+    #[test]
+    fn test_schedulers() {
+        struct LastEnabledScheduler;
+
+        impl Scheduler for LastEnabledScheduler {
+            fn choose(&mut self, enabled: &[usize], _net: &Petrinet<'_>) -> Option<usize> {
+                enabled.last().copied()
+            }
+        }
+
+        let p1 = Place::new(1, "P1");
+        let p2 = Place::new(0, "P2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut net = Petrinet::new();
+        for priority in &[0, 2, 2, 1] {
+            let mut t = Transition::new();
+            t.add_input(&a1);
+            t.add_output(&a1);
+            t.set_priority(*priority);
+            net.add_transition(t);
+        }
+        let mut t = Transition::new();
+        t.add_input(&a2);
+        net.add_transition(t);
+
+        let enabled = net.enabled_transitions();
+        assert_eq!(vec![0, 1, 2, 3], enabled);
+        assert_eq!(Some(0), FirstEnabledScheduler.choose(&enabled, &net));
+        assert_eq!(Some(1), PriorityScheduler.choose(&enabled, &net));
+        assert_eq!(Some(3), LastEnabledScheduler.choose(&enabled, &net));
+        assert_eq!(None, PriorityScheduler.choose(&[], &net));
+        assert_eq!(2, net.transitions()[1].priority());
+
+        net.enable_trace();
+        assert!(net.step_with(&mut LastEnabledScheduler));
+        assert_eq!(3, net.trace()[0].transition());
+    }
+
+    #[test]
+    fn test_fair_scheduler() {
+        let p1 = Place::new(1, "P1");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+
+        // three transitions competing for the same token, which always
+        // returns
+        let mut net = Petrinet::new();
+        for _ in 0..3 {
+            let mut t = Transition::new();
+            t.add_input(&a1);
+            t.add_output(&a1);
+            net.add_transition(t);
+        }
+        net.enable_trace();
+
+        let mut scheduler = FairScheduler::new();
+        for _ in 0..6 {
+            assert!(net.step_with(&mut scheduler));
+        }
+        let fired: Vec<usize> = net.trace().iter().map(|e| e.transition()).collect();
+        assert_eq!(vec![0, 1, 2, 0, 1, 2], fired);
+
+        p1.tokens.set(0);
+        assert!(!net.step_with(&mut scheduler));
+    }
+
+    #[test]
+    fn test_branch_schedulers() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(1, "p3");
+        let p4 = Place::new(0, "p4");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+        let a4 = Arc::new(&p4, 1).expect("weight greater than zero");
+
+        // two parallel cycles, t0 and t1 between p1 and p2, and t2 and t3
+        // between p3 and p4
+        let mut net = Petrinet::new();
+        for (input, output) in [(&a1, &a2), (&a2, &a1), (&a3, &a4), (&a4, &a3)] {
+            let mut t = Transition::new();
+            t.add_input(input);
+            t.add_output(output);
+            net.add_transition(t);
+        }
+
+        fn run(net: &mut Petrinet<'_>, scheduler: &mut impl Scheduler) -> Vec<usize> {
+            net.reset();
+            net.enable_trace();
+            let start = net.trace().len();
+            for _ in 0..4 {
+                assert!(net.step_with(scheduler));
+            }
+            net.trace()[start..]
+                .iter()
+                .map(|e| e.transition())
+                .collect()
+        }
+
+        let mut fair = FairBranchScheduler::new();
+        assert_eq!(vec![0, 1, 2, 3], run(&mut net, &mut fair));
+        let mut sequential = SequentialBranchScheduler::new();
+        assert_eq!(vec![0, 1, 0, 1], run(&mut net, &mut sequential));
+
+        assert_eq!(None, fair.choose(&[], &net));
+        assert_eq!(None, sequential.choose(&[], &net));
+    }
+
+    #[test]
+    fn test_step_weighted() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        t.set_rate(0.5);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        assert!(
+            net.step_weighted(&mut rng),
+            "single enabled transition fires"
+        );
+        assert!(!net.step_weighted(&mut rng));
+        assert_eq!(vec![0, 1], net.marking());
+    }
+
+    #[test]
+    #[should_panic(expected = "rate must be finite and non-negative")]
+    fn test_negative_rate() {
+        Transition::new().set_rate(-1.0);
+    }
+
+    #[test]
+    fn test_step_maximal() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+        let p4 = Place::new(1, "p4");
+        let p5 = Place::with_capacity(0, "p5", 1);
+
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+        let a4 = Arc::new(&p4, 1).expect("weight greater than zero");
+        let a5 = Arc::new(&p5, 1).expect("weight greater than zero");
+
+        // t1 and t2 conflict for the tokens on p1, t3 runs concurrently
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a4);
+        t3.add_output(&a5);
+        // t4 needs the token produced by t1 and must wait for the next step
+        let mut t4 = Transition::new();
+        t4.add_input(&a2);
+        t4.add_output(&a5);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+        net.add_transition(t4);
+
+        assert_eq!(2, net.step_maximal());
+        assert_eq!(vec![1, 1, 0, 0, 1], net.marking());
+
+        assert_eq!(0, net.step_maximal(), "p5 is at capacity");
+        assert_eq!(vec![1, 1, 0, 0, 1], net.marking());
+    }
+
+    #[test]
+    fn test_random_transition_scheduler() {
+        let rng = StepRng::new(2, 1);
+        let mut scheduler = super::RandomTransitionScheduler::<StepRng>::new(rng);
+
+        let mut net = Petrinet::new();
+        net.add_transition(Transition::new());
+        net.add_transition(Transition::new());
+
+        if let Some(i) = scheduler.choose(&[0, 1], &net) {
+            assert!(i < 2);
+        } else {
+            panic!("scheduler didn't return any valid transition");
+        }
+    }
+
+    #[test]
+    fn test_random_transition_scheduler_only_picks_enabled() {
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(1, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let mut scheduler = RandomTransitionScheduler::new(StdRng::seed_from_u64(42));
+        for _ in 0..10 {
+            assert_eq!(Some(1), scheduler.choose(&net.enabled_transitions(), &net));
+        }
+
+        assert_eq!(1, p2.tokens(), "scheduling must not fire transitions");
+
+        p2.tokens.set(0);
+        assert_eq!(None, scheduler.choose(&net.enabled_transitions(), &net));
+        assert!(!net.step_with(&mut scheduler));
+    }
+}
@@ -0,0 +1,792 @@
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::result;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{covers, fired_tokens, Arc, ArcError, FireError, Petrinet, Place, Transition};
+
+/// An arc of an `OwnedPetrinet`, referring to its place by index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedArc {
+    place: usize,
+    weight: u32,
+}
+
+impl OwnedArc {
+    /// Index of the place in the owning net.
+    pub fn place(&self) -> usize {
+        self.place
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+}
+
+/// Error returned when an `OwnedPetrinet` cannot be built from incidence
+/// matrices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixError {
+    /// A matrix doesn't have a row for every place of the initial marking.
+    RowCount { expected: usize, actual: usize },
+    /// The row of this place has a different number of columns than the
+    /// first row of the pre matrix.
+    ColumnCount { place: usize },
+    /// The columns of this transition hold only zeros, so it has no arc.
+    EmptyTransition(usize),
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RowCount { expected, actual } => write!(
+                f,
+                "matrix has {} rows, but the initial marking has {} places",
+                actual, expected
+            ),
+            Self::ColumnCount { place } => {
+                write!(f, "row of place {} has the wrong number of columns", place)
+            }
+            Self::EmptyTransition(transition) => {
+                write!(f, "transition {} has no arc", transition)
+            }
+        }
+    }
+}
+
+impl error::Error for MatrixError {}
+
+/// Error returned when two `OwnedPetrinet`s cannot be composed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComposeError {
+    /// There is no place with this name in the respective net.
+    NoSuchPlace(String),
+    /// The place with this name is in more than one shared pair.
+    SharedTwice(String),
+    /// The shared places hold different numbers of tokens.
+    MarkingMismatch {
+        place: String,
+        left: u32,
+        right: u32,
+    },
+}
+
+impl fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchPlace(name) => write!(f, "no such place {}", name),
+            Self::SharedTwice(name) => write!(f, "place {} is shared twice", name),
+            Self::MarkingMismatch { place, left, right } => write!(
+                f,
+                "shared place {} holds {} tokens in one net and {} in the other",
+                place, left, right
+            ),
+        }
+    }
+}
+
+impl error::Error for ComposeError {}
+
+/// A transition of an `OwnedPetrinet`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OwnedTransition {
+    name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    priority: u32,
+    inputs: Vec<OwnedArc>,
+    outputs: Vec<OwnedArc>,
+}
+
+impl OwnedTransition {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    pub fn inputs(&self) -> &[OwnedArc] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[OwnedArc] {
+        &self.outputs
+    }
+}
+
+/// A petri net which owns its places.
+///
+/// Places and transitions are referred to by index instead of by reference,
+/// so an owned net can be built, returned and stored freely.
+///
+/// With the `serde` feature, owned nets can be serialized. Deserialization
+/// checks that all arcs refer to existing places and have a positive weight.
+///
+/// Cloning a net copies its marking, so the clone can be fired
+/// independently, e.g. to explore different futures from the same state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "OwnedPetrinetData"))]
+pub struct OwnedPetrinet {
+    places: Vec<Place>,
+    transitions: Vec<OwnedTransition>,
+}
+
+impl OwnedPetrinet {
+    pub fn new() -> Self {
+        Self {
+            places: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Build a net from its pre and post incidence matrices, where
+    /// `pre[p][t]` is the weight of the arc from place `p` to transition `t`,
+    /// `post[p][t]` the weight of the arc from `t` to `p`, and 0 stands for
+    /// no arc. `initial` holds the initial token count of every place.
+    ///
+    /// Places are named `p0`, `p1` and so on, and transitions `t0`, `t1` and
+    /// so on. Every transition needs at least one arc.
+    pub fn from_matrices(
+        pre: &[Vec<u32>],
+        post: &[Vec<u32>],
+        initial: &[u32],
+    ) -> result::Result<Self, MatrixError> {
+        for matrix in &[pre, post] {
+            if matrix.len() != initial.len() {
+                return Err(MatrixError::RowCount {
+                    expected: initial.len(),
+                    actual: matrix.len(),
+                });
+            }
+        }
+
+        let transitions = pre.first().map_or(0, Vec::len);
+        for (place, (pre, post)) in pre.iter().zip(post).enumerate() {
+            if pre.len() != transitions || post.len() != transitions {
+                return Err(MatrixError::ColumnCount { place });
+            }
+        }
+
+        let mut net = Self::new();
+        for (p, &tokens) in initial.iter().enumerate() {
+            net.add_place(tokens, &format!("p{}", p));
+        }
+
+        for t in 0..transitions {
+            net.add_transition(Some(&format!("t{}", t)));
+            for p in 0..initial.len() {
+                if pre[p][t] > 0 {
+                    net.transitions[t].inputs.push(OwnedArc {
+                        place: p,
+                        weight: pre[p][t],
+                    });
+                }
+                if post[p][t] > 0 {
+                    net.transitions[t].outputs.push(OwnedArc {
+                        place: p,
+                        weight: post[p][t],
+                    });
+                }
+            }
+
+            if net.transitions[t].inputs.is_empty() && net.transitions[t].outputs.is_empty() {
+                return Err(MatrixError::EmptyTransition(t));
+            }
+        }
+
+        Ok(net)
+    }
+
+    /// Fuse `other` into the net along shared places. Every pair of `shared`
+    /// names a place of the net and a place of `other`, which become one
+    /// place, keeping the name and capacity of the place of the net. Shared
+    /// places must hold the same number of tokens. Names refer to the first
+    /// place with that name.
+    ///
+    /// The places and transitions of the net keep their indices. The
+    /// unshared places of `other` and then its transitions are appended in
+    /// order, with their arcs referring to the new indices.
+    pub fn compose(
+        mut self,
+        other: Self,
+        shared: &[(&str, &str)],
+    ) -> result::Result<Self, ComposeError> {
+        let find = |net: &Self, name: &str| {
+            net.places
+                .iter()
+                .position(|place| place.name() == name)
+                .ok_or_else(|| ComposeError::NoSuchPlace(name.to_string()))
+        };
+
+        let mut mapping: Vec<Option<usize>> = vec![None; other.places.len()];
+        let mut fused = Vec::with_capacity(shared.len());
+        for &(left, right) in shared {
+            let p = find(&self, left)?;
+            let q = find(&other, right)?;
+            if fused.contains(&p) {
+                return Err(ComposeError::SharedTwice(left.to_string()));
+            }
+            if mapping[q].is_some() {
+                return Err(ComposeError::SharedTwice(right.to_string()));
+            }
+
+            let (left_tokens, right_tokens) = (self.places[p].tokens(), other.places[q].tokens());
+            if left_tokens != right_tokens {
+                return Err(ComposeError::MarkingMismatch {
+                    place: left.to_string(),
+                    left: left_tokens,
+                    right: right_tokens,
+                });
+            }
+
+            fused.push(p);
+            mapping[q] = Some(p);
+        }
+
+        for (q, place) in other.places.into_iter().enumerate() {
+            if mapping[q].is_none() {
+                self.places.push(place);
+                mapping[q] = Some(self.places.len() - 1);
+            }
+        }
+
+        let reindex = |arcs: &mut Vec<OwnedArc>| {
+            for arc in arcs {
+                arc.place = mapping[arc.place].expect("every place is mapped");
+            }
+        };
+        for mut transition in other.transitions {
+            reindex(&mut transition.inputs);
+            reindex(&mut transition.outputs);
+            self.transitions.push(transition);
+        }
+
+        Ok(self)
+    }
+
+    /// Add a place and return its index.
+    pub fn add_place(&mut self, tokens: u32, name: &str) -> usize {
+        self.places.push(Place::new(tokens, name));
+        self.places.len() - 1
+    }
+
+    /// Add a transition without arcs and return its index.
+    pub fn add_transition(&mut self, name: Option<&str>) -> usize {
+        self.transitions.push(OwnedTransition {
+            name: name.map(str::to_string),
+            ..OwnedTransition::default()
+        });
+        self.transitions.len() - 1
+    }
+
+    /// Set the priority of the transition at index `transition`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn set_priority(&mut self, transition: usize, priority: u32) {
+        self.transitions[transition].priority = priority;
+    }
+
+    fn arc(
+        &self,
+        transition: usize,
+        place: usize,
+        weight: u32,
+    ) -> result::Result<OwnedArc, ArcError> {
+        if transition >= self.transitions.len() {
+            Err(ArcError::NoSuchTransition(transition))
+        } else if place >= self.places.len() {
+            Err(ArcError::NoSuchPlace(place))
+        } else if weight < 1 {
+            Err(ArcError::ZeroWeight)
+        } else {
+            Ok(OwnedArc { place, weight })
+        }
+    }
+
+    /// Add an arc from `place` to `transition`.
+    pub fn add_input(
+        &mut self,
+        transition: usize,
+        place: usize,
+        weight: u32,
+    ) -> result::Result<(), ArcError> {
+        let arc = self.arc(transition, place, weight)?;
+        self.transitions[transition].inputs.push(arc);
+        Ok(())
+    }
+
+    /// Add an arc from `transition` to `place`.
+    pub fn add_output(
+        &mut self,
+        transition: usize,
+        place: usize,
+        weight: u32,
+    ) -> result::Result<(), ArcError> {
+        let arc = self.arc(transition, place, weight)?;
+        self.transitions[transition].outputs.push(arc);
+        Ok(())
+    }
+
+    pub fn places(&self) -> &[Place] {
+        &self.places
+    }
+
+    pub fn transitions(&self) -> &[OwnedTransition] {
+        &self.transitions
+    }
+
+    /// The token counts of all places, in index order.
+    pub fn marking(&self) -> Vec<u32> {
+        self.places.iter().map(Place::tokens).collect()
+    }
+
+    /// The summed weights of the input and of the output arcs of
+    /// `transition`, per place.
+    fn token_flow(&self, transition: usize) -> (Vec<u64>, Vec<u64>) {
+        let mut demand = vec![0u64; self.places.len()];
+        let mut supply = vec![0u64; self.places.len()];
+        for arc in &self.transitions[transition].inputs {
+            demand[arc.place] += u64::from(arc.weight);
+        }
+        for arc in &self.transitions[transition].outputs {
+            supply[arc.place] += u64::from(arc.weight);
+        }
+        (demand, supply)
+    }
+
+    /// A transition is enabled if every input place holds at least the
+    /// summed weight of its input arcs, and no place exceeds its capacity
+    /// after firing, like `Transition::is_enabled`.
+    pub fn is_enabled(&self, transition: usize) -> bool {
+        let (demand, supply) = self.token_flow(transition);
+        demand
+            .iter()
+            .zip(&supply)
+            .zip(&self.places)
+            .all(|((&demand, &supply), place)| {
+                let tokens = u64::from(place.tokens());
+                covers(place.tokens(), demand)
+                    && place
+                        .capacity()
+                        .is_none_or(|capacity| tokens - demand + supply <= u64::from(capacity))
+            })
+    }
+
+    /// Indices of all transitions which are enabled in the current marking.
+    pub fn enabled_transitions(&self) -> Vec<usize> {
+        (0..self.transitions.len())
+            .filter(|&t| self.is_enabled(t))
+            .collect()
+    }
+
+    /// Fire the transition at index `transition`. Like
+    /// `TokenPolicy::Checked`, firing fails with `FireError::Overflow`
+    /// without touching any place if a token count would overflow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn fire(&mut self, transition: usize) -> result::Result<(), FireError> {
+        if !self.is_enabled(transition) {
+            return Err(FireError::NotEnabled);
+        }
+
+        let (demand, supply) = self.token_flow(transition);
+        let marking = self
+            .places
+            .iter()
+            .zip(demand.iter().zip(&supply))
+            .map(|(place, (&demand, &supply))| fired_tokens(place.tokens(), demand, supply))
+            .collect::<result::Result<Vec<_>, _>>()?;
+
+        for (place, tokens) in self.places.iter().zip(marking) {
+            place.tokens.set(tokens);
+        }
+        Ok(())
+    }
+
+    /// Fire the first enabled transition. Returns whether a transition
+    /// fired.
+    pub fn step(&mut self) -> bool {
+        (0..self.transitions.len()).any(|t| self.fire(t).is_ok())
+    }
+
+    /// Call `f` with a borrowed `Petrinet` view of the net. The view shares
+    /// the places of the owned net, so firing transitions of the view changes
+    /// the marking of the owned net.
+    ///
+    /// Places and transitions keep their indices in the view.
+    pub fn with_net<R>(&self, f: impl FnOnce(&mut Petrinet<'_>) -> R) -> R {
+        let arcs: Vec<(Vec<Arc<'_>>, Vec<Arc<'_>>)> = self
+            .transitions
+            .iter()
+            .map(|transition| {
+                let arcs = |arcs: &[OwnedArc]| {
+                    arcs.iter()
+                        .map(|arc| Arc {
+                            weight: arc.weight,
+                            place: &self.places[arc.place],
+                        })
+                        .collect()
+                };
+                (arcs(&transition.inputs), arcs(&transition.outputs))
+            })
+            .collect();
+
+        let mut net = Petrinet::new();
+        for place in &self.places {
+            net.register_place(place);
+        }
+
+        for (transition, (inputs, outputs)) in self.transitions.iter().zip(&arcs) {
+            let mut view = Transition::new();
+            view.name = transition.name.clone();
+            view.priority = transition.priority;
+            for arc in inputs {
+                view.add_input(arc);
+            }
+            for arc in outputs {
+                view.add_output(arc);
+            }
+            net.add_transition(view);
+        }
+
+        f(&mut net)
+    }
+}
+
+/// Unchecked contents of a deserialized `OwnedPetrinet`.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct OwnedPetrinetData {
+    places: Vec<Place>,
+    transitions: Vec<OwnedTransition>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<OwnedPetrinetData> for OwnedPetrinet {
+    type Error = ArcError;
+
+    fn try_from(data: OwnedPetrinetData) -> result::Result<Self, Self::Error> {
+        let mut net = Self {
+            places: data.places,
+            transitions: Vec::new(),
+        };
+
+        for transition in data.transitions {
+            let t = net.add_transition(transition.name());
+            net.set_priority(t, transition.priority);
+            for arc in transition.inputs {
+                net.add_input(t, arc.place, arc.weight)?;
+            }
+            for arc in transition.outputs {
+                net.add_output(t, arc.place, arc.weight)?;
+            }
+        }
+
+        Ok(net)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_matrices() {
+        // p0 -> t0 -> p1 -> t1 -> p0, where t0 needs two tokens
+        let pre = vec![vec![2, 0], vec![0, 1]];
+        let post = vec![vec![0, 2], vec![1, 0]];
+        let mut net = OwnedPetrinet::from_matrices(&pre, &post, &[2, 0]).expect("valid matrices");
+
+        assert_eq!("p1", net.places()[1].name());
+        assert_eq!(Some("t1"), net.transitions()[1].name());
+        assert_eq!(
+            &[OwnedArc {
+                place: 0,
+                weight: 2
+            }],
+            net.transitions()[0].inputs()
+        );
+        assert_eq!(vec![0], net.enabled_transitions());
+        assert!(net.step());
+        assert_eq!(vec![0, 1], net.marking());
+        assert!(net.step());
+        assert_eq!(vec![2, 0], net.marking());
+    }
+
+    #[test]
+    fn test_from_invalid_matrices() {
+        assert_eq!(
+            Err(MatrixError::RowCount {
+                expected: 2,
+                actual: 1
+            }),
+            OwnedPetrinet::from_matrices(&[vec![1]], &[vec![0], vec![1]], &[0, 0])
+        );
+        assert_eq!(
+            Err(MatrixError::ColumnCount { place: 1 }),
+            OwnedPetrinet::from_matrices(&[vec![1], vec![0]], &[vec![0], vec![1, 0]], &[0, 0])
+        );
+        let error = OwnedPetrinet::from_matrices(&[vec![1, 0]], &[vec![0, 0]], &[0]).unwrap_err();
+        assert_eq!(MatrixError::EmptyTransition(1), error);
+        assert_eq!("transition 1 has no arc", error.to_string());
+    }
+
+    #[test]
+    fn test_owned_petrinet() {
+        let mut net = OwnedPetrinet::new();
+        let p1 = net.add_place(2, "p1");
+        let p2 = net.add_place(0, "p2");
+        let t = net.add_transition(Some("t"));
+
+        assert_eq!(Ok(()), net.add_input(t, p1, 2));
+        assert_eq!(Ok(()), net.add_output(t, p2, 1));
+        assert_eq!(Err(ArcError::NoSuchPlace(2)), net.add_input(t, 2, 1));
+        assert_eq!(Err(ArcError::NoSuchTransition(1)), net.add_output(1, p2, 1));
+        assert_eq!(Err(ArcError::ZeroWeight), net.add_output(t, p2, 0));
+
+        assert_eq!(2, net.places().len());
+        assert_eq!("p1", net.places()[p1].name());
+        assert_eq!(Some("t"), net.transitions()[t].name());
+        assert_eq!(p1, net.transitions()[t].inputs()[0].place());
+        assert_eq!(2, net.transitions()[t].inputs()[0].weight());
+        assert_eq!(p2, net.transitions()[t].outputs()[0].place());
+        assert_eq!(1, net.transitions()[t].outputs()[0].weight());
+    }
+
+    fn build_net() -> OwnedPetrinet {
+        let mut net = OwnedPetrinet::new();
+        let p1 = net.add_place(2, "p1");
+        let p2 = net.add_place(0, "p2");
+        let p3 = net.add_place(0, "p3");
+        let t1 = net.add_transition(Some("t1"));
+        let t2 = net.add_transition(None);
+
+        net.add_input(t1, p1, 1).expect("valid arc");
+        net.add_output(t1, p2, 2).expect("valid arc");
+        net.add_input(t2, p2, 3).expect("valid arc");
+        net.add_output(t2, p3, 1).expect("valid arc");
+
+        net
+    }
+
+    #[test]
+    fn test_compose() {
+        // a producer and a consumer, wired together by a buffer place
+        let mut producer = OwnedPetrinet::new();
+        let idle = producer.add_place(1, "idle");
+        let out = producer.add_place(0, "out");
+        let produce = producer.add_transition(Some("produce"));
+        producer.add_input(produce, idle, 1).expect("valid arc");
+        producer.add_output(produce, out, 1).expect("valid arc");
+        producer.add_output(produce, idle, 1).expect("valid arc");
+
+        let mut consumer = OwnedPetrinet::new();
+        let done = consumer.add_place(0, "done");
+        let buffer = consumer.add_place(0, "in");
+        let consume = consumer.add_transition(Some("consume"));
+        consumer.add_input(consume, buffer, 2).expect("valid arc");
+        consumer.add_output(consume, done, 1).expect("valid arc");
+
+        let mut net = producer
+            .compose(consumer, &[("out", "in")])
+            .expect("composable nets");
+        assert_eq!(vec![1, 0, 0], net.marking());
+        assert_eq!("done", net.places()[2].name());
+        assert_eq!(Some("consume"), net.transitions()[1].name());
+        assert_eq!(1, net.transitions()[1].inputs()[0].place());
+        assert_eq!(2, net.transitions()[1].outputs()[0].place());
+
+        assert_eq!(Ok(()), net.fire(0));
+        assert_eq!(Ok(()), net.fire(0));
+        assert_eq!(Ok(()), net.fire(1));
+        assert_eq!(vec![1, 0, 1], net.marking());
+    }
+
+    #[test]
+    fn test_compose_errors() {
+        let net = |tokens| {
+            let mut net = OwnedPetrinet::new();
+            net.add_place(tokens, "a");
+            net.add_place(0, "b");
+            net
+        };
+
+        assert_eq!(
+            Err(ComposeError::NoSuchPlace("c".to_string())),
+            net(0).compose(net(0), &[("a", "c")])
+        );
+        assert_eq!(
+            Err(ComposeError::SharedTwice("a".to_string())),
+            net(0).compose(net(0), &[("a", "a"), ("a", "b")])
+        );
+        assert_eq!(
+            Err(ComposeError::SharedTwice("b".to_string())),
+            net(0).compose(net(0), &[("a", "b"), ("b", "b")])
+        );
+
+        let error = net(1).compose(net(2), &[("a", "a")]).unwrap_err();
+        assert_eq!(
+            ComposeError::MarkingMismatch {
+                place: "a".to_string(),
+                left: 1,
+                right: 2
+            },
+            error
+        );
+        assert_eq!(
+            "shared place a holds 1 tokens in one net and 2 in the other",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_owned_petrinet_firing() {
+        let mut net = build_net();
+
+        assert_eq!(vec![0], net.enabled_transitions());
+        assert_eq!(Err(FireError::NotEnabled), net.fire(1));
+        assert_eq!(vec![2, 0, 0], net.marking());
+
+        assert_eq!(Ok(()), net.fire(0));
+        assert_eq!(vec![1, 2, 0], net.marking());
+
+        assert!(net.step());
+        assert_eq!(vec![0, 4, 0], net.marking());
+        assert_eq!(vec![1], net.enabled_transitions());
+
+        assert!(net.step());
+        assert_eq!(vec![0, 1, 1], net.marking());
+        assert!(!net.step());
+    }
+
+    #[test]
+    fn test_owned_petrinet_parallel_arcs() {
+        let mut net = OwnedPetrinet::new();
+        let p = net.add_place(1, "p");
+        let t = net.add_transition(None);
+        net.add_input(t, p, 1).expect("valid arc");
+        net.add_input(t, p, 1).expect("valid arc");
+
+        assert!(!net.is_enabled(t));
+        assert_eq!(Err(FireError::NotEnabled), net.fire(t));
+        assert_eq!(vec![1], net.marking());
+    }
+
+    #[test]
+    fn test_owned_petrinet_overflow() {
+        let mut net = OwnedPetrinet::new();
+        let p = net.add_place(u32::MAX - 1, "p");
+        let q = net.add_place(1, "q");
+        let t = net.add_transition(None);
+        net.add_input(t, q, 1).expect("valid arc");
+        net.add_output(t, p, 2).expect("valid arc");
+
+        assert_eq!(Err(FireError::Overflow), net.fire(t));
+        assert_eq!(vec![u32::MAX - 1, 1], net.marking(), "no place changed");
+        assert!(!net.step());
+    }
+
+    #[test]
+    fn test_owned_petrinet_with_net() {
+        let net = build_net();
+
+        let marking = net.with_net(|view| {
+            assert_eq!(vec![2, 0, 0], view.marking());
+            view.run_until_deadlock(10);
+            view.marking()
+        });
+
+        assert_eq!(vec![0, 1, 1], marking);
+        assert_eq!(marking, net.marking(), "view shares the places");
+    }
+
+    #[test]
+    fn test_owned_petrinet_clone() {
+        let mut net = build_net();
+        net.set_priority(0, 2);
+        let mut branch = net.clone();
+        assert_eq!(net, branch);
+
+        branch.fire(0).expect("transition is enabled");
+        assert_eq!(vec![1, 2, 0], branch.marking());
+        assert_eq!(vec![2, 0, 0], net.marking(), "clone has its own places");
+        assert_eq!(Some("t1"), branch.transitions()[0].name());
+        assert_eq!(2, branch.transitions()[0].priority());
+    }
+
+    #[test]
+    fn test_owned_petrinet_priority() {
+        let mut net = build_net();
+        net.set_priority(1, 3);
+        assert_eq!(3, net.transitions()[1].priority());
+        assert_eq!(0, net.transitions()[0].priority());
+
+        net.with_net(|view| {
+            assert!(view.step_priority());
+            assert_eq!(vec![1, 2, 0], view.marking());
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_owned_petrinet_serde() {
+        let mut net = build_net();
+        net.set_priority(0, 2);
+        net.fire(0).expect("transition is enabled");
+
+        let json = serde_json::to_string(&net).expect("serializable");
+        let deserialized: OwnedPetrinet = serde_json::from_str(&json).expect("valid JSON");
+        assert_eq!(net, deserialized);
+
+        let json = r#"{
+            "places": [{"tokens": 1, "name": "p"}],
+            "transitions": [{"name": null, "inputs": [{"place": 0, "weight": 1}], "outputs": []}]
+        }"#;
+        let net: OwnedPetrinet = serde_json::from_str(json).expect("valid JSON");
+        assert_eq!(None, net.places()[0].capacity());
+        assert_eq!(0, net.transitions()[0].priority());
+
+        let json = r#"{
+            "places": [{"tokens": 1, "name": "p"}],
+            "transitions": [{"name": "t", "inputs": [{"place": 1, "weight": 1}], "outputs": []}]
+        }"#;
+        let error = serde_json::from_str::<OwnedPetrinet>(json).unwrap_err();
+        assert!(error.to_string().contains("no such place 1"), "{}", error);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_owned_petrinet_capacity() {
+        let json = r#"{
+            "places": [{"tokens": 0, "name": "p", "capacity": 1}],
+            "transitions": [{"name": "source", "inputs": [], "outputs": [{"place": 0, "weight": 1}]}]
+        }"#;
+        let mut net: OwnedPetrinet = serde_json::from_str(json).expect("valid JSON");
+
+        assert_eq!(Ok(()), net.fire(0));
+        assert!(!net.is_enabled(0), "p is full");
+        assert_eq!(Err(FireError::NotEnabled), net.fire(0));
+        assert_eq!(vec![1], net.marking());
+
+        // a self-loop on the full place leaves it within its capacity
+        let q = net.add_place(0, "q");
+        let t = net.add_transition(None);
+        net.add_input(t, 0, 1).expect("valid arc");
+        net.add_output(t, 0, 1).expect("valid arc");
+        net.add_output(t, q, 1).expect("valid arc");
+        assert_eq!(Ok(()), net.fire(t));
+        assert_eq!(vec![1, 1], net.marking());
+    }
+}
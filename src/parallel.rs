@@ -0,0 +1,17 @@
+//! Successor markings computed on several threads.
+
+use rayon::prelude::*;
+
+use crate::rules::Rules;
+use crate::Marking;
+
+impl Rules {
+    /// The successors of every marking of `frontier`, see `successors_of`.
+    /// The markings are processed in parallel.
+    pub(crate) fn successors(&self, frontier: &[Marking]) -> Vec<Vec<(usize, Marking)>> {
+        frontier
+            .par_iter()
+            .map(|marking| self.successors_of(marking))
+            .collect()
+    }
+}
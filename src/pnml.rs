@@ -0,0 +1,359 @@
+//! Import and export of the Petri Net Markup Language (PNML).
+//!
+//! Only the core elements of place/transition nets are supported: places
+//! with their initial marking, transitions, and arcs with their inscription.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+use std::result;
+
+use crate::{OwnedPetrinet, Petrinet};
+
+/// Error returned when a PNML document cannot be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PnmlError {
+    /// The document is not well-formed XML.
+    Xml(String),
+    /// The document contains no `net` element.
+    MissingNet,
+    /// A place, transition or arc has no `id` attribute.
+    MissingId,
+    /// An arc has no `source` or `target` attribute.
+    MissingEndpoint(String),
+    /// An arc refers to a node which does not exist.
+    UnknownNode(String),
+    /// An arc connects two places or two transitions.
+    InvalidArc(String),
+    /// An initial marking or inscription is not a valid number.
+    InvalidNumber(String),
+    /// The inscription of the arc with this id is 0.
+    ZeroWeight(String),
+}
+
+impl fmt::Display for PnmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(message) => write!(f, "malformed XML: {}", message),
+            Self::MissingNet => write!(f, "document contains no net"),
+            Self::MissingId => write!(f, "node without id"),
+            Self::MissingEndpoint(id) => write!(f, "arc {} lacks source or target", id),
+            Self::UnknownNode(id) => write!(f, "unknown node {}", id),
+            Self::InvalidArc(id) => write!(f, "arc {} must connect a place and a transition", id),
+            Self::InvalidNumber(text) => write!(f, "invalid number {:?}", text),
+            Self::ZeroWeight(id) => write!(f, "arc {} has weight 0", id),
+        }
+    }
+}
+
+impl error::Error for PnmlError {}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Text content of the `<text>` element below the child element `name`.
+fn label<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name(name))?
+        .children()
+        .find(|child| child.has_tag_name("text"))?
+        .text()
+        .map(str::trim)
+}
+
+fn number(
+    node: roxmltree::Node<'_, '_>,
+    name: &str,
+    default: u32,
+) -> result::Result<u32, PnmlError> {
+    match label(node, name) {
+        Some(text) => text
+            .parse()
+            .map_err(|_| PnmlError::InvalidNumber(text.to_string())),
+        None => Ok(default),
+    }
+}
+
+impl Petrinet<'_> {
+    /// Render the net as PNML document of a place/transition net.
+    ///
+    /// A read arc or self-loop is exported as an input and an output arc of
+    /// the same weight. Inhibitor, reset, zero-test, variable, sampled and overflow
+    /// arcs have no counterpart in core PNML and are not exported.
+    pub fn to_pnml(&self) -> String {
+        let mut pnml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<pnml xmlns=\"http://www.pnml.org/version-2009/grammar/pnml\">\n",
+            "  <net id=\"net\" type=\"http://www.pnml.org/version-2009/grammar/ptnet\">\n",
+            "    <page id=\"page\">\n",
+        ));
+
+        for (i, place) in self.places.iter().enumerate() {
+            writeln!(pnml, "      <place id=\"p{}\">", i).unwrap();
+            writeln!(
+                pnml,
+                "        <name><text>{}</text></name>",
+                escape(place.name())
+            )
+            .unwrap();
+            writeln!(
+                pnml,
+                "        <initialMarking><text>{}</text></initialMarking>",
+                place.tokens()
+            )
+            .unwrap();
+            pnml.push_str("      </place>\n");
+        }
+
+        for (i, transition) in self.transitions.iter().enumerate() {
+            match transition.name() {
+                Some(name) => {
+                    writeln!(pnml, "      <transition id=\"t{}\">", i).unwrap();
+                    writeln!(pnml, "        <name><text>{}</text></name>", escape(name)).unwrap();
+                    pnml.push_str("      </transition>\n");
+                }
+                None => writeln!(pnml, "      <transition id=\"t{}\"/>", i).unwrap(),
+            }
+        }
+
+        let mut arc_id = 0;
+        for (i, transition) in self.transitions.iter().enumerate() {
+            let reads = transition
+                .read_arcs
+                .iter()
+                .map(|arc| (arc.place, arc.weight));
+            let inputs = transition
+                .weighted_inputs()
+                .chain(reads.clone())
+                .map(|(place, weight)| (place, weight, true));
+            let outputs = transition
+                .weighted_outputs()
+                .chain(reads)
+                .map(|(place, weight)| (place, weight, false));
+            for (place, weight, is_input) in inputs.chain(outputs) {
+                let p = self.place_index(place).expect("registered place");
+                let (source, target) = if is_input {
+                    (format!("p{}", p), format!("t{}", i))
+                } else {
+                    (format!("t{}", i), format!("p{}", p))
+                };
+                writeln!(
+                    pnml,
+                    "      <arc id=\"a{}\" source=\"{}\" target=\"{}\">",
+                    arc_id, source, target
+                )
+                .unwrap();
+                writeln!(
+                    pnml,
+                    "        <inscription><text>{}</text></inscription>",
+                    weight
+                )
+                .unwrap();
+                pnml.push_str("      </arc>\n");
+                arc_id += 1;
+            }
+        }
+
+        pnml.push_str("    </page>\n  </net>\n</pnml>\n");
+        pnml
+    }
+
+    /// Import the first net of a PNML document.
+    ///
+    /// Places, transitions and arcs may be spread over several pages. A
+    /// missing initial marking means no tokens, a missing inscription means
+    /// weight 1.
+    pub fn from_pnml(xml: &str) -> result::Result<OwnedPetrinet, PnmlError> {
+        enum Node {
+            Place(usize),
+            Transition(usize),
+        }
+
+        let document =
+            roxmltree::Document::parse(xml).map_err(|e| PnmlError::Xml(e.to_string()))?;
+        let net_node = document
+            .descendants()
+            .find(|node| node.has_tag_name("net"))
+            .ok_or(PnmlError::MissingNet)?;
+
+        let mut net = OwnedPetrinet::new();
+        let mut nodes = HashMap::new();
+
+        for node in net_node.descendants() {
+            if node.has_tag_name("place") {
+                let id = node.attribute("id").ok_or(PnmlError::MissingId)?;
+                let tokens = number(node, "initialMarking", 0)?;
+                let place = net.add_place(tokens, label(node, "name").unwrap_or(id));
+                nodes.insert(id, Node::Place(place));
+            } else if node.has_tag_name("transition") {
+                let id = node.attribute("id").ok_or(PnmlError::MissingId)?;
+                let transition = net.add_transition(label(node, "name"));
+                nodes.insert(id, Node::Transition(transition));
+            }
+        }
+
+        for node in net_node
+            .descendants()
+            .filter(|node| node.has_tag_name("arc"))
+        {
+            let id = node.attribute("id").ok_or(PnmlError::MissingId)?;
+            let endpoint = |name| {
+                let endpoint = node
+                    .attribute(name)
+                    .ok_or_else(|| PnmlError::MissingEndpoint(id.to_string()))?;
+                nodes
+                    .get(endpoint)
+                    .ok_or_else(|| PnmlError::UnknownNode(endpoint.to_string()))
+            };
+            let source = endpoint("source")?;
+            let target = endpoint("target")?;
+            let weight = number(node, "inscription", 1)?;
+
+            let added = match (source, target) {
+                (Node::Place(p), Node::Transition(t)) => net.add_input(*t, *p, weight),
+                (Node::Transition(t), Node::Place(p)) => net.add_output(*t, *p, weight),
+                _ => return Err(PnmlError::InvalidArc(id.to_string())),
+            };
+            // the endpoints exist, so only the weight can be invalid
+            added.map_err(|_| PnmlError::ZeroWeight(id.to_string()))?;
+        }
+
+        Ok(net)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Arc, Place, Transition};
+
+    #[test]
+    fn test_pnml_round_trip() {
+        let p1 = Place::new(2, "P1");
+        let p2 = Place::new(0, "P<2>");
+
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::with_name("t1");
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        let mut t3 = Transition::new();
+        t3.add_read(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        let imported = Petrinet::from_pnml(&net.to_pnml()).expect("valid PNML");
+
+        let places = imported.places();
+        assert_eq!(2, places.len());
+        assert_eq!(("P1", 2), (places[0].name(), places[0].tokens()));
+        assert_eq!(("P<2>", 0), (places[1].name(), places[1].tokens()));
+
+        let transitions = imported.transitions();
+        assert_eq!(3, transitions.len());
+        assert_eq!(Some("t1"), transitions[0].name());
+        assert_eq!(None, transitions[1].name());
+        assert_eq!(1, transitions[0].inputs().len());
+        assert_eq!(0, transitions[0].inputs()[0].place());
+        assert_eq!(2, transitions[0].inputs()[0].weight());
+        assert_eq!(1, transitions[0].outputs()[0].place());
+        assert_eq!(1, transitions[0].outputs()[0].weight());
+        assert_eq!(1, transitions[1].inputs()[0].place());
+        assert!(transitions[1].outputs().is_empty());
+        assert_eq!(
+            transitions[2].inputs(),
+            transitions[2].outputs(),
+            "read arc"
+        );
+        assert_eq!(2, transitions[2].inputs()[0].weight());
+    }
+
+    #[test]
+    fn test_from_pnml_defaults() {
+        let xml = r#"<pnml>
+              <net id="n" type="http://www.pnml.org/version-2009/grammar/ptnet">
+                <page id="page1">
+                  <place id="a"/>
+                  <transition id="t"/>
+                </page>
+                <page id="page2">
+                  <place id="b">
+                    <initialMarking><text> 3 </text></initialMarking>
+                  </place>
+                  <arc id="x" source="b" target="t"/>
+                  <arc id="y" source="t" target="a"/>
+                </page>
+              </net>
+            </pnml>"#;
+
+        let net = Petrinet::from_pnml(xml).expect("valid PNML");
+        assert_eq!("a", net.places()[0].name(), "id is the fallback name");
+        assert_eq!(0, net.places()[0].tokens());
+        assert_eq!(3, net.places()[1].tokens());
+        assert_eq!(1, net.transitions()[0].inputs()[0].weight());
+        assert_eq!(1, net.transitions()[0].inputs()[0].place());
+        assert_eq!(0, net.transitions()[0].outputs()[0].place());
+    }
+
+    #[test]
+    fn test_from_pnml_errors() {
+        assert!(matches!(
+            Petrinet::from_pnml("<pnml>"),
+            Err(PnmlError::Xml(_))
+        ));
+        assert_eq!(
+            Err(PnmlError::MissingNet),
+            Petrinet::from_pnml("<pnml/>").map(|_| ())
+        );
+        assert_eq!(
+            Err(PnmlError::InvalidArc("x".to_string())),
+            Petrinet::from_pnml(
+                r#"<pnml><net><place id="a"/><place id="b"/>
+                   <arc id="x" source="a" target="b"/></net></pnml>"#
+            )
+            .map(|_| ())
+        );
+        assert_eq!(
+            Err(PnmlError::UnknownNode("c".to_string())),
+            Petrinet::from_pnml(
+                r#"<pnml><net><place id="a"/>
+                   <arc id="x" source="a" target="c"/></net></pnml>"#
+            )
+            .map(|_| ())
+        );
+        assert_eq!(
+            Err(PnmlError::InvalidNumber("two".to_string())),
+            Petrinet::from_pnml(
+                r#"<pnml><net><place id="a">
+                   <initialMarking><text>two</text></initialMarking>
+                   </place></net></pnml>"#
+            )
+            .map(|_| ())
+        );
+        let zero = Petrinet::from_pnml(
+            r#"<pnml><net><place id="a"/><transition id="t"/>
+               <arc id="x" source="a" target="t">
+               <inscription><text>0</text></inscription></arc></net></pnml>"#,
+        );
+        assert_eq!(
+            Err(PnmlError::ZeroWeight("x".to_string())),
+            zero.map(|_| ())
+        );
+        assert_eq!(
+            "arc x has weight 0",
+            PnmlError::ZeroWeight("x".to_string()).to_string()
+        );
+    }
+}
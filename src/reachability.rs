@@ -0,0 +1,962 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use crate::dot::escape;
+use crate::{BitMarking, Marking, Petrinet};
+
+/// The graph of markings reachable from an initial marking.
+///
+/// States are numbered in the order they are discovered by a breadth-first
+/// search, so state 0 is the initial marking.
+#[derive(Debug, Clone, Default)]
+pub struct ReachabilityGraph {
+    markings: Vec<Marking>,
+    indices: HashMap<Marking, usize>,
+    edges: Vec<Vec<(usize, usize)>>,
+    transition_names: Vec<Option<String>>,
+    complete: bool,
+}
+
+impl ReachabilityGraph {
+    /// Explore the markings reachable from `initial` by a breadth-first
+    /// search, stopping at `max_states` distinct markings. `successors`
+    /// returns the successors of a marking as pairs of fired transition and
+    /// successor marking.
+    pub(crate) fn explore(
+        initial: Marking,
+        transition_names: Vec<Option<String>>,
+        max_states: usize,
+        mut successors: impl FnMut(&Marking) -> Vec<(usize, Marking)>,
+    ) -> Self {
+        let mut graph = Self {
+            transition_names,
+            complete: true,
+            ..Self::default()
+        };
+
+        if max_states == 0 {
+            graph.complete = false;
+            return graph;
+        }
+
+        let mut queue = VecDeque::new();
+        queue.push_back(graph.add_state(initial));
+
+        while let Some(state) = queue.pop_front() {
+            let marking = graph.markings[state].clone();
+            for (transition, successor) in successors(&marking) {
+                let successor = match graph.state(&successor) {
+                    Some(successor) => successor,
+                    None if graph.state_count() < max_states => {
+                        let successor = graph.add_state(successor);
+                        queue.push_back(successor);
+                        successor
+                    }
+                    None => {
+                        graph.complete = false;
+                        continue;
+                    }
+                };
+                graph.edges[state].push((transition, successor));
+            }
+        }
+
+        graph
+    }
+
+    fn add_state(&mut self, marking: Marking) -> usize {
+        let state = self.markings.len();
+        self.indices.insert(marking.clone(), state);
+        self.markings.push(marking);
+        self.edges.push(Vec::new());
+        state
+    }
+
+    /// Number of distinct reachable markings found.
+    pub fn state_count(&self) -> usize {
+        self.markings.len()
+    }
+
+    /// The marking of every state, indexed by state.
+    pub fn markings(&self) -> &[Marking] {
+        &self.markings
+    }
+
+    /// State of `marking`, if the marking was found.
+    pub fn state(&self, marking: &[u32]) -> Option<usize> {
+        self.indices.get(marking).copied()
+    }
+
+    /// Whether `marking` was found to be reachable.
+    pub fn is_reachable(&self, marking: &[u32]) -> bool {
+        self.indices.contains_key(marking)
+    }
+
+    /// The outgoing edges of `state` as pairs of fired transition and
+    /// successor state.
+    pub fn edges(&self, state: usize) -> &[(usize, usize)] {
+        &self.edges[state]
+    }
+
+    /// Whether the whole state space was explored. If not, the search was
+    /// truncated and edges to undiscovered markings are missing.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// For every state, whether one of `targets` can be reached from it. The
+    /// graph is searched backwards from the targets.
+    pub(crate) fn states_reaching(&self, targets: impl IntoIterator<Item = usize>) -> Vec<bool> {
+        let mut predecessors = vec![Vec::new(); self.state_count()];
+        for state in 0..self.state_count() {
+            for &(_, successor) in self.edges(state) {
+                predecessors[successor].push(state);
+            }
+        }
+
+        let mut reaches = vec![false; self.state_count()];
+        let mut stack: Vec<usize> = targets.into_iter().collect();
+        for &state in &stack {
+            reaches[state] = true;
+        }
+
+        while let Some(state) = stack.pop() {
+            for &predecessor in &predecessors[state] {
+                if !reaches[predecessor] {
+                    reaches[predecessor] = true;
+                    stack.push(predecessor);
+                }
+            }
+        }
+
+        reaches
+    }
+
+    /// For every state, whether it can be reached from `state`.
+    fn states_reachable_from(&self, state: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.state_count()];
+        reachable[state] = true;
+        let mut stack = vec![state];
+        while let Some(state) = stack.pop() {
+            for &(_, successor) in self.edges(state) {
+                if !reachable[successor] {
+                    reachable[successor] = true;
+                    stack.push(successor);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// The states reachable from every state, in ascending order. They form
+    /// the only terminal strongly connected component, if there is just one.
+    fn home_states(&self) -> Vec<usize> {
+        if self.state_count() == 0 {
+            return Vec::new();
+        }
+
+        // descend from the initial state into a terminal component, picking
+        // a state which can't get back whenever there is one
+        let mut candidate = 0;
+        let reachable = loop {
+            let reaching = self.states_reaching([candidate]);
+            let reachable = self.states_reachable_from(candidate);
+            match (0..self.state_count()).find(|&s| reachable[s] && !reaching[s]) {
+                Some(state) => candidate = state,
+                None => break reachable,
+            }
+        };
+
+        if !self.states_reaching([candidate]).iter().all(|&r| r) {
+            return Vec::new();
+        }
+        (0..self.state_count()).filter(|&s| reachable[s]).collect()
+    }
+
+    /// Render the graph in the Graphviz DOT format.
+    ///
+    /// States are labeled with the token counts of their marking, edges with
+    /// the name of the fired transition (or its index, if it is unnamed).
+    /// States without outgoing edges, the deadlocks, are filled red. If the
+    /// graph is not complete, states may also lack edges because their
+    /// successors were not discovered.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph reachability {\n");
+
+        for (state, marking) in self.markings.iter().enumerate() {
+            let tokens: Vec<String> = marking.iter().map(u32::to_string).collect();
+            let deadlock = if self.edges[state].is_empty() {
+                ", style=filled, fillcolor=red"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "    s{} [label=\"({})\"{}];",
+                state,
+                tokens.join(", "),
+                deadlock
+            )
+            .unwrap();
+        }
+
+        for (state, edges) in self.edges.iter().enumerate() {
+            for &(transition, successor) in edges {
+                let label = match &self.transition_names[transition] {
+                    Some(name) => escape(name),
+                    None => format!("t{}", transition),
+                };
+                writeln!(
+                    dot,
+                    "    s{} -> s{} [label=\"{}\"];",
+                    state, successor, label
+                )
+                .unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Petrinet<'_> {
+    fn transition_names(&self) -> Vec<Option<String>> {
+        self.transitions
+            .iter()
+            .map(|transition| transition.name.clone())
+            .collect()
+    }
+
+    /// An empty, complete graph for the transitions of the net.
+    #[cfg(feature = "parallel")]
+    fn empty_graph(&self) -> ReachabilityGraph {
+        ReachabilityGraph {
+            transition_names: self.transition_names(),
+            complete: true,
+            ..ReachabilityGraph::default()
+        }
+    }
+
+    /// Explore the markings reachable from the current marking by a
+    /// breadth-first search, stopping at `max_states` distinct markings.
+    ///
+    /// The marking of the net is restored afterwards.
+    pub fn reachability_graph(&mut self, max_states: usize) -> ReachabilityGraph {
+        let initial = self.snapshot();
+        let names = self.transition_names();
+        let graph = ReachabilityGraph::explore(initial.clone(), names, max_states, |marking| {
+            let mut successors = Vec::new();
+            for transition in 0..self.transitions.len() {
+                self.restore(marking);
+                if self.transitions[transition]
+                    .fire_tokens(self.token_policy)
+                    .is_ok()
+                {
+                    successors.push((transition, self.marking()));
+                }
+            }
+            successors
+        });
+
+        self.restore(&initial);
+        graph
+    }
+
+    /// The markings reachable from the current marking of a safe net, with
+    /// one bit per place, in breadth-first order, but at most `max_states`
+    /// of them. Unless `max_states` markings are returned, these are all
+    /// reachable markings.
+    ///
+    /// Returns `None` as soon as a marking is found in which a place holds
+    /// more than one token, so the net is not safe, and the general
+    /// `reachability_graph` has to be used instead.
+    ///
+    /// The marking of the net is restored afterwards.
+    pub fn safe_reachable_markings(&mut self, max_states: usize) -> Option<Vec<BitMarking>> {
+        let initial = self.snapshot();
+        let mut markings = Vec::new();
+        let mut seen = HashSet::new();
+        if max_states > 0 {
+            let marking = BitMarking::from_marking(&initial)?;
+            seen.insert(marking.clone());
+            markings.push(marking);
+        }
+
+        let mut next = 0;
+        let mut safe = true;
+        'search: while next < markings.len() {
+            let marking = markings[next].to_marking();
+            next += 1;
+            for transition in 0..self.transitions.len() {
+                self.restore(&marking);
+                if self.transitions[transition]
+                    .fire_tokens(self.token_policy)
+                    .is_err()
+                {
+                    continue;
+                }
+
+                let successor = match BitMarking::from_marking(&self.marking()) {
+                    Some(successor) => successor,
+                    None => {
+                        safe = false;
+                        break 'search;
+                    }
+                };
+                if markings.len() < max_states && seen.insert(successor.clone()) {
+                    markings.push(successor);
+                }
+            }
+        }
+
+        self.restore(&initial);
+        if safe {
+            Some(markings)
+        } else {
+            None
+        }
+    }
+
+    /// Like `reachability_graph`, but the successors of all markings at the
+    /// same distance from the initial marking are computed in parallel. The
+    /// new markings are then merged in the order of the sequential search, so
+    /// the result is the same, including the numbering of the states.
+    ///
+    /// Expressions, guards and the weights of variable arcs can't be shared
+    /// between threads, and sampled and overflow arcs aren't supported
+    /// either. If any transition has one, the graph is built sequentially.
+    #[cfg(feature = "parallel")]
+    pub fn reachability_graph_parallel(&mut self, max_states: usize) -> ReachabilityGraph {
+        let rules = match crate::rules::Rules::new(self) {
+            Some(rules) => rules,
+            None => return self.reachability_graph(max_states),
+        };
+
+        let mut graph = self.empty_graph();
+
+        if max_states == 0 {
+            graph.complete = false;
+            return graph;
+        }
+
+        let mut frontier = vec![graph.add_state(self.snapshot())];
+        while !frontier.is_empty() {
+            let markings: Vec<Marking> = frontier
+                .iter()
+                .map(|&state| graph.markings[state].clone())
+                .collect();
+            let successors = rules.successors(&markings);
+
+            let mut next = Vec::new();
+            for (&state, successors) in frontier.iter().zip(successors) {
+                for (transition, successor) in successors {
+                    let successor = match graph.state(&successor) {
+                        Some(successor) => successor,
+                        None if graph.state_count() < max_states => {
+                            let successor = graph.add_state(successor);
+                            next.push(successor);
+                            successor
+                        }
+                        None => {
+                            graph.complete = false;
+                            continue;
+                        }
+                    };
+                    graph.edges[state].push((transition, successor));
+                }
+            }
+            frontier = next;
+        }
+
+        graph
+    }
+
+    /// For every state of `graph`, which transitions are enabled in its
+    /// marking. The marking of the net is restored afterwards.
+    pub(crate) fn enabled_in_states(&mut self, graph: &ReachabilityGraph) -> Vec<Vec<bool>> {
+        let initial = self.snapshot();
+        let enabled = graph
+            .markings()
+            .iter()
+            .map(|marking| {
+                self.restore(marking);
+                self.transitions.iter().map(|t| t.is_enabled()).collect()
+            })
+            .collect();
+        self.restore(&initial);
+        enabled
+    }
+
+    /// Indices of the transitions which are not enabled in any marking
+    /// reachable from the current marking, exploring at most `max_states`
+    /// distinct markings.
+    ///
+    /// If the state space is truncated, a reported transition may still be
+    /// enabled in an unexplored marking. The marking of the net is restored
+    /// afterwards.
+    pub fn dead_transitions(&mut self, max_states: usize) -> Vec<usize> {
+        let graph = self.reachability_graph(max_states);
+        let enabled = self.enabled_in_states(&graph);
+
+        (0..self.transitions.len())
+            .filter(|&t| !enabled.iter().any(|state| state[t]))
+            .collect()
+    }
+
+    /// Liveness of every transition, indexed by transition. A transition is
+    /// live if from every reachable marking some marking is reachable in
+    /// which the transition is enabled.
+    ///
+    /// The reachability graph is built with at most `max_states` states, and
+    /// then searched backwards from the states enabling each transition, so
+    /// the cost is `O(T * (S + E))` for `T` transitions, `S` states and `E`
+    /// edges on top of building the graph.
+    ///
+    /// Only the explored part of the state space is considered. If it was
+    /// truncated, paths through unexplored markings are missed and the result
+    /// is only an approximation. The marking of the net is restored
+    /// afterwards.
+    pub fn live_transitions(&mut self, max_states: usize) -> Vec<bool> {
+        let graph = self.reachability_graph(max_states);
+        let enabled = self.enabled_in_states(&graph);
+
+        (0..self.transitions.len())
+            .map(|t| {
+                let reaches =
+                    graph.states_reaching((0..graph.state_count()).filter(|&s| enabled[s][t]));
+                graph.state_count() > 0 && reaches.iter().all(|&r| r)
+            })
+            .collect()
+    }
+
+    /// The home markings, which are reachable from every marking reachable
+    /// from the current marking, exploring at most `max_states` distinct
+    /// markings. They are the markings of the only terminal strongly
+    /// connected component of the reachability graph, so the result is empty
+    /// if there are several, e.g. two different deadlocks.
+    ///
+    /// Finding the component takes `O(S * (S + E))` for `S` states and `E`
+    /// edges in the worst case, on top of building the graph. As with
+    /// `live_transitions`, a truncated state space only gives an
+    /// approximation. The marking of the net is restored afterwards.
+    pub fn home_markings(&mut self, max_states: usize) -> Vec<Marking> {
+        let graph = self.reachability_graph(max_states);
+        graph
+            .home_states()
+            .into_iter()
+            .map(|state| graph.markings()[state].clone())
+            .collect()
+    }
+
+    /// Whether the net is reversible, i.e. the current marking is a home
+    /// marking and can be reached again from every reachable marking, see
+    /// `home_markings`.
+    pub fn is_reversible(&mut self, max_states: usize) -> bool {
+        let graph = self.reachability_graph(max_states);
+        graph.home_states().first() == Some(&0)
+    }
+
+    /// Whether `target` is reachable from the current marking, exploring at
+    /// most `max_states` distinct markings.
+    ///
+    /// The marking of the net is restored afterwards.
+    pub fn is_reachable(&mut self, target: &[u32], max_states: usize) -> bool {
+        let target: Vec<_> = target.iter().copied().map(Some).collect();
+        self.is_partially_reachable(&target, max_states)
+    }
+
+    /// Like `is_reachable`, but places with target `None` may hold any number
+    /// of tokens.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` doesn't hold an entry for every place.
+    pub fn is_partially_reachable(&mut self, target: &[Option<u32>], max_states: usize) -> bool {
+        assert_eq!(
+            self.places.len(),
+            target.len(),
+            "target must hold an entry for every place"
+        );
+
+        let matches = |marking: &[u32]| {
+            marking
+                .iter()
+                .zip(target)
+                .all(|(&tokens, target)| target.is_none() || *target == Some(tokens))
+        };
+
+        let initial = self.snapshot();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        let mut found = false;
+
+        if max_states > 0 {
+            visited.insert(initial.clone());
+            queue.push_back(initial.clone());
+        }
+
+        while let Some(marking) = queue.pop_front() {
+            if matches(&marking) {
+                found = true;
+                break;
+            }
+
+            for transition in 0..self.transitions.len() {
+                self.restore(&marking);
+                if self.transitions[transition]
+                    .fire_tokens(self.token_policy)
+                    .is_ok()
+                {
+                    let successor = self.marking();
+                    if visited.len() < max_states && !visited.contains(&successor) {
+                        visited.insert(successor.clone());
+                        queue.push_back(successor);
+                    }
+                }
+            }
+        }
+
+        self.restore(&initial);
+        found
+    }
+
+    /// The indices of the transitions to fire, in order, to get from the
+    /// current marking to `target` with as few firings as possible, or `None`
+    /// if `target` is not found within `max_states` distinct markings. The
+    /// sequence is empty if the net is already in `target`.
+    ///
+    /// The marking of the net is restored afterwards.
+    pub fn shortest_firing_sequence(
+        &mut self,
+        target: &[u32],
+        max_states: usize,
+    ) -> Option<Vec<usize>> {
+        let graph = self.reachability_graph(max_states);
+        let goal = graph.state(target)?;
+
+        // breadth-first search from the initial state, remembering the edge
+        // every state was first reached by
+        let mut parents: Vec<Option<(usize, usize)>> = vec![None; graph.state_count()];
+        let mut queue = VecDeque::from(vec![0]);
+        while let Some(state) = queue.pop_front() {
+            if state == goal {
+                break;
+            }
+
+            for &(transition, successor) in graph.edges(state) {
+                if successor != 0 && parents[successor].is_none() {
+                    parents[successor] = Some((state, transition));
+                    queue.push_back(successor);
+                }
+            }
+        }
+
+        let mut sequence = Vec::new();
+        let mut state = goal;
+        while let Some((parent, transition)) = parents[state] {
+            sequence.push(transition);
+            state = parent;
+        }
+        sequence.reverse();
+        Some(sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, BitMarking, Marking, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_reachability_graph() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // the token either moves from p1 to p2 or to p3, and back from p2
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a2);
+        t3.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        let graph = net.reachability_graph(100);
+        assert!(graph.is_complete());
+        assert_eq!(3, graph.state_count());
+        assert_eq!(
+            &[vec![1, 0, 0], vec![0, 1, 0], vec![0, 0, 1]],
+            graph.markings()
+        );
+        assert_eq!(&[(0, 1), (1, 2)], graph.edges(0));
+        assert_eq!(&[(2, 0)], graph.edges(1));
+        assert!(graph.edges(2).is_empty());
+
+        assert!(graph.is_reachable(&[0, 0, 1]));
+        assert!(!graph.is_reachable(&[1, 1, 0]));
+        assert_eq!(Some(1), graph.state(&[0, 1, 0]));
+        assert_eq!(vec![1, 0, 0], net.marking(), "marking is restored");
+    }
+
+    #[test]
+    fn test_reachability_graph_to_dot() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::with_name("go");
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::with_name("\"stop\"");
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a2);
+        t3.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        let expected = concat!(
+            "digraph reachability {\n",
+            "    s0 [label=\"(1, 0, 0)\"];\n",
+            "    s1 [label=\"(0, 1, 0)\"];\n",
+            "    s2 [label=\"(0, 0, 1)\", style=filled, fillcolor=red];\n",
+            "    s0 -> s1 [label=\"go\"];\n",
+            "    s0 -> s2 [label=\"\\\"stop\\\"\"];\n",
+            "    s1 -> s0 [label=\"t2\"];\n",
+            "}\n"
+        );
+        assert_eq!(expected, net.reachability_graph(100).to_dot());
+    }
+
+    #[test]
+    fn test_reachability_graph_truncated() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // unbounded: t produces a token on p2 whenever it fires
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a1);
+        t1.add_output(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+
+        let graph = net.reachability_graph(5);
+        assert!(!graph.is_complete());
+        assert_eq!(5, graph.state_count());
+        assert!(graph.is_reachable(&[1, 4]));
+        assert!(!graph.is_reachable(&[1, 5]));
+        assert!(graph.edges(4).is_empty());
+
+        assert_eq!(0, net.reachability_graph(0).state_count());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_reachability_graph_parallel() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::with_capacity(0, "p2", 40);
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // p1 keeps its token while filling p2 up to its capacity, and tokens
+        // move on from p2 to p3 at any time
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a1);
+        t3.add_inhibitor(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        for &max_states in &[0, 1, 100, 100_000] {
+            let sequential = net.reachability_graph(max_states);
+            let parallel = net.reachability_graph_parallel(max_states);
+            assert_eq!(sequential.markings(), parallel.markings());
+            assert_eq!(sequential.is_complete(), parallel.is_complete());
+            for state in 0..sequential.state_count() {
+                assert_eq!(sequential.edges(state), parallel.edges(state));
+            }
+        }
+        assert!(net.reachability_graph_parallel(100_000).state_count() > 1000);
+        assert_eq!(vec![3, 0, 0], net.marking(), "marking is untouched");
+    }
+
+    #[test]
+    fn test_dead_transitions() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+        let a4 = Arc::new(&p2, 2).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        // p2 never holds two tokens
+        let mut t3 = Transition::new();
+        t3.add_input(&a4);
+        t3.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        assert_eq!(vec![2], net.dead_transitions(100));
+        assert_eq!(vec![1, 2], net.dead_transitions(1), "truncated search");
+        assert_eq!(vec![1, 0, 0], net.marking(), "marking is restored");
+    }
+
+    #[test]
+    fn test_live_transitions() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // t1 and t2 form a cycle, t3 leaves it for good
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+        let mut t3 = Transition::new();
+        t3.add_input(&a2);
+        t3.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+        assert_eq!(vec![false, false, false], net.live_transitions(100));
+
+        let mut cycle = Petrinet::new();
+        cycle.add_transition(net.transitions.remove(0));
+        cycle.add_transition(net.transitions.remove(0));
+        assert_eq!(vec![true, true], cycle.live_transitions(100));
+        assert_eq!(vec![1, 0], cycle.marking(), "marking is restored");
+    }
+
+    #[test]
+    fn test_home_markings() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // t1 leaves p1 for the cycle of t2 and t3 between p2 and p3
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a3);
+        t3.add_output(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+        assert_eq!(vec![vec![0, 1, 0], vec![0, 0, 1]], net.home_markings(100));
+        assert!(!net.is_reversible(100));
+        assert_eq!(vec![1, 0, 0], net.marking(), "marking is restored");
+
+        net.fire_transition(0).expect("t1 is enabled");
+        assert!(net.is_reversible(100));
+
+        // a choice between two deadlocks has no home marking
+        let q1 = Place::new(1, "q1");
+        let q2 = Place::new(0, "q2");
+        let q3 = Place::new(0, "q3");
+        let b1 = Arc::new(&q1, 1).expect("weight greater than zero");
+        let b2 = Arc::new(&q2, 1).expect("weight greater than zero");
+        let b3 = Arc::new(&q3, 1).expect("weight greater than zero");
+        let mut left = Transition::new();
+        left.add_input(&b1);
+        left.add_output(&b2);
+        let mut right = Transition::new();
+        right.add_input(&b1);
+        right.add_output(&b3);
+        let mut choice = Petrinet::new();
+        choice.add_transition(left);
+        choice.add_transition(right);
+        assert!(choice.home_markings(100).is_empty());
+        assert!(!choice.is_reversible(100));
+        assert!(choice.home_markings(0).is_empty());
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 2).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        assert!(net.is_reachable(&[2, 0, 0], 100));
+        assert!(net.is_reachable(&[0, 1, 2], 100));
+        assert!(net.is_reachable(&[0, 0, 4], 100));
+        assert!(!net.is_reachable(&[0, 0, 3], 100));
+        assert!(!net.is_reachable(&[0, 0, 4], 3), "too few states explored");
+
+        assert!(net.is_partially_reachable(&[None, Some(2), None], 100));
+        assert!(!net.is_partially_reachable(&[None, Some(3), None], 100));
+        assert!(net.is_partially_reachable(&[Some(0), None, Some(4)], 100));
+
+        assert_eq!(vec![2, 0, 0], net.marking(), "marking is restored");
+    }
+
+    #[test]
+    fn test_shortest_firing_sequence() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // p1 -> p2 -> p3 takes two firings, the shortcut t3 only one
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a1);
+        t3.add_output(&a3);
+        let mut back = Transition::new();
+        back.add_input(&a3);
+        back.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(back);
+        assert_eq!(
+            Some(vec![0, 1]),
+            net.shortest_firing_sequence(&[0, 0, 1], 100)
+        );
+        assert_eq!(
+            Some(Vec::new()),
+            net.shortest_firing_sequence(&[1, 0, 0], 100)
+        );
+        assert_eq!(None, net.shortest_firing_sequence(&[1, 1, 0], 100));
+        assert_eq!(
+            None,
+            net.shortest_firing_sequence(&[0, 0, 1], 2),
+            "truncated"
+        );
+
+        net.add_transition(t3);
+        assert_eq!(Some(vec![3]), net.shortest_firing_sequence(&[0, 0, 1], 100));
+        assert_eq!(vec![1, 0, 0], net.marking(), "marking is restored");
+    }
+
+    #[test]
+    fn test_safe_reachable_markings() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // a single token cycling p1 -> p2 -> p3 -> p1
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a3);
+        t3.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        let markings = net.safe_reachable_markings(100).expect("the net is safe");
+        let markings: Vec<Marking> = markings.iter().map(BitMarking::to_marking).collect();
+        assert_eq!(vec![vec![1, 0, 0], vec![0, 1, 0], vec![0, 0, 1]], markings);
+        assert_eq!(Some(2), net.safe_reachable_markings(2).map(|m| m.len()));
+        assert_eq!(Some(Vec::new()), net.safe_reachable_markings(0));
+
+        // refilling the empty p3 lets a second token reach p1
+        let mut source = Transition::new();
+        source.add_zero_test(&p3);
+        source.add_output(&a3);
+        net.add_transition(source);
+        assert_eq!(None, net.safe_reachable_markings(100));
+        assert_eq!(vec![1, 0, 0], net.marking(), "marking is restored");
+    }
+}
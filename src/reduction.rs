@@ -0,0 +1,281 @@
+//! Structural reduction rules, which shrink a net while preserving liveness,
+//! boundedness and safeness (T. Murata, "Petri nets: Properties, analysis
+//! and applications", 1989).
+
+use std::ptr;
+
+use crate::{Arc, Petrinet, Place, Transition};
+
+/// How a self-loop place is connected to its transition: by a loop added
+/// with `Transition::add_loop`, or by an input and an output arc.
+enum SelfLoop {
+    Loop(usize),
+    Arcs(usize, usize),
+}
+
+/// Whether the transition only has input and output arcs, and neither an
+/// expression nor a guard.
+fn is_plain(transition: &Transition<'_>) -> bool {
+    transition.inhibitor_arcs.is_empty()
+        && transition.read_arcs.is_empty()
+        && transition.loop_arcs.is_empty()
+        && transition.reset_places.is_empty()
+        && transition.zero_test_places.is_empty()
+        && !transition.has_dynamic_arcs()
+        && transition.expression.is_none()
+        && transition.guard.is_none()
+}
+
+impl<'a> Petrinet<'a> {
+    /// Number of arcs of any kind connected to `place`.
+    fn arc_count(&self, place: &Place) -> usize {
+        self.transitions
+            .iter()
+            .map(|transition| transition.places().filter(|p| ptr::eq(*p, place)).count())
+            .sum()
+    }
+
+    /// Remove the place at index `p` from the place registry.
+    fn unregister_place(&mut self, p: usize) {
+        self.places.remove(p);
+        self.initial_marking.remove(p);
+    }
+
+    /// Fusion of series transitions at the place at index `p`. Returns
+    /// whether the rule applied.
+    fn fuse_series_transitions(&mut self, p: usize) -> bool {
+        let place = self.places[p];
+        if place.tokens() != 0 || self.arc_count(place) != 2 {
+            return false;
+        }
+
+        let producer = self
+            .transitions
+            .iter()
+            .position(|t| t.output_arcs.len() == 1 && ptr::eq(t.output_arcs[0].place, place));
+        let consumer = self
+            .transitions
+            .iter()
+            .position(|t| t.input_arcs.len() == 1 && ptr::eq(t.input_arcs[0].place, place));
+        let (t1, t2) = match (producer, consumer) {
+            (Some(t1), Some(t2)) if t1 != t2 => (t1, t2),
+            _ => return false,
+        };
+
+        let (first, second) = (&self.transitions[t1], &self.transitions[t2]);
+        if !is_plain(first)
+            || !is_plain(second)
+            || first.output_arcs[0].weight != 1
+            || second.input_arcs[0].weight != 1
+        {
+            return false;
+        }
+
+        let second = self.transitions.remove(t2);
+        let t1 = if t2 < t1 { t1 - 1 } else { t1 };
+        let fused = &mut self.transitions[t1];
+        fused.output_arcs = second.output_arcs;
+        fused.on_fire = match (fused.on_fire.take(), second.on_fire) {
+            (Some(mut first), Some(mut second)) => Some(Box::new(move || {
+                first();
+                second();
+            })),
+            (first, second) => first.or(second),
+        };
+        self.unregister_place(p);
+        true
+    }
+
+    /// Elimination of the self-loop place at index `p`. Returns whether the
+    /// rule applied.
+    fn eliminate_self_loop_place(&mut self, p: usize) -> bool {
+        let place = self.places[p];
+        let arc_count = self.arc_count(place);
+        let on_place = |arcs: &[&Arc<'_>]| arcs.iter().position(|arc| ptr::eq(arc.place, place));
+        let found = self
+            .transitions
+            .iter()
+            .enumerate()
+            .find_map(|(t, transition)| {
+                let looped = transition
+                    .loop_arcs
+                    .iter()
+                    .position(|&(q, _)| ptr::eq(q, place));
+                match (
+                    looped,
+                    on_place(&transition.input_arcs),
+                    on_place(&transition.output_arcs),
+                ) {
+                    (Some(l), _, _) if arc_count == 1 => {
+                        Some((t, transition.loop_arcs[l].1, SelfLoop::Loop(l)))
+                    }
+                    (None, Some(input), Some(output))
+                        if arc_count == 2
+                            && transition.input_arcs[input].weight
+                                == transition.output_arcs[output].weight =>
+                    {
+                        let weight = transition.input_arcs[input].weight;
+                        Some((t, weight, SelfLoop::Arcs(input, output)))
+                    }
+                    _ => None,
+                }
+            });
+        let (t, weight, self_loop) = match found {
+            Some(found) => found,
+            None => return false,
+        };
+
+        let transition = &self.transitions[t];
+        if place.tokens() < weight || transition.guard.is_some() || transition.has_dynamic_arcs() {
+            return false;
+        }
+
+        let transition = &mut self.transitions[t];
+        match self_loop {
+            SelfLoop::Loop(l) => {
+                transition.loop_arcs.remove(l);
+            }
+            SelfLoop::Arcs(input, output) => {
+                transition.input_arcs.remove(input);
+                transition.output_arcs.remove(output);
+            }
+        }
+        self.unregister_place(p);
+        true
+    }
+
+    /// Apply structural reductions until none applies any more, and return
+    /// how many were applied. Two rules are implemented:
+    ///
+    /// * Fusion of series transitions: an unmarked place whose only arcs are
+    ///   one arc of weight 1 from a transition `t1` and one arc of weight 1
+    ///   to a transition `t2`, where `t1` has no other output arc and `t2` no
+    ///   other input arc. `t2` is removed and `t1` takes over its output
+    ///   arcs, so firing the fused transition stands for firing `t1` and `t2`
+    ///   in a row. Both transitions must only have input and output arcs, and
+    ///   neither an expression nor a guard. The fused transition keeps the
+    ///   name, priority, rate and delay of `t1`, and runs the `on_fire`
+    ///   actions of `t1` and `t2` in this order.
+    /// * Elimination of self-loop places: a place whose only arcs are a
+    ///   self-loop, or an input and an output arc of equal weight, of a single
+    ///   transition without guard, variable, sampled and overflow arcs, and which holds at
+    ///   least the arc weight. The place never disables the transition, so it is
+    ///   removed with both arcs.
+    ///
+    /// Both rules preserve liveness, boundedness and safeness. Removed places
+    /// are unregistered, so the indices of places and transitions change, and
+    /// markings taken before the reduction don't fit the net any more.
+    pub fn reduce(&mut self) -> usize {
+        let mut reductions = 0;
+        'reduce: loop {
+            for p in 0..self.places.len() {
+                if self.eliminate_self_loop_place(p) || self.fuse_series_transitions(p) {
+                    reductions += 1;
+                    continue 'reduce;
+                }
+            }
+            return reductions;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_reduce() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+        let p4 = Place::new(1, "p4");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // p1 -> t1 -> p2 -> t2 -> p3, with the self-loop place p4 on t1
+        let mut t1 = Transition::with_name("t1");
+        t1.add_input(&a1);
+        t1.add_loop(&p4, 1).expect("weight greater than zero");
+        t1.add_output(&a2);
+        let mut t2 = Transition::with_name("t2");
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        assert_eq!(vec![1, 1, 0, 0], net.marking());
+
+        assert_eq!(2, net.reduce());
+        assert_eq!(vec![("p1", 1), ("p3", 0)], net.named_marking());
+        assert_eq!(1, net.transitions().len());
+        assert_eq!(Some("t1"), net.transitions()[0].name());
+        assert_eq!(&[1, 0], net.initial_marking());
+
+        assert_eq!(1, net.run_until_deadlock(10));
+        assert_eq!(vec![0, 1], net.marking());
+        assert_eq!(0, net.reduce());
+    }
+
+    #[test]
+    fn test_reduce_keeps_actions() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let fired = RefCell::new(Vec::new());
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        t1.on_fire(|| fired.borrow_mut().push("t1"));
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        t2.on_fire(|| fired.borrow_mut().push("t2"));
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        assert_eq!(1, net.reduce());
+        assert_eq!(1, net.run_until_deadlock(10));
+        drop(net);
+        assert_eq!(vec!["t1", "t2"], fired.into_inner());
+    }
+
+    #[test]
+    fn test_reduce_keeps_marked_places_and_guards() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(1, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // p2 is marked, so t1 and t2 can't be fused
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+        // the guard may depend on the self-loop place
+        let mut t3 = Transition::new_with_guard(|_| true);
+        t3.add_loop(&p3, 1).expect("weight greater than zero");
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        assert_eq!(0, net.reduce());
+        assert_eq!(3, net.transitions().len());
+    }
+}
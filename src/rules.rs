@@ -0,0 +1,142 @@
+//! The firing rule of a net on plain markings, so successor markings can be
+//! computed without touching the places, e.g. on several threads.
+
+use std::convert::TryFrom;
+
+use crate::{Marking, Petrinet, TokenPolicy};
+
+/// The arcs of a transition, referring to places by index.
+#[derive(Debug, Clone)]
+struct Rule {
+    demand: Vec<(usize, u64)>,
+    reads: Vec<(usize, u32)>,
+    inhibitors: Vec<(usize, u32)>,
+    zero_tests: Vec<usize>,
+    resets: Vec<usize>,
+    outputs: Vec<(usize, u32)>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Rules {
+    rules: Vec<Rule>,
+    capacities: Vec<Option<u32>>,
+    policy: TokenPolicy,
+}
+
+impl Rules {
+    /// The firing rule of `net`, or `None` if a transition has an expression,
+    /// a guard, a variable, sampled or overflow arc, which can't be evaluated
+    /// on a plain marking.
+    pub(crate) fn new(net: &Petrinet<'_>) -> Option<Self> {
+        let index = |place| net.place_index(place).expect("registered place");
+
+        let mut rules = Vec::with_capacity(net.transitions.len());
+        for transition in &net.transitions {
+            if transition.expression.is_some()
+                || transition.guard.is_some()
+                || transition.has_dynamic_arcs()
+            {
+                return None;
+            }
+
+            let mut demand: Vec<(usize, u64)> = Vec::new();
+            for (place, w) in transition.weighted_inputs() {
+                let p = index(place);
+                match demand.iter_mut().find(|(q, _)| *q == p) {
+                    Some((_, weight)) => *weight += u64::from(w),
+                    None => demand.push((p, u64::from(w))),
+                }
+            }
+
+            rules.push(Rule {
+                demand,
+                reads: transition
+                    .read_arcs
+                    .iter()
+                    .map(|arc| (index(arc.place), arc.weight))
+                    .collect(),
+                inhibitors: transition
+                    .inhibitor_arcs
+                    .iter()
+                    .map(|arc| (index(arc.place), arc.weight))
+                    .collect(),
+                zero_tests: transition
+                    .zero_test_places
+                    .iter()
+                    .map(|place| index(place))
+                    .collect(),
+                resets: transition
+                    .reset_places
+                    .iter()
+                    .map(|place| index(place))
+                    .collect(),
+                outputs: transition
+                    .weighted_outputs()
+                    .map(|(place, weight)| (index(place), weight))
+                    .collect(),
+            });
+        }
+
+        Some(Self {
+            rules,
+            capacities: net.places.iter().map(|place| place.capacity()).collect(),
+            policy: net.token_policy,
+        })
+    }
+
+    /// The marking after firing the transition of `rule` in `marking`, or
+    /// `None` if the transition is not enabled or overflows a token count under
+    /// `TokenPolicy::Checked`. Mirrors `Transition::fire`.
+    fn fire(&self, rule: &Rule, marking: &[u32]) -> Option<Marking> {
+        let enabled = rule
+            .demand
+            .iter()
+            .all(|&(p, weight)| u64::from(marking[p]) >= weight)
+            && rule.reads.iter().all(|&(p, weight)| marking[p] >= weight)
+            && !rule
+                .inhibitors
+                .iter()
+                .any(|&(p, weight)| marking[p] >= weight)
+            && rule.zero_tests.iter().all(|&p| marking[p] == 0);
+        if !enabled {
+            return None;
+        }
+
+        let mut successor: Vec<u64> = marking.iter().map(|&tokens| u64::from(tokens)).collect();
+        for &(p, weight) in &rule.demand {
+            successor[p] -= weight;
+        }
+        for &p in &rule.resets {
+            successor[p] = 0;
+        }
+        for &(p, weight) in &rule.outputs {
+            successor[p] += u64::from(weight);
+        }
+
+        let within_capacity = rule.outputs.iter().all(|&(p, _)| {
+            self.capacities[p].is_none_or(|capacity| successor[p] <= u64::from(capacity))
+        });
+        if !within_capacity {
+            return None;
+        }
+
+        successor
+            .into_iter()
+            .map(|tokens| match self.policy {
+                TokenPolicy::Saturating => Some(u32::try_from(tokens).unwrap_or(u32::MAX)),
+                TokenPolicy::Checked => u32::try_from(tokens).ok(),
+                TokenPolicy::Wrapping => Some(tokens as u32),
+            })
+            .collect()
+    }
+
+    /// The successors of `marking` as pairs of fired transition and
+    /// successor marking, in transition order.
+    pub(crate) fn successors_of(&self, marking: &[u32]) -> Vec<(usize, Marking)> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter_map(|(t, rule)| self.fire(rule, marking).map(|m| (t, m)))
+            .collect()
+    }
+}
@@ -0,0 +1,177 @@
+use rand::Rng;
+
+use crate::{FirstEnabledScheduler, Petrinet, Scheduler};
+
+/// Statistics about the firings of a run, see
+/// `Petrinet::run_until_deadlock_with_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunStats {
+    steps: usize,
+    fire_counts: Vec<u64>,
+    tokens_produced: Vec<u64>,
+    tokens_consumed: Vec<u64>,
+}
+
+impl RunStats {
+    /// Number of steps taken.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// How often each transition fired, indexed by transition.
+    pub fn fire_counts(&self) -> &[u64] {
+        &self.fire_counts
+    }
+
+    /// The tokens put on each place by firing, in registration order.
+    pub fn tokens_produced(&self) -> &[u64] {
+        &self.tokens_produced
+    }
+
+    /// The tokens taken from each place by firing, in registration order.
+    /// Tokens removed by reset arcs count as consumed.
+    pub fn tokens_consumed(&self) -> &[u64] {
+        &self.tokens_consumed
+    }
+}
+
+impl Petrinet<'_> {
+    /// Like `run_until_deadlock`, but count how often each transition fires
+    /// and how many tokens are produced on and consumed from each place.
+    ///
+    /// Places connected by a self-loop count the tokens in both directions,
+    /// although their token count doesn't change. Plain runs don't collect
+    /// any statistics.
+    pub fn run_until_deadlock_with_stats(&mut self, max_steps: usize) -> RunStats {
+        let mut stats = RunStats {
+            steps: 0,
+            fire_counts: vec![0; self.transitions.len()],
+            tokens_produced: vec![0; self.places.len()],
+            tokens_consumed: vec![0; self.places.len()],
+        };
+
+        while stats.steps < max_steps {
+            let enabled = self.enabled_transitions();
+            let i = match FirstEnabledScheduler.choose(&enabled, self) {
+                Some(i) => i,
+                None => break,
+            };
+
+            let (consumed, produced) = self.transitions[i].token_flow();
+            if self.fire_at(i).is_err() {
+                break;
+            }
+
+            stats.steps += 1;
+            stats.fire_counts[i] += 1;
+            for (place, tokens) in consumed {
+                let p = self.place_index(place).expect("registered place");
+                stats.tokens_consumed[p] += tokens;
+            }
+            for (place, tokens) in produced {
+                let p = self.place_index(place).expect("registered place");
+                stats.tokens_produced[p] += tokens;
+            }
+        }
+
+        stats
+    }
+
+    /// Step the net up to `steps` times with `step_random` and return, for
+    /// every step, how many transitions were enabled before firing. A
+    /// sequential net yields only ones, and large counts hint that
+    /// `step_maximal` fires many transitions at once. The profile ends early
+    /// when the net deadlocks.
+    ///
+    /// The marking of the net is restored afterwards.
+    pub fn concurrency_profile(&mut self, steps: usize, rng: &mut impl Rng) -> Vec<usize> {
+        let initial = self.snapshot();
+        let mut profile = Vec::with_capacity(steps);
+
+        for _ in 0..steps {
+            let enabled = self.enabled_transitions().len();
+            if !self.step_random(rng) {
+                break;
+            }
+            profile.push(enabled);
+        }
+
+        self.restore(&initial);
+        profile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_run_until_deadlock_with_stats() {
+        let p1 = Place::new(3, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(1, "p3");
+        let p4 = Place::new(0, "p4");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 2).expect("weight greater than zero");
+        let a4 = Arc::new(&p4, 1).expect("weight greater than zero");
+        let a5 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // t1 moves single tokens from p1 to p2 with the self-loop p3, t2
+        // takes two tokens from p2 and resets p3, which disables t1
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_loop(&p3, 1).expect("weight greater than zero");
+        t1.add_output(&a5);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_reset(&p3);
+        t2.add_output(&a4);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t2);
+        net.add_transition(t1);
+        assert_eq!(
+            vec![("p2", 0), ("p4", 0), ("p3", 1), ("p1", 3)],
+            net.named_marking()
+        );
+
+        let stats = net.run_until_deadlock_with_stats(10);
+        assert_eq!(3, stats.steps());
+        assert_eq!(&[1, 2], stats.fire_counts());
+        assert_eq!(&[2, 0, 3, 2], stats.tokens_consumed());
+        assert_eq!(&[2, 1, 2, 0], stats.tokens_produced());
+        assert_eq!(vec![0, 1, 0, 1], net.marking());
+
+        net.reset();
+        assert_eq!(&[0, 1], net.run_until_deadlock_with_stats(1).fire_counts());
+    }
+
+    #[test]
+    fn test_concurrency_profile() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(1, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // two independent sinks, which drain their places
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let profile = net.concurrency_profile(10, &mut rng);
+        assert_eq!(3, profile.len(), "deadlocked after three steps");
+        assert_eq!(2, profile[0]);
+        assert_eq!(1, profile[2]);
+        assert_eq!(vec![2, 1], net.marking(), "marking is restored");
+        assert_eq!(vec![2], net.concurrency_profile(1, &mut rng));
+    }
+}
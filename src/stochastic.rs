@@ -0,0 +1,184 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::Petrinet;
+
+impl Petrinet<'_> {
+    /// The enabled immediate transitions among `enabled`, or all of
+    /// `enabled` if none of them is immediate.
+    pub(crate) fn gspn_candidates(&self, enabled: &[usize]) -> Vec<usize> {
+        let immediate: Vec<usize> = enabled
+            .iter()
+            .copied()
+            .filter(|&i| self.transitions[i].is_immediate())
+            .collect();
+        if immediate.is_empty() {
+            enabled.to_vec()
+        } else {
+            immediate
+        }
+    }
+
+    /// Simulate the net as generalized stochastic petri net with Gillespie's
+    /// stochastic simulation algorithm.
+    ///
+    /// Each enabled timed transition fires after an exponentially distributed
+    /// delay with its rate as parameter. In a vanishing marking, where an
+    /// immediate transition is enabled, only immediate transitions fire,
+    /// chosen by their weights, and the clock doesn't advance. The
+    /// simulation clock starts at 0 and stops at `t_end`, or as soon as the
+    /// total rate or weight of the candidate transitions drops to 0. Returns
+    /// the firing times together with the index of the fired transition.
+    ///
+    /// The clock of the net, see `set_time`, follows the simulation clock, so
+    /// timed guards see the time of the next firing. As a simplification,
+    /// the delay is drawn from the transitions enabled before it, so a guard
+    /// which only opens later doesn't shorten it, and the simulation stops if
+    /// no transition is enabled, even if a guard would open later.
+    pub fn simulate_ssa(&mut self, rng: &mut impl Rng, t_end: f64) -> Vec<(f64, usize)> {
+        let mut time = 0.0;
+        let mut firings = Vec::new();
+        self.set_time(time);
+
+        loop {
+            let enabled = self.gspn_candidates(&self.enabled_transitions());
+            let rates: Vec<f64> = enabled
+                .iter()
+                .map(|&i| self.transitions[i].rate())
+                .collect();
+            let total: f64 = rates.iter().sum();
+            if total <= 0.0 {
+                break;
+            }
+
+            let vanishing = enabled
+                .first()
+                .is_some_and(|&i| self.transitions[i].is_immediate());
+            if !vanishing {
+                // inverse transform sampling of the exponential distribution,
+                // 1 - u lies in (0, 1], so the logarithm is finite
+                let u: f64 = rng.gen();
+                time += -(1.0 - u).ln() / total;
+                if time > t_end {
+                    break;
+                }
+                self.set_time(time);
+            }
+
+            let index = WeightedIndex::new(&rates).expect("positive total rate");
+            let transition = enabled[index.sample(rng)];
+            if self.fire_at(transition).is_ok() {
+                // a timed guard may have closed meanwhile
+                firings.push((time, transition));
+            }
+        }
+
+        firings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::{Arc, Petrinet, Place, Transition, TransitionKind};
+
+    #[test]
+    fn test_simulate_ssa() {
+        let p1 = Place::new(100, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        t.set_rate(10.0);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let firings = net.simulate_ssa(&mut rng, 5.0);
+
+        // with rate 10, about 50 firings happen within 5 time units
+        assert!(
+            firings.len() > 30 && firings.len() < 70,
+            "{}",
+            firings.len()
+        );
+        assert!(firings.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(firings.iter().all(|&(time, t)| time <= 5.0 && t == 0));
+        assert_eq!(100, p1.tokens() + p2.tokens());
+        assert_eq!(firings.len() as u32, p2.tokens());
+    }
+
+    #[test]
+    fn test_simulate_ssa_deadlock() {
+        let p1 = Place::new(3, "p1");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let firings = net.simulate_ssa(&mut rng, f64::INFINITY);
+        assert_eq!(3, firings.len(), "stops when the net deadlocks");
+
+        let mut t = Transition::new();
+        t.set_rate(0.0);
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        assert!(net.simulate_ssa(&mut rng, 10.0).is_empty(), "total rate 0");
+    }
+
+    #[test]
+    fn test_simulate_gspn() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // a timed transition puts a token back on p1, which two immediate
+        // transitions compete for
+        let mut timed = Transition::new();
+        timed.add_input(&a3);
+        timed.add_output(&a1);
+        let mut t1 = Transition::new();
+        t1.set_kind(TransitionKind::Immediate { weight: 1.0 });
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.set_kind(TransitionKind::Immediate { weight: 3.0 });
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+        assert!(t2.is_immediate());
+        assert_eq!(3.0, t2.rate());
+
+        let mut net = Petrinet::new();
+        net.add_transition(timed);
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let firings = net.simulate_ssa(&mut rng, f64::INFINITY);
+        assert_eq!(Some(&(0.0, firings[0].1)), firings.first());
+        assert_eq!(Some(1), firings.last().map(|&(_, t)| t));
+        for pair in firings.windows(2) {
+            match pair {
+                [(t0, 2), (t1, 0)] => assert!(t1 > t0, "timed firings take time"),
+                [(t0, 0), (t1, _)] => assert_eq!(t0, t1, "immediate firings don't"),
+                _ => {}
+            }
+        }
+        assert_eq!((0, 1, 0), (p1.tokens(), p2.tokens(), p3.tokens()));
+    }
+}
@@ -0,0 +1,556 @@
+//! Structural analysis, which only depends on the arcs of a net but not on
+//! its marking.
+//!
+//! Presets and postsets only take input and output arcs into account, and
+//! are returned in ascending order without duplicates.
+
+use std::ptr;
+
+use crate::{Petrinet, Place};
+
+/// A place or a transition of a net, referred to by index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Node {
+    Place(usize),
+    Transition(usize),
+}
+
+fn sorted(mut indices: Vec<usize>) -> Vec<usize> {
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/// The minimal non-empty sets of the places `0..n` which are `closed`, given as a
+/// membership vector. Sets are enumerated by increasing size, skipping
+/// supersets of sets already found.
+pub(crate) fn minimal_place_sets(n: usize, closed: impl Fn(&[bool]) -> bool) -> Vec<Vec<usize>> {
+    let mut found: Vec<Vec<usize>> = Vec::new();
+
+    for size in 1..=n {
+        let mut set: Vec<usize> = (0..size).collect();
+        loop {
+            if !found.iter().any(|f| f.iter().all(|p| set.contains(p))) {
+                let mut member = vec![false; n];
+                for &p in &set {
+                    member[p] = true;
+                }
+                if closed(&member) {
+                    found.push(set.clone());
+                }
+            }
+
+            // advance to the next combination in lexicographic order
+            let mut i = size;
+            while i > 0 && set[i - 1] == n - size + i - 1 {
+                i -= 1;
+            }
+            if i == 0 {
+                break;
+            }
+            set[i - 1] += 1;
+            for j in i..size {
+                set[j] = set[j - 1] + 1;
+            }
+        }
+    }
+
+    found
+}
+
+/// Presets and postsets of transitions.
+pub(crate) type Flow = [(Vec<usize>, Vec<usize>)];
+
+/// Whether every transition producing tokens into `set` also consumes from
+/// it.
+pub(crate) fn is_siphon(flow: &Flow, set: &[bool]) -> bool {
+    flow.iter()
+        .all(|(preset, postset)| !postset.iter().any(|&p| set[p]) || preset.iter().any(|&p| set[p]))
+}
+
+/// Whether every transition consuming tokens from `set` also produces into
+/// it.
+pub(crate) fn is_trap(flow: &Flow, set: &[bool]) -> bool {
+    flow.iter()
+        .all(|(preset, postset)| !preset.iter().any(|&p| set[p]) || postset.iter().any(|&p| set[p]))
+}
+
+impl Petrinet<'_> {
+    fn arc_places<'b>(&self, arcs: impl Iterator<Item = (&'b Place, u32)>) -> Vec<usize> {
+        sorted(
+            arcs.map(|(place, _)| self.place_index(place).expect("registered place"))
+                .collect(),
+        )
+    }
+
+    fn arc_transitions(&self, place: usize, connected: impl Fn(usize) -> bool) -> Vec<usize> {
+        assert!(place < self.places.len(), "no such place");
+        (0..self.transitions.len())
+            .filter(|&t| connected(t))
+            .collect()
+    }
+
+    /// Indices of the places with an arc to the transition at index
+    /// `transition`, •t.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn transition_preset(&self, transition: usize) -> Vec<usize> {
+        self.arc_places(self.transitions[transition].weighted_inputs())
+    }
+
+    /// Indices of the places with an arc from the transition at index
+    /// `transition`, t•.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn transition_postset(&self, transition: usize) -> Vec<usize> {
+        self.arc_places(self.transitions[transition].weighted_outputs())
+    }
+
+    /// Indices of the transitions with an arc to the place at index `place`,
+    /// •p.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no place at index `place`.
+    pub fn place_preset(&self, place: usize) -> Vec<usize> {
+        self.arc_transitions(place, |t| {
+            self.transitions[t]
+                .weighted_outputs()
+                .any(|(p, _)| ptr::eq(p, self.places[place]))
+        })
+    }
+
+    /// Indices of the transitions with an arc from the place at index
+    /// `place`, p•.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no place at index `place`.
+    pub fn place_postset(&self, place: usize) -> Vec<usize> {
+        self.arc_transitions(place, |t| {
+            self.transitions[t]
+                .weighted_inputs()
+                .any(|(p, _)| ptr::eq(p, self.places[place]))
+        })
+    }
+
+    /// Pairs of transitions which share an input place and can therefore be
+    /// in conflict. Each pair `(i, j)` satisfies `i < j`.
+    pub fn conflicting_pairs(&self) -> Vec<(usize, usize)> {
+        let inputs: Vec<Vec<usize>> = (0..self.transitions.len())
+            .map(|t| self.transition_preset(t))
+            .collect();
+
+        let mut pairs = Vec::new();
+        for i in 0..inputs.len() {
+            for j in i + 1..inputs.len() {
+                if inputs[i].iter().any(|p| inputs[j].contains(p)) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    /// The presets of the transitions with an arc from each place, p• for
+    /// every place p.
+    fn postset_presets(&self) -> Vec<Vec<Vec<usize>>> {
+        let presets: Vec<Vec<usize>> = (0..self.transitions.len())
+            .map(|t| self.transition_preset(t))
+            .collect();
+        (0..self.places.len())
+            .map(|p| {
+                presets
+                    .iter()
+                    .filter(|preset| preset.contains(&p))
+                    .cloned()
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Whether the net is free-choice: every place with more than one
+    /// transition in its postset is the only input place of all of them, so
+    /// the choice between these transitions doesn't depend on other places.
+    /// Arc weights are not taken into account.
+    pub fn is_free_choice(&self) -> bool {
+        self.postset_presets()
+            .iter()
+            .enumerate()
+            .all(|(p, presets)| presets.len() < 2 || presets.iter().all(|preset| preset == &[p]))
+    }
+
+    /// Whether the net is extended free-choice: any two transitions sharing
+    /// an input place have the same input places. Every free-choice net is
+    /// extended free-choice. Arc weights are not taken into account.
+    pub fn is_extended_free_choice(&self) -> bool {
+        self.postset_presets()
+            .iter()
+            .all(|presets| presets.windows(2).all(|pair| pair[0] == pair[1]))
+    }
+
+    /// The incidence matrix of the net, with a row per place and a column per
+    /// transition. Entry `[p][t]` is the number of tokens transition `t`
+    /// produces on place `p` minus the number it consumes from it.
+    ///
+    /// A self-loop nets to zero. Inhibitor and reset arcs are not taken into
+    /// account.
+    pub fn incidence_matrix(&self) -> Vec<Vec<i64>> {
+        let mut matrix = vec![vec![0; self.transitions.len()]; self.places.len()];
+
+        for (t, transition) in self.transitions.iter().enumerate() {
+            for (place, weight) in transition.weighted_inputs() {
+                let p = self.place_index(place).expect("registered place");
+                matrix[p][t] -= i64::from(weight);
+            }
+
+            for (place, weight) in transition.weighted_outputs() {
+                let p = self.place_index(place).expect("registered place");
+                matrix[p][t] += i64::from(weight);
+            }
+        }
+
+        matrix
+    }
+
+    /// Presets and postsets of all transitions.
+    pub(crate) fn flow(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        (0..self.transitions.len())
+            .map(|t| (self.transition_preset(t), self.transition_postset(t)))
+            .collect()
+    }
+
+    /// The minimal siphons of the net. A siphon is a set of places `S` with
+    /// •S ⊆ S•: every transition producing tokens into `S` also consumes from
+    /// `S`, so an empty siphon stays empty.
+    ///
+    /// Every place set is a candidate, so the cost is exponential in the
+    /// number of places. The result is sorted by size.
+    pub fn siphons(&self) -> Vec<Vec<usize>> {
+        let flow = self.flow();
+        minimal_place_sets(self.places.len(), |set| is_siphon(&flow, set))
+    }
+
+    /// The minimal traps of the net. A trap is a set of places `S` with
+    /// S• ⊆ •S: every transition consuming tokens from `S` also produces
+    /// into `S`, so a marked trap stays marked.
+    ///
+    /// Like `siphons`, the cost is exponential in the number of places.
+    pub fn traps(&self) -> Vec<Vec<usize>> {
+        let flow = self.flow();
+        minimal_place_sets(self.places.len(), |set| is_trap(&flow, set))
+    }
+
+    /// The strongly connected components of the graph with the places and
+    /// transitions as nodes and the arcs as edges. The net is strongly
+    /// connected if there is a single component.
+    ///
+    /// Components are computed with Tarjan's algorithm and returned in
+    /// reverse topological order, so no component has an arc to a later
+    /// one. The nodes of each component are sorted, places first.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<Node>> {
+        // places are the nodes 0..places, transitions follow
+        let places = self.places.len();
+        let node = |i: usize| {
+            if i < places {
+                Node::Place(i)
+            } else {
+                Node::Transition(i - places)
+            }
+        };
+        let successors: Vec<Vec<usize>> = (0..places)
+            .map(|p| {
+                self.place_postset(p)
+                    .into_iter()
+                    .map(|t| places + t)
+                    .collect()
+            })
+            .chain((0..self.transitions.len()).map(|t| self.transition_postset(t)))
+            .collect();
+
+        let nodes = successors.len();
+        let mut index: Vec<Option<usize>> = vec![None; nodes];
+        let mut lowlink = vec![0; nodes];
+        let mut on_stack = vec![false; nodes];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+
+        for root in 0..nodes {
+            if index[root].is_some() {
+                continue;
+            }
+
+            // depth-first search without recursion, every frame holds a
+            // node and the number of its successors visited so far
+            let mut frames = vec![(root, 0)];
+            while let Some(&(v, visited)) = frames.last() {
+                if visited == 0 {
+                    index[v] = Some(next_index);
+                    lowlink[v] = next_index;
+                    next_index += 1;
+                    stack.push(v);
+                    on_stack[v] = true;
+                }
+
+                if let Some(&w) = successors[v].get(visited) {
+                    frames.last_mut().expect("current frame").1 += 1;
+                    match index[w] {
+                        None => frames.push((w, 0)),
+                        Some(w_index) if on_stack[w] => lowlink[v] = lowlink[v].min(w_index),
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                if let Some(&(u, _)) = frames.last() {
+                    lowlink[u] = lowlink[u].min(lowlink[v]);
+                }
+                if index[v] == Some(lowlink[v]) {
+                    let mut component = Vec::new();
+                    loop {
+                        let w = stack.pop().expect("node of the component");
+                        on_stack[w] = false;
+                        component.push(node(w));
+                        if w == v {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Arc, Transition};
+
+    #[test]
+    fn test_conflicting_pairs() {
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a3);
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+        t2.add_input(&a2);
+        let mut t3 = Transition::new();
+        t3.add_input(&a2);
+        // t4 only produces into p1, which is no conflict
+        let mut t4 = Transition::new();
+        t4.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+        net.add_transition(t4);
+
+        assert_eq!(vec![(0, 1), (1, 2)], net.conflicting_pairs());
+    }
+
+    #[test]
+    fn test_free_choice() {
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // t1 and t2 choose freely between the tokens on p1, t3 synchronizes
+        // p2 and p3
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_input(&a2);
+        t3.add_input(&a3);
+        t3.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+        assert!(net.is_free_choice());
+        assert!(net.is_extended_free_choice());
+
+        // t4 shares all input places with t3
+        let mut t4 = Transition::new();
+        t4.add_input(&a2);
+        t4.add_input(&a3);
+        net.add_transition(t4);
+        assert!(!net.is_free_choice());
+        assert!(net.is_extended_free_choice());
+
+        // t5 only shares p2 with t3 and t4
+        let mut t5 = Transition::new();
+        t5.add_input(&a2);
+        net.add_transition(t5);
+        assert!(!net.is_free_choice());
+        assert!(!net.is_extended_free_choice());
+    }
+
+    #[test]
+    fn test_presets_and_postsets() {
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 2).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        t1.add_output(&a3);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+        t2.add_read(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+
+        assert_eq!(vec![0], net.transition_preset(0));
+        assert_eq!(vec![1, 2], net.transition_postset(0));
+        assert_eq!(vec![1], net.transition_preset(1), "read arcs are ignored");
+        assert_eq!(vec![0], net.transition_postset(1));
+
+        assert_eq!(vec![1], net.place_preset(0));
+        assert_eq!(vec![0], net.place_postset(0));
+        assert_eq!(vec![0], net.place_preset(2));
+        assert!(net.place_postset(2).is_empty());
+    }
+
+    #[test]
+    fn test_siphons_and_traps() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // the token cycles between p1 and p2 until t3 moves it to p3
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+        let mut t3 = Transition::new();
+        t3.add_input(&a2);
+        t3.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        assert_eq!(vec![vec![0, 1]], net.siphons());
+        assert_eq!(vec![vec![2]], net.traps());
+        assert!(Petrinet::new().siphons().is_empty());
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // t1 and t2 cycle between p1 and p2, t3 leaves the cycle for p3
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+        let mut t3 = Transition::new();
+        t3.add_input(&a2);
+        t3.add_output(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        assert_eq!(
+            vec![
+                vec![Node::Place(2)],
+                vec![Node::Transition(2)],
+                vec![
+                    Node::Place(0),
+                    Node::Place(1),
+                    Node::Transition(0),
+                    Node::Transition(1)
+                ],
+            ],
+            net.strongly_connected_components()
+        );
+
+        // p3 stays registered, but unconnected
+        net.remove_transition(2);
+        assert_eq!(2, net.strongly_connected_components().len());
+        assert!(Petrinet::new().strongly_connected_components().is_empty());
+    }
+
+    #[test]
+    fn test_incidence_matrix() {
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(0, "p2");
+
+        let a1 = Arc::new(&p1, 2).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 3).expect("weight greater than zero");
+        let a3 = Arc::new(&p1, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        // self-loop consuming two and producing one token
+        let mut t2 = Transition::new();
+        t2.add_input(&a1);
+        t2.add_output(&a3);
+        let mut t3 = Transition::new();
+        t3.add_inhibitor(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        net.add_transition(t3);
+
+        assert_eq!(vec![vec![-2, -1, 0], vec![3, 0, 0]], net.incidence_matrix());
+    }
+}
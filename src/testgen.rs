@@ -0,0 +1,150 @@
+//! Random nets for property testing, behind the `testgen` feature.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::OwnedPetrinet;
+
+/// Add an arc of random direction and weight between `transition` and
+/// `place`.
+fn add_random_arc(
+    net: &mut OwnedPetrinet,
+    rng: &mut impl Rng,
+    transition: usize,
+    place: usize,
+    max_weight: u32,
+) {
+    let weight = rng.gen_range(1..=max_weight);
+    if rng.gen() {
+        net.add_input(transition, place, weight)
+    } else {
+        net.add_output(transition, place, weight)
+    }
+    .expect("valid arc");
+}
+
+/// Generate a net with `num_places` places named `p0`, `p1`, ... and
+/// `num_transitions` transitions named `t0`, `t1`, ..., whose arcs have
+/// random directions and weights from 1 to `max_weight`, and whose places
+/// hold from 0 to `max_weight` tokens.
+///
+/// The net is connected: the arcs contain a random spanning tree of all
+/// places and transitions, and then as many further arcs as there are
+/// transitions, between random pairs. A place and a transition may be
+/// connected by several arcs.
+///
+/// # Panics
+///
+/// Panics if `num_places`, `num_transitions` or `max_weight` is 0.
+pub fn random_net(
+    rng: &mut impl Rng,
+    num_places: usize,
+    num_transitions: usize,
+    max_weight: u32,
+) -> OwnedPetrinet {
+    assert!(num_places > 0, "a net needs at least one place");
+    assert!(num_transitions > 0, "a net needs at least one transition");
+    assert!(max_weight > 0, "arc weights must be greater than zero");
+
+    let mut net = OwnedPetrinet::new();
+    for p in 0..num_places {
+        let tokens = rng.gen_range(0..=max_weight);
+        net.add_place(tokens, &format!("p{}", p));
+    }
+    for t in 0..num_transitions {
+        net.add_transition(Some(&format!("t{}", t)));
+    }
+
+    // connect every node but p0 and t0 to a random node of the other kind
+    // added before it
+    let mut nodes: Vec<(bool, usize)> = (1..num_places)
+        .map(|p| (true, p))
+        .chain((1..num_transitions).map(|t| (false, t)))
+        .collect();
+    nodes.shuffle(rng);
+    let mut places = vec![0];
+    let mut transitions = vec![0];
+    add_random_arc(&mut net, rng, 0, 0, max_weight);
+    for (is_place, i) in nodes {
+        if is_place {
+            let t = *transitions.choose(rng).expect("t0 is added");
+            add_random_arc(&mut net, rng, t, i, max_weight);
+            places.push(i);
+        } else {
+            let p = *places.choose(rng).expect("p0 is added");
+            add_random_arc(&mut net, rng, i, p, max_weight);
+            transitions.push(i);
+        }
+    }
+
+    for _ in 0..num_transitions {
+        let t = rng.gen_range(0..num_transitions);
+        let p = rng.gen_range(0..num_places);
+        add_random_arc(&mut net, rng, t, p, max_weight);
+    }
+
+    net
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    /// Whether all places and transitions are connected, ignoring the
+    /// direction of the arcs.
+    fn is_connected(net: &OwnedPetrinet) -> bool {
+        let mut reached_places = vec![false; net.places().len()];
+        let mut reached_transitions = vec![false; net.transitions().len()];
+        reached_places[0] = true;
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (t, transition) in net.transitions().iter().enumerate() {
+                for arc in transition.inputs().iter().chain(transition.outputs()) {
+                    let p = arc.place();
+                    if reached_places[p] != reached_transitions[t] {
+                        reached_places[p] = true;
+                        reached_transitions[t] = true;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        reached_places
+            .into_iter()
+            .chain(reached_transitions)
+            .all(|r| r)
+    }
+
+    #[test]
+    fn test_random_net() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for (num_places, num_transitions) in [(1, 1), (1, 5), (5, 1), (6, 4), (20, 30)] {
+            let net = random_net(&mut rng, num_places, num_transitions, 3);
+            assert_eq!(num_places, net.places().len());
+            assert_eq!(num_transitions, net.transitions().len());
+            assert!(is_connected(&net));
+            assert!(net.marking().iter().all(|&tokens| tokens <= 3));
+
+            let arcs = net
+                .transitions()
+                .iter()
+                .flat_map(|transition| transition.inputs().iter().chain(transition.outputs()));
+            assert!(arcs.clone().all(|arc| (1..=3).contains(&arc.weight())));
+            assert_eq!(num_places + 2 * num_transitions - 1, arcs.count());
+            assert_eq!(Some("t0"), net.transitions()[0].name());
+            assert_eq!(Ok(()), net.with_net(|net| net.validate()));
+        }
+
+        assert_eq!(
+            random_net(&mut StdRng::seed_from_u64(1), 4, 4, 2),
+            random_net(&mut StdRng::seed_from_u64(1), 4, 4, 2),
+            "the same seed generates the same net"
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! A compact text format for nets.
+//!
+//! A net is a sequence of statements, each terminated by a semicolon, which
+//! may be omitted after the last one. A place is declared with its initial
+//! token count, a transition with its input and output places, each list
+//! separated by commas and with an optional weight after a `*`:
+//!
+//! ```text
+//! p1: 2;
+//! p2: 0;
+//! t1: p1 -> p2*2;
+//! ```
+//!
+//! Places must be declared before the transitions using them, and all names
+//! must be distinct. Whitespace, including line breaks, is insignificant.
+
+use std::error;
+use std::fmt;
+use std::result;
+
+use crate::OwnedPetrinet;
+
+/// What went wrong when parsing a net, see `ParseError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A character which is not part of the format.
+    UnexpectedCharacter(char),
+    /// Another token or the end of the input was found instead of this.
+    Expected(&'static str),
+    /// A token count or weight is not a valid `u32`.
+    InvalidNumber(String),
+    /// An arc has weight 0.
+    ZeroWeight,
+    /// An arc refers to a place which was not declared before.
+    UnknownPlace(String),
+    /// A place or transition is declared twice.
+    DuplicateName(String),
+    /// A comment is not closed until the end of the input.
+    UnterminatedComment,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedCharacter(c) => write!(f, "unexpected character {:?}", c),
+            Self::Expected(expected) => write!(f, "expected {}", expected),
+            Self::InvalidNumber(text) => write!(f, "invalid number {:?}", text),
+            Self::ZeroWeight => write!(f, "arc weight must be greater than zero"),
+            Self::UnknownPlace(name) => write!(f, "unknown place {}", name),
+            Self::DuplicateName(name) => write!(f, "{} is declared twice", name),
+            Self::UnterminatedComment => write!(f, "unterminated comment"),
+        }
+    }
+}
+
+/// Error returned by `parse_net` and `OwnedPetrinet::from_lola`, with the
+/// position it occurred at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    column: usize,
+    kind: ParseErrorKind,
+}
+
+impl ParseError {
+    /// The line of the error, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column of the error in characters, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    Number(String),
+    Colon,
+    Semicolon,
+    Comma,
+    Star,
+    Arrow,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Position {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Position {
+    pub(crate) fn error(self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+/// Split `input` into tokens with their positions, and return the position
+/// of the end of the input along with them.
+fn tokenize(input: &str) -> result::Result<(Vec<(Token, Position)>, Position), ParseError> {
+    let mut tokens = Vec::new();
+    let mut position = Position { line: 1, column: 1 };
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        let start = position;
+        chars.next();
+        position.column += 1;
+
+        let token = match c {
+            '\n' => {
+                position.line += 1;
+                position.column = 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            ':' => Token::Colon,
+            ';' => Token::Semicolon,
+            ',' => Token::Comma,
+            '*' => Token::Star,
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                position.column += 1;
+                Token::Arrow
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                    position.column += 1;
+                }
+
+                if word.chars().all(|c| c.is_ascii_digit()) {
+                    Token::Number(word)
+                } else {
+                    Token::Name(word)
+                }
+            }
+            c => return Err(start.error(ParseErrorKind::UnexpectedCharacter(c))),
+        };
+        tokens.push((token, start));
+    }
+
+    Ok((tokens, position))
+}
+
+struct Parser {
+    tokens: Vec<(Token, Position)>,
+    next: usize,
+    end: Position,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    /// Position of the next token, or of the end of the input.
+    fn position(&self) -> Position {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |&(_, position)| position)
+    }
+
+    fn expect(&mut self, token: Token, expected: &'static str) -> result::Result<(), ParseError> {
+        if self.peek() == Some(&token) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(self.position().error(ParseErrorKind::Expected(expected)))
+        }
+    }
+
+    fn name(&mut self, expected: &'static str) -> result::Result<(String, Position), ParseError> {
+        let position = self.position();
+        match self.peek() {
+            Some(Token::Name(name)) => {
+                let name = name.clone();
+                self.next += 1;
+                Ok((name, position))
+            }
+            _ => Err(position.error(ParseErrorKind::Expected(expected))),
+        }
+    }
+
+    fn number(&mut self, expected: &'static str) -> result::Result<(u32, Position), ParseError> {
+        let position = self.position();
+        match self.peek() {
+            Some(Token::Number(text)) => {
+                let number = text
+                    .parse()
+                    .map_err(|_| position.error(ParseErrorKind::InvalidNumber(text.clone())))?;
+                self.next += 1;
+                Ok((number, position))
+            }
+            _ => Err(position.error(ParseErrorKind::Expected(expected))),
+        }
+    }
+
+    /// A possibly empty, comma separated list of places with optional
+    /// weights.
+    fn arcs(&mut self, net: &OwnedPetrinet) -> result::Result<Vec<(usize, u32)>, ParseError> {
+        let mut arcs = Vec::new();
+        if !matches!(self.peek(), Some(Token::Name(_))) {
+            return Ok(arcs);
+        }
+
+        loop {
+            let (name, position) = self.name("place name")?;
+            let place = net
+                .places()
+                .iter()
+                .position(|place| place.name() == name)
+                .ok_or_else(|| position.error(ParseErrorKind::UnknownPlace(name)))?;
+
+            let weight = if self.peek() == Some(&Token::Star) {
+                self.next += 1;
+                let (weight, position) = self.number("arc weight")?;
+                if weight == 0 {
+                    return Err(position.error(ParseErrorKind::ZeroWeight));
+                }
+                weight
+            } else {
+                1
+            };
+            arcs.push((place, weight));
+
+            if self.peek() != Some(&Token::Comma) {
+                return Ok(arcs);
+            }
+            self.next += 1;
+        }
+    }
+
+    fn statement(&mut self, net: &mut OwnedPetrinet) -> result::Result<(), ParseError> {
+        let (name, position) = self.name("place or transition name")?;
+        let declared = net.places().iter().any(|place| place.name() == name)
+            || net
+                .transitions()
+                .iter()
+                .any(|transition| transition.name() == Some(&name));
+        if declared {
+            return Err(position.error(ParseErrorKind::DuplicateName(name)));
+        }
+        self.expect(Token::Colon, "':'")?;
+
+        if let Some(Token::Number(_)) = self.peek() {
+            let (tokens, _) = self.number("token count")?;
+            net.add_place(tokens, &name);
+            return Ok(());
+        }
+
+        let inputs = self.arcs(net)?;
+        self.expect(Token::Arrow, "'->'")?;
+        let outputs = self.arcs(net)?;
+
+        let transition = net.add_transition(Some(&name));
+        for (place, weight) in inputs {
+            net.add_input(transition, place, weight).expect("valid arc");
+        }
+        for (place, weight) in outputs {
+            net.add_output(transition, place, weight)
+                .expect("valid arc");
+        }
+        Ok(())
+    }
+}
+
+/// Parse a net in the text format described in the module documentation.
+pub fn parse_net(input: &str) -> result::Result<OwnedPetrinet, ParseError> {
+    let (tokens, end) = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        next: 0,
+        end,
+    };
+
+    let mut net = OwnedPetrinet::new();
+    while parser.peek().is_some() {
+        parser.statement(&mut net)?;
+        if parser.peek().is_some() {
+            parser.expect(Token::Semicolon, "';'")?;
+        }
+    }
+
+    Ok(net)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_net() {
+        let mut net = parse_net(
+            "p1: 2; p2: 0;
+             p3: 0;
+             t1: p1 -> p2*2;
+             t2: p2*3, p1 -> p3;
+             source: -> p1",
+        )
+        .expect("valid net");
+
+        assert_eq!(vec![2, 0, 0], net.marking());
+        assert_eq!("p2", net.places()[1].name());
+        assert_eq!(3, net.transitions().len());
+        assert_eq!(Some("t2"), net.transitions()[1].name());
+        assert_eq!(3, net.transitions()[1].inputs()[0].weight());
+        assert_eq!(0, net.transitions()[1].inputs()[1].place());
+        assert!(net.transitions()[2].inputs().is_empty());
+
+        assert_eq!(Ok(()), net.fire(0));
+        assert_eq!(vec![1, 2, 0], net.marking());
+
+        assert_eq!(Ok(OwnedPetrinet::new()), parse_net(" \n "));
+    }
+
+    #[test]
+    fn test_parse_net_errors() {
+        let error = |input| parse_net(input).unwrap_err();
+
+        let unknown = error("p1: 1;\nt1: p1 -> p2;");
+        assert_eq!(
+            (2, 11, &ParseErrorKind::UnknownPlace("p2".to_string())),
+            (unknown.line(), unknown.column(), unknown.kind())
+        );
+        assert_eq!("2:11: unknown place p2", unknown.to_string());
+
+        assert_eq!("1:3: unexpected character '?'", error("p1? 1").to_string());
+        assert_eq!("1:9: expected ':'", error("p1: 1;p2").to_string());
+        assert_eq!("1:7: expected ';'", error("p1: 1 p2: 0").to_string());
+        assert_eq!("1:13: expected '->'", error("p1: 1; t: p1").to_string());
+        assert_eq!(
+            "1:14: arc weight must be greater than zero",
+            error("p1: 1; t: p1*0 ->").to_string()
+        );
+        assert_eq!(
+            "1:5: invalid number \"4294967296\"",
+            error("p1: 4294967296").to_string()
+        );
+        assert_eq!(
+            "1:8: p1 is declared twice",
+            error("p1: 1; p1: 2").to_string()
+        );
+        assert_eq!(
+            "1:1: expected place or transition name",
+            error(";").to_string()
+        );
+    }
+}
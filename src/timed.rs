@@ -0,0 +1,243 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::Petrinet;
+
+/// A petri net with deterministic timed transitions.
+///
+/// Once a transition gets enabled, it is scheduled to fire after its delay.
+/// If it gets disabled before its deadline, the scheduled firing is
+/// cancelled. Transitions without delay fire immediately. A transition whose
+/// deadline would be beyond `u64::MAX` is never scheduled.
+///
+/// The clock of the net, see `Petrinet::set_time`, follows the clock of the
+/// timed net, so timed guards decide about scheduling at the current time.
+/// They are evaluated whenever a transition fired, and when `advance_to`
+/// reaches its target time. A transition whose guard closes before its
+/// deadline doesn't fire.
+pub struct TimedPetrinet<'a> {
+    net: Petrinet<'a>,
+    time: u64,
+    deadlines: Vec<Option<u64>>,
+    events: BinaryHeap<Reverse<(u64, usize)>>,
+}
+
+impl<'a> TimedPetrinet<'a> {
+    /// Wrap `net` with the clock starting at 0. Transitions enabled in the
+    /// current marking are scheduled right away.
+    pub fn new(mut net: Petrinet<'a>) -> Self {
+        net.set_time(0.0);
+        let mut timed = Self {
+            deadlines: vec![None; net.transitions.len()],
+            net,
+            time: 0,
+            events: BinaryHeap::new(),
+        };
+        timed.update_schedule();
+        timed
+    }
+
+    pub fn net(&self) -> &Petrinet<'a> {
+        &self.net
+    }
+
+    /// The current value of the simulation clock.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Schedule newly enabled transitions and cancel disabled ones.
+    fn update_schedule(&mut self) {
+        for (i, transition) in self.net.transitions.iter().enumerate() {
+            match (transition.is_enabled(), self.deadlines[i]) {
+                (true, None) => {
+                    if let Some(deadline) = self.time.checked_add(transition.delay.unwrap_or(0)) {
+                        self.deadlines[i] = Some(deadline);
+                        self.events.push(Reverse((deadline, i)));
+                    }
+                }
+                (false, Some(_)) => self.deadlines[i] = None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Process all scheduled firings up to and including `time`, and advance
+    /// the clock to `time`. Returns the firings as pairs of firing time and
+    /// transition index. Firings scheduled for the same time are processed in
+    /// transition order.
+    ///
+    /// Cycles of transitions without delay keep firing without advancing the
+    /// clock, so this doesn't return for such nets.
+    pub fn advance_to(&mut self, time: u64) -> Vec<(u64, usize)> {
+        let mut firings = Vec::new();
+
+        loop {
+            while let Some(&Reverse((deadline, i))) = self.events.peek() {
+                if deadline > time {
+                    break;
+                }
+
+                self.events.pop();
+                if self.deadlines[i] != Some(deadline) {
+                    // cancelled
+                    continue;
+                }
+
+                self.time = deadline;
+                self.net.set_time(deadline as f64);
+                self.deadlines[i] = None;
+                if self.net.fire_at(i).is_ok() {
+                    firings.push((deadline, i));
+                }
+                self.update_schedule();
+            }
+
+            if time <= self.time {
+                break;
+            }
+
+            // timed guards may have opened meanwhile
+            self.time = time;
+            self.net.set_time(time as f64);
+            self.update_schedule();
+        }
+
+        firings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Arc, Place, Transition};
+
+    #[test]
+    fn test_timed_petrinet() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        let mut t1 = Transition::new();
+        t1.set_delay(5);
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.set_delay(3);
+        t2.add_input(&a2);
+        t2.add_output(&a3);
+
+        assert_eq!(Some(5), t1.delay());
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        let mut timed = TimedPetrinet::new(net);
+
+        assert!(timed.advance_to(4).is_empty());
+        assert_eq!(4, timed.time());
+        assert_eq!(vec![(5, 0)], timed.advance_to(7));
+        assert_eq!(vec![0, 1, 0], timed.net().marking());
+        assert_eq!(vec![(8, 1)], timed.advance_to(100));
+        assert_eq!(vec![0, 0, 1], timed.net().marking());
+        assert_eq!(100, timed.time());
+    }
+
+    #[test]
+    fn test_timed_petrinet_overflow() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // t2 gets enabled at time 1, when its deadline would overflow
+        let mut t1 = Transition::new();
+        t1.set_delay(1);
+        t1.add_input(&a1);
+        t1.add_output(&a2);
+        let mut t2 = Transition::new();
+        t2.set_delay(u64::MAX);
+        t2.add_input(&a2);
+        t2.add_output(&a1);
+
+        let mut net = Petrinet::new();
+        net.add_transition(t1);
+        net.add_transition(t2);
+        let mut timed = TimedPetrinet::new(net);
+
+        assert_eq!(vec![(1, 0)], timed.advance_to(10));
+        assert_eq!(10, timed.time());
+        assert_eq!(vec![0, 1], timed.net().marking());
+    }
+
+    #[test]
+    fn test_timed_petrinet_cancellation() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::new(0, "p2");
+        let p3 = Place::new(0, "p3");
+
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p3, 1).expect("weight greater than zero");
+
+        // slow and fast compete for the token on p1; once fast took it, the
+        // firing of slow is cancelled
+        let mut slow = Transition::new();
+        slow.set_delay(10);
+        slow.add_input(&a1);
+        slow.add_output(&a2);
+        let mut fast = Transition::new();
+        fast.set_delay(2);
+        fast.add_input(&a1);
+        fast.add_output(&a3);
+        // immediate transition consuming the token produced by fast
+        let mut sink = Transition::new();
+        sink.add_input(&a3);
+
+        let mut net = Petrinet::new();
+        net.add_transition(slow);
+        net.add_transition(fast);
+        net.add_transition(sink);
+        let mut timed = TimedPetrinet::new(net);
+
+        assert_eq!(vec![(2, 1), (2, 2)], timed.advance_to(20));
+        assert_eq!(vec![0, 0, 0], timed.net().marking(), "slow was cancelled");
+    }
+
+    #[test]
+    fn test_timed_guard() {
+        let p1 = Place::new(2, "p1");
+        let p2 = Place::new(0, "p2");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+
+        // a timeout from time 10 on, which moves a token after a delay of 1
+        // while p2 is empty
+        let mut timeout = Transition::new_with_timed_guard(|time, marking| {
+            time >= 10.0 && marking.tokens("p2") == Some(0)
+        });
+        timeout.set_delay(1);
+        timeout.add_input(&a1);
+        timeout.add_output(&a2);
+
+        let mut net = Petrinet::new();
+        net.add_transition(timeout);
+        assert!(!net.step(), "untimed stepping sees time 0");
+        net.set_time(10.0);
+        assert_eq!(10.0, net.time());
+        assert!(net.transitions()[0].is_enabled());
+        net.reset();
+
+        let mut timed = TimedPetrinet::new(net);
+        assert!(timed.advance_to(9).is_empty());
+        assert!(timed.advance_to(10).is_empty(), "scheduled for 11");
+        assert_eq!(vec![(11, 0)], timed.advance_to(30));
+        assert_eq!(vec![1, 1], timed.net().marking());
+        assert_eq!(30.0, timed.net().time());
+    }
+}
@@ -0,0 +1,150 @@
+use std::error;
+use std::fmt;
+use std::ptr;
+use std::result;
+
+use crate::Petrinet;
+
+/// A structural problem found by `Petrinet::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The place at this index is not connected to any transition.
+    UnconnectedPlace(usize),
+    /// The transition at this index has no arcs at all.
+    EmptyTransition(usize),
+    /// The arc weight is greater than the capacity of its place, so the arc
+    /// can never be satisfied.
+    WeightExceedsCapacity { transition: usize, place: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnconnectedPlace(place) => {
+                write!(f, "place {} is not connected to any transition", place)
+            }
+            Self::EmptyTransition(transition) => {
+                write!(f, "transition {} has no arcs", transition)
+            }
+            Self::WeightExceedsCapacity { transition, place } => write!(
+                f,
+                "arc between transition {} and place {} exceeds the capacity of the place",
+                transition, place
+            ),
+        }
+    }
+}
+
+impl error::Error for ValidationError {}
+
+impl Petrinet<'_> {
+    /// Check the net for common modeling mistakes: places without any arc,
+    /// transitions without any arc, and input, output or read arcs whose
+    /// weight exceeds the capacity of their place. All problems are
+    /// reported, in this order.
+    ///
+    /// Inhibitor arcs may exceed the capacity; they then never inhibit.
+    pub fn validate(&self) -> result::Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        for (p, place) in self.places.iter().enumerate() {
+            let connected = self
+                .transitions
+                .iter()
+                .any(|transition| transition.places().any(|q| ptr::eq(q, *place)));
+            if !connected {
+                errors.push(ValidationError::UnconnectedPlace(p));
+            }
+        }
+
+        for (t, transition) in self.transitions.iter().enumerate() {
+            if transition.places().next().is_none() {
+                errors.push(ValidationError::EmptyTransition(t));
+            }
+        }
+
+        for (t, transition) in self.transitions.iter().enumerate() {
+            let arcs = transition
+                .weighted_inputs()
+                .chain(
+                    transition
+                        .output_arcs
+                        .iter()
+                        .map(|arc| (arc.place, arc.weight)),
+                )
+                .chain(
+                    transition
+                        .read_arcs
+                        .iter()
+                        .map(|arc| (arc.place, arc.weight)),
+                );
+            for (place, weight) in arcs {
+                if place.capacity().is_some_and(|c| weight > c) {
+                    errors.push(ValidationError::WeightExceedsCapacity {
+                        transition: t,
+                        place: self.place_index(place).expect("registered place"),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arc, OwnedPetrinet, Place, Transition};
+
+    #[test]
+    fn test_validate() {
+        let p1 = Place::new(1, "p1");
+        let p2 = Place::with_capacity(0, "p2", 1);
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let a3 = Arc::new(&p2, 2).expect("weight greater than zero");
+
+        let mut t = Transition::new();
+        t.add_input(&a1);
+        t.add_output(&a2);
+        let mut net = Petrinet::new();
+        net.add_transition(t);
+        assert_eq!(Ok(()), net.validate());
+
+        let mut t = Transition::new();
+        t.add_output(&a3);
+        net.add_transition(t);
+        net.add_transition(Transition::new());
+        assert_eq!(
+            Err(vec![
+                ValidationError::EmptyTransition(2),
+                ValidationError::WeightExceedsCapacity {
+                    transition: 1,
+                    place: 1
+                },
+            ]),
+            net.validate()
+        );
+    }
+
+    #[test]
+    fn test_validate_unconnected_place() {
+        let mut net = OwnedPetrinet::new();
+        net.add_place(0, "p1");
+        let p2 = net.add_place(0, "p2");
+        let t = net.add_transition(None);
+        net.add_output(t, p2, 1).expect("valid arc");
+
+        net.with_net(|view| {
+            assert_eq!(
+                Err(vec![ValidationError::UnconnectedPlace(0)]),
+                view.validate()
+            );
+        });
+    }
+}
@@ -0,0 +1,286 @@
+//! Nets with token counts wider than `u32`, for models of large quantities.
+//!
+//! `WidePetrinet` owns its places and refers to them by index, like
+//! `OwnedPetrinet`, but token counts, arc weights and capacities are of a
+//! `Tokens` type such as `u64` or `u128`. Only input and output arcs and
+//! capacities are supported.
+
+use std::fmt;
+use std::result;
+
+use crate::{ArcError, FireError};
+
+/// An unsigned integer type for token counts, see `WidePetrinet`.
+pub trait Tokens: Copy + Ord + fmt::Debug + fmt::Display + Default {
+    /// `self + other`, or `None` on overflow.
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    /// `self - other`, or `None` if `other` is greater.
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_tokens {
+    ($($t:ty),*) => {
+        $(impl Tokens for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+        })*
+    };
+}
+
+impl_tokens!(u32, u64, u128);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WidePlace<T> {
+    name: String,
+    tokens: T,
+    capacity: Option<T>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct WideTransition<T> {
+    name: Option<String>,
+    inputs: Vec<(usize, T)>,
+    outputs: Vec<(usize, T)>,
+}
+
+/// A net whose token counts, arc weights and capacities are of type `T`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WidePetrinet<T> {
+    places: Vec<WidePlace<T>>,
+    transitions: Vec<WideTransition<T>>,
+}
+
+impl<T: Tokens> Default for WidePetrinet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Tokens> WidePetrinet<T> {
+    pub fn new() -> Self {
+        Self {
+            places: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Add a place and return its index.
+    pub fn add_place(&mut self, tokens: T, name: &str) -> usize {
+        self.places.push(WidePlace {
+            name: name.to_string(),
+            tokens,
+            capacity: None,
+        });
+        self.places.len() - 1
+    }
+
+    /// Add a place which never holds more than `capacity` tokens, like
+    /// `Place::with_capacity`, and return its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tokens` exceeds `capacity`.
+    pub fn add_place_with_capacity(&mut self, tokens: T, name: &str, capacity: T) -> usize {
+        assert!(tokens <= capacity, "tokens must not exceed the capacity");
+        let place = self.add_place(tokens, name);
+        self.places[place].capacity = Some(capacity);
+        place
+    }
+
+    /// Add a transition without arcs and return its index.
+    pub fn add_transition(&mut self, name: Option<&str>) -> usize {
+        self.transitions.push(WideTransition {
+            name: name.map(str::to_string),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        });
+        self.transitions.len() - 1
+    }
+
+    fn check_arc(
+        &self,
+        transition: usize,
+        place: usize,
+        weight: T,
+    ) -> result::Result<(), ArcError> {
+        if transition >= self.transitions.len() {
+            Err(ArcError::NoSuchTransition(transition))
+        } else if place >= self.places.len() {
+            Err(ArcError::NoSuchPlace(place))
+        } else if weight == T::default() {
+            Err(ArcError::ZeroWeight)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Add an arc from `place` to `transition`.
+    pub fn add_input(
+        &mut self,
+        transition: usize,
+        place: usize,
+        weight: T,
+    ) -> result::Result<(), ArcError> {
+        self.check_arc(transition, place, weight)?;
+        self.transitions[transition].inputs.push((place, weight));
+        Ok(())
+    }
+
+    /// Add an arc from `transition` to `place`.
+    pub fn add_output(
+        &mut self,
+        transition: usize,
+        place: usize,
+        weight: T,
+    ) -> result::Result<(), ArcError> {
+        self.check_arc(transition, place, weight)?;
+        self.transitions[transition].outputs.push((place, weight));
+        Ok(())
+    }
+
+    pub fn place_count(&self) -> usize {
+        self.places.len()
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if there is no place at index `place`.
+    pub fn place_name(&self, place: usize) -> &str {
+        &self.places[place].name
+    }
+
+    /// # Panics
+    ///
+    /// Panics if there is no transition at index `transition`.
+    pub fn transition_name(&self, transition: usize) -> Option<&str> {
+        self.transitions[transition].name.as_deref()
+    }
+
+    /// The token counts of all places, in index order.
+    pub fn marking(&self) -> Vec<T> {
+        self.places.iter().map(|place| place.tokens).collect()
+    }
+
+    /// The marking after firing the transition at `transition`, or the
+    /// reason it can't fire.
+    fn successor(&self, transition: usize) -> result::Result<Vec<T>, FireError> {
+        let transition = self
+            .transitions
+            .get(transition)
+            .ok_or(FireError::NoSuchTransition(transition))?;
+
+        let mut marking = self.marking();
+        for &(place, weight) in &transition.inputs {
+            marking[place] = marking[place]
+                .checked_sub(weight)
+                .ok_or(FireError::NotEnabled)?;
+        }
+        for &(place, weight) in &transition.outputs {
+            marking[place] = marking[place]
+                .checked_add(weight)
+                .ok_or(FireError::Overflow)?;
+        }
+
+        let within_capacity = self
+            .places
+            .iter()
+            .zip(&marking)
+            .all(|(place, &tokens)| place.capacity.is_none_or(|capacity| tokens <= capacity));
+        if within_capacity {
+            Ok(marking)
+        } else {
+            Err(FireError::NotEnabled)
+        }
+    }
+
+    /// A transition is enabled if every input place holds at least the
+    /// summed weight of its input arcs, and no capacity would be exceeded
+    /// after firing. Returns `false` if there is no transition at
+    /// `transition`. Like for `Transition::is_enabled`, an overflowing token
+    /// count doesn't disable a transition, but makes `fire` fail.
+    pub fn is_enabled(&self, transition: usize) -> bool {
+        !matches!(
+            self.successor(transition),
+            Err(FireError::NotEnabled) | Err(FireError::NoSuchTransition(_))
+        )
+    }
+
+    /// Indices of all transitions which are enabled in the current marking.
+    pub fn enabled_transitions(&self) -> Vec<usize> {
+        (0..self.transitions.len())
+            .filter(|&t| self.is_enabled(t))
+            .collect()
+    }
+
+    /// Fire the transition at `transition`. Fails without changing the
+    /// marking if there is no such transition, it is not enabled, or a token
+    /// count would exceed the largest value of `T`.
+    pub fn fire(&mut self, transition: usize) -> result::Result<(), FireError> {
+        let marking = self.successor(transition)?;
+        for (place, tokens) in self.places.iter_mut().zip(marking) {
+            place.tokens = tokens;
+        }
+        Ok(())
+    }
+
+    /// Fire the first transition which can fire. Returns whether a
+    /// transition fired.
+    pub fn step(&mut self) -> bool {
+        (0..self.transitions.len()).any(|t| self.fire(t).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_petrinet() {
+        let mut net = WidePetrinet::<u64>::new();
+        let stock = net.add_place(10_000_000_000, "stock");
+        let shipped = net.add_place_with_capacity(0, "shipped", 20_000_000_000);
+        let ship = net.add_transition(Some("ship"));
+        net.add_input(ship, stock, 6_000_000_000)
+            .expect("valid arc");
+        net.add_output(ship, shipped, 12_000_000_000)
+            .expect("valid arc");
+
+        assert_eq!(Err(ArcError::ZeroWeight), net.add_input(ship, stock, 0));
+        assert_eq!(Err(ArcError::NoSuchPlace(2)), net.add_input(ship, 2, 1));
+        assert_eq!((2, 1), (net.place_count(), net.transition_count()));
+        assert_eq!(
+            ("shipped", Some("ship")),
+            (net.place_name(1), net.transition_name(0))
+        );
+
+        assert_eq!(vec![0], net.enabled_transitions());
+        assert!(net.step());
+        assert_eq!(vec![4_000_000_000, 12_000_000_000], net.marking());
+        assert!(!net.is_enabled(ship), "not enough stock");
+
+        net.add_output(ship, stock, 1).expect("valid arc");
+        net.places[stock].tokens = 7_000_000_000;
+        assert!(!net.is_enabled(ship), "shipped would exceed its capacity");
+        assert_eq!(Err(FireError::NotEnabled), net.fire(ship));
+        assert_eq!(Err(FireError::NoSuchTransition(1)), net.fire(1));
+        assert!(!net.is_enabled(1));
+
+        let mut net = WidePetrinet::<u128>::new();
+        let p = net.add_place(u128::MAX, "p");
+        let source = net.add_transition(None);
+        net.add_output(source, p, 1).expect("valid arc");
+        assert!(net.is_enabled(source), "overflow doesn't disable");
+        assert_eq!(Err(FireError::Overflow), net.fire(source));
+        assert_eq!(vec![u128::MAX], net.marking());
+    }
+}
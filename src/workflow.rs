@@ -0,0 +1,240 @@
+//! Workflow nets, which model business processes from a single source place
+//! to a single sink place, and their soundness.
+
+use crate::Petrinet;
+
+impl Petrinet<'_> {
+    /// The source and sink place of a workflow net, or `None` if the net is
+    /// no workflow net.
+    fn workflow_places(&self) -> Option<(usize, usize)> {
+        let places = self.places.len();
+        let sources: Vec<usize> = (0..places)
+            .filter(|&p| self.place_preset(p).is_empty())
+            .collect();
+        let sinks: Vec<usize> = (0..places)
+            .filter(|&p| self.place_postset(p).is_empty())
+            .collect();
+        let (source, sink) = match (sources.as_slice(), sinks.as_slice()) {
+            (&[source], &[sink]) if source != sink => (source, sink),
+            _ => return None,
+        };
+
+        // nodes are the places followed by the transitions, so the places
+        // after place `p` are at indices `places + t` for `t` in p•
+        let successors = |node: usize| {
+            if node < places {
+                self.place_postset(node)
+                    .into_iter()
+                    .map(|t| places + t)
+                    .collect()
+            } else {
+                self.transition_postset(node - places)
+            }
+        };
+        let predecessors = |node: usize| {
+            if node < places {
+                self.place_preset(node)
+                    .into_iter()
+                    .map(|t| places + t)
+                    .collect()
+            } else {
+                self.transition_preset(node - places)
+            }
+        };
+
+        let nodes = places + self.transitions.len();
+        let covers_all = |start: usize, next: &dyn Fn(usize) -> Vec<usize>| {
+            let mut visited = vec![false; nodes];
+            visited[start] = true;
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                for neighbour in next(node) {
+                    if !visited[neighbour] {
+                        visited[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+            visited.into_iter().all(|visited| visited)
+        };
+
+        if covers_all(source, &successors) && covers_all(sink, &predecessors) {
+            Some((source, sink))
+        } else {
+            None
+        }
+    }
+
+    /// Whether the net is a workflow net: exactly one place, the source, has
+    /// no incoming arc, exactly one other place, the sink, has no outgoing
+    /// arc, and every place and transition lies on a path from the source to
+    /// the sink.
+    ///
+    /// Like presets and postsets, only input and output arcs are taken into
+    /// account.
+    pub fn is_workflow_net(&self) -> bool {
+        self.workflow_places().is_some()
+    }
+
+    /// Whether the net is a sound workflow net, after van der Aalst. Starting
+    /// with a single token on the source place,
+    ///
+    /// - a single token on the sink place can be reached from every reachable
+    ///   marking (option to complete),
+    /// - every reachable marking with a token on the sink place holds no
+    ///   other token (proper completion), and
+    /// - every transition is enabled in some reachable marking (no dead
+    ///   transitions).
+    ///
+    /// The reachability graph is built with at most `max_states` states.
+    /// Sound nets are bounded, so if the state space is truncated, the net is
+    /// reported as not sound. The marking of the net is restored afterwards.
+    pub fn is_sound(&mut self, max_states: usize) -> bool {
+        let (source, sink) = match self.workflow_places() {
+            Some(places) => places,
+            None => return false,
+        };
+
+        let marking = self.snapshot();
+        let mut start = vec![0; self.places.len()];
+        start[source] = 1;
+        self.restore(&start);
+        let graph = self.reachability_graph(max_states);
+        let enabled = self.enabled_in_states(&graph);
+        self.restore(&marking);
+
+        if !graph.is_complete() {
+            return false;
+        }
+
+        let mut end = vec![0; self.places.len()];
+        end[sink] = 1;
+        let proper_completion = graph
+            .markings()
+            .iter()
+            .all(|marking| marking[sink] == 0 || *marking == end);
+        let no_dead_transitions =
+            (0..self.transitions.len()).all(|t| enabled.iter().any(|state| state[t]));
+        let option_to_complete = graph
+            .state(&end)
+            .is_some_and(|end| graph.states_reaching(Some(end)).into_iter().all(|r| r));
+
+        proper_completion && no_dead_transitions && option_to_complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Arc, Petrinet, Place, Transition};
+
+    #[test]
+    fn test_is_workflow_net() {
+        let i = Place::new(0, "i");
+        let p = Place::new(0, "p");
+        let o = Place::new(0, "o");
+        let q = Place::new(0, "q");
+        let ai = Arc::new(&i, 1).expect("weight greater than zero");
+        let ap = Arc::new(&p, 1).expect("weight greater than zero");
+        let ao = Arc::new(&o, 1).expect("weight greater than zero");
+        let aq = Arc::new(&q, 1).expect("weight greater than zero");
+
+        let mut start = Transition::new();
+        start.add_input(&ai);
+        start.add_output(&ap);
+        let mut finish = Transition::new();
+        finish.add_input(&ap);
+        finish.add_output(&ao);
+        let mut net = Petrinet::new();
+        net.add_transition(start);
+        net.add_transition(finish);
+        assert!(net.is_workflow_net());
+
+        // a loop on p keeps the net a workflow net
+        let mut retry = Transition::new();
+        retry.add_input(&ap);
+        retry.add_output(&ap);
+        net.add_transition(retry);
+        assert!(net.is_workflow_net());
+
+        // a second sink
+        let mut leak = Transition::new();
+        leak.add_input(&ap);
+        leak.add_output(&aq);
+        net.add_transition(leak);
+        assert!(!net.is_workflow_net());
+
+        // a cycle which can't be entered from the source
+        let mut cycle = Petrinet::new();
+        for (from, to) in &[(&ai, &ao), (&ap, &aq), (&aq, &ap)] {
+            let mut t = Transition::new();
+            t.add_input(from);
+            t.add_output(to);
+            cycle.add_transition(t);
+        }
+        assert!(!cycle.is_workflow_net());
+    }
+
+    #[test]
+    fn test_is_sound() {
+        let i = Place::new(0, "i");
+        let p1 = Place::new(0, "p1");
+        let p2 = Place::new(0, "p2");
+        let o = Place::new(0, "o");
+        let ai = Arc::new(&i, 1).expect("weight greater than zero");
+        let a1 = Arc::new(&p1, 1).expect("weight greater than zero");
+        let a2 = Arc::new(&p2, 1).expect("weight greater than zero");
+        let ao = Arc::new(&o, 1).expect("weight greater than zero");
+
+        // split into two parallel tasks and join them again
+        let mut split = Transition::new();
+        split.add_input(&ai);
+        split.add_output(&a1);
+        split.add_output(&a2);
+        let mut join = Transition::new();
+        join.add_input(&a1);
+        join.add_input(&a2);
+        join.add_output(&ao);
+        let mut net = Petrinet::new();
+        net.add_transition(split);
+        net.add_transition(join);
+        assert!(net.is_sound(100));
+        assert!(!net.is_sound(2), "truncated state space");
+        assert_eq!(vec![0, 0, 0, 0], net.marking(), "marking is restored");
+
+        // finishing on p1 alone leaves a token on p2 behind
+        let mut shortcut = Transition::new();
+        shortcut.add_input(&a1);
+        shortcut.add_output(&ao);
+        net.add_transition(shortcut);
+        assert!(!net.is_sound(100), "no proper completion");
+    }
+
+    #[test]
+    fn test_is_sound_dead_transition() {
+        let i = Place::new(0, "i");
+        let p = Place::new(0, "p");
+        let o = Place::new(0, "o");
+        let ai = Arc::new(&i, 1).expect("weight greater than zero");
+        let ap = Arc::new(&p, 1).expect("weight greater than zero");
+        let ap2 = Arc::new(&p, 2).expect("weight greater than zero");
+        let ao = Arc::new(&o, 1).expect("weight greater than zero");
+
+        let mut start = Transition::new();
+        start.add_input(&ai);
+        start.add_output(&ap);
+        let mut finish = Transition::new();
+        finish.add_input(&ap);
+        finish.add_output(&ao);
+        // p never holds two tokens
+        let mut dead = Transition::new();
+        dead.add_input(&ap2);
+        dead.add_output(&ao);
+        let mut net = Petrinet::new();
+        net.add_transition(start);
+        net.add_transition(finish);
+        net.add_transition(dead);
+
+        assert!(net.is_workflow_net());
+        assert!(!net.is_sound(100));
+    }
+}